@@ -0,0 +1,181 @@
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+use crate::ipp::{IppAttribute, IppValue};
+use crate::{
+    Platform,
+    common::{
+        base::{errors::PrintersError, printer::Printer},
+        traits::platform::PlatformActions,
+    },
+};
+
+/**
+ * PrinterConfig describes a queue to create or the changes to apply to a queue,
+ * fields left as None are not sent and keep their current value
+ */
+#[derive(Debug, Clone)]
+pub struct PrinterConfig<'a> {
+    /**
+     * Device URI of the queue, ex: ipp://10.0.0.5/ipp/print or socket://10.0.0.6
+     */
+    pub device_uri: Option<&'a str>,
+
+    /**
+     * Driver of the queue as a PPD name, "everywhere" creates a driverless IPP Everywhere queue
+     */
+    pub model: Option<&'a str>,
+
+    /**
+     * Visible description of the queue (printer-info)
+     */
+    pub description: Option<&'a str>,
+
+    /**
+     * Location of the printer
+     */
+    pub location: Option<&'a str>,
+
+    /**
+     * Share the queue on the network
+     */
+    pub shared: Option<bool>,
+
+    /**
+     * Enable the queue and accept jobs (as lpadmin -E), or disable it and reject jobs
+     */
+    pub enabled: Option<bool>,
+
+    /**
+     * Default job options of the queue, ex: ("media", "iso_a4_210x297mm")
+     */
+    pub default_options: &'a [(&'a str, &'a str)],
+}
+
+impl PrinterConfig<'_> {
+    pub fn none() -> Self {
+        PrinterConfig {
+            device_uri: None,
+            model: None,
+            description: None,
+            location: None,
+            shared: None,
+            enabled: None,
+            default_options: &[],
+        }
+    }
+
+    /**
+     * Printer attributes of a CUPS-Add-Modify-Printer request
+     */
+    #[cfg(any(target_family = "unix", feature = "ipp-client"))]
+    pub(crate) fn printer_attributes(&self) -> Vec<IppAttribute> {
+        let mut attributes = Vec::new();
+
+        if let Some(device_uri) = self.device_uri {
+            attributes.push(IppAttribute::new(
+                "device-uri",
+                IppValue::Uri(device_uri.to_string()),
+            ));
+        }
+
+        if let Some(model) = self.model {
+            attributes.push(IppAttribute::new(
+                "ppd-name",
+                IppValue::Name(model.to_string()),
+            ));
+        }
+
+        if let Some(description) = self.description {
+            attributes.push(IppAttribute::new(
+                "printer-info",
+                IppValue::Text(description.to_string()),
+            ));
+        }
+
+        if let Some(location) = self.location {
+            attributes.push(IppAttribute::new(
+                "printer-location",
+                IppValue::Text(location.to_string()),
+            ));
+        }
+
+        if let Some(shared) = self.shared {
+            attributes.push(IppAttribute::new(
+                "printer-is-shared",
+                IppValue::Boolean(shared),
+            ));
+        }
+
+        if let Some(enabled) = self.enabled {
+            attributes.push(IppAttribute::new(
+                "printer-state",
+                IppValue::Enum(if enabled { 3 } else { 5 }),
+            ));
+            attributes.push(IppAttribute::new(
+                "printer-is-accepting-jobs",
+                IppValue::Boolean(enabled),
+            ));
+        }
+
+        for (name, value) in self.default_options {
+            attributes.push(IppAttribute::new(
+                &format!("{name}-default"),
+                IppValue::Name(value.to_string()),
+            ));
+        }
+
+        attributes
+    }
+}
+
+/**
+ * Create a queue, the config must have a device URI.
+ * New queues are enabled unless the config says otherwise.
+ */
+pub fn add_printer(printer_name: &str, config: PrinterConfig) -> Result<Printer, PrintersError> {
+    if config.device_uri.is_none() {
+        return Err(PrintersError::error(
+            "a device uri is required to add a printer",
+        ));
+    }
+
+    if Platform::get_printer_by_name(printer_name).is_some() {
+        return Err(PrintersError::error(format!(
+            "printer {printer_name} already exists"
+        )));
+    }
+
+    let config = PrinterConfig {
+        enabled: config.enabled.or(Some(true)),
+        ..config
+    };
+
+    Platform::add_modify_printer(printer_name, &config)?;
+    find_printer(printer_name)
+}
+
+/**
+ * Change an existing queue, only the fields set on the config are changed
+ */
+pub fn modify_printer(printer_name: &str, config: PrinterConfig) -> Result<Printer, PrintersError> {
+    if Platform::get_printer_by_name(printer_name).is_none() {
+        return Err(PrintersError::error(format!(
+            "printer {printer_name} not found"
+        )));
+    }
+
+    Platform::add_modify_printer(printer_name, &config)?;
+    find_printer(printer_name)
+}
+
+/**
+ * Delete a queue and its jobs
+ */
+pub fn delete_printer(printer_name: &str) -> Result<(), PrintersError> {
+    Platform::delete_printer(printer_name)
+}
+
+fn find_printer(printer_name: &str) -> Result<Printer, PrintersError> {
+    Platform::get_printer_by_name(printer_name).ok_or_else(|| {
+        PrintersError::error(format!("printer {printer_name} not found after the change"))
+    })
+}
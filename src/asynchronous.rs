@@ -0,0 +1,286 @@
+use std::{
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    common::{
+        base::{
+            errors::PrintersError,
+            job::{
+                OwnedPrinterJobOptions, POLL_INTERVAL, PrintJobHandle, PrinterJob, PrinterJobState,
+            },
+            printer::Printer,
+        },
+        converters::Converter,
+    },
+    server::{self, with_server},
+};
+
+/**
+ * Operation of the lib run on its own thread, as the platform apis block.
+ * Tasks work with any executor and start when first polled, dropping a task before does nothing.
+ * A started operation always runs to completion and dropping its task only discards the result,
+ * so a job is never left half submitted
+ */
+#[must_use = "tasks do nothing unless polled"]
+pub struct Task<T> {
+    operation: Option<Box<dyn FnOnce() -> T + Send>>,
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+struct Shared<T> {
+    result: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+impl<T: Send + 'static> Task<T> {
+    /**
+     * Wrap an operation, sent to the server current when the task is created
+     */
+    fn new(operation: impl FnOnce() -> T + Send + 'static) -> Self {
+        let server = server::current();
+        Task {
+            operation: Some(Box::new(move || with_server(server.as_ref(), operation))),
+            shared: Arc::new(Mutex::new(Shared {
+                result: None,
+                waker: None,
+            })),
+        }
+    }
+}
+
+impl<T: Send + 'static> Future for Task<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        {
+            let mut shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(result) = shared.result.take() {
+                // Panics of the operation are raised where the task is awaited
+                return Poll::Ready(result.unwrap_or_else(|panic| panic::resume_unwind(panic)));
+            }
+            shared.waker = Some(cx.waker().clone());
+        }
+
+        if let Some(operation) = self.operation.take() {
+            let shared = self.shared.clone();
+            thread::spawn(move || {
+                let result = panic::catch_unwind(AssertUnwindSafe(operation));
+                let mut shared = shared.lock().unwrap_or_else(|e| e.into_inner());
+                shared.result = Some(result);
+                if let Some(waker) = shared.waker.take() {
+                    waker.wake();
+                }
+            });
+        }
+
+        Poll::Pending
+    }
+}
+
+/**
+ * Return all available printers on a system
+ */
+pub fn get_printers() -> Task<Vec<Printer>> {
+    Task::new(crate::get_printers)
+}
+
+/**
+ * If you know the printer name, you can try to get the printer directly
+ */
+pub fn get_printer_by_name(printer_name: &str) -> Task<Option<Printer>> {
+    let printer_name = printer_name.to_string();
+    Task::new(move || crate::get_printer_by_name(&printer_name))
+}
+
+/**
+ * Return the default system printer
+ */
+pub fn get_default_printer() -> Task<Option<Printer>> {
+    Task::new(crate::get_default_printer)
+}
+
+/**
+ * Return a job of any printer by id
+ */
+pub fn get_job(job_id: u64) -> Task<Option<PrinterJob>> {
+    Task::new(move || crate::get_job(job_id))
+}
+
+impl Printer {
+    /**
+     * Print bytes without blocking, see Printer::print.
+     * The options are copied, so they can borrow values local to the caller
+     */
+    pub fn print_async(
+        &self,
+        buffer: impl Into<Vec<u8>>,
+        options: impl Into<OwnedPrinterJobOptions>,
+    ) -> Task<Result<PrintJobHandle, PrintersError>> {
+        let (printer, buffer, options) = (self.clone(), buffer.into(), options.into());
+        Task::new(move || options.with_options(|options| printer.print(&buffer, options)))
+    }
+
+    /**
+     * Print a file without blocking, see Printer::print_file.
+     * The options are copied, so they can borrow values local to the caller
+     */
+    pub fn print_file_async(
+        &self,
+        file_path: &str,
+        options: impl Into<OwnedPrinterJobOptions>,
+    ) -> Task<Result<PrintJobHandle, PrintersError>> {
+        let (printer, file_path, options) = (self.clone(), file_path.to_string(), options.into());
+        Task::new(move || options.with_options(|options| printer.print_file(&file_path, options)))
+    }
+
+    /**
+     * Return active jobs without blocking
+     */
+    pub fn get_active_jobs_async(&self) -> Task<Vec<PrinterJob>> {
+        let printer = self.clone();
+        Task::new(move || printer.get_active_jobs())
+    }
+
+    /**
+     * Return the job history without blocking
+     */
+    pub fn get_job_history_async(&self) -> Task<Vec<PrinterJob>> {
+        let printer = self.clone();
+        Task::new(move || printer.get_job_history())
+    }
+
+    /**
+     * Return a job of the printer by id without blocking
+     */
+    pub fn get_job_async(&self, job_id: u64) -> Task<Option<PrinterJob>> {
+        let printer = self.clone();
+        Task::new(move || printer.get_job(job_id))
+    }
+
+    /**
+     * Pause (hold) a job without blocking
+     */
+    pub fn pause_job_async(&self, job_id: u64) -> Task<Result<(), PrintersError>> {
+        let printer = self.clone();
+        Task::new(move || printer.pause_job(job_id))
+    }
+
+    /**
+     * Resume a paused job without blocking
+     */
+    pub fn resume_job_async(&self, job_id: u64) -> Task<Result<(), PrintersError>> {
+        let printer = self.clone();
+        Task::new(move || printer.resume_job(job_id))
+    }
+
+    /**
+     * Restart a job without blocking
+     */
+    pub fn restart_job_async(&self, job_id: u64) -> Task<Result<(), PrintersError>> {
+        let printer = self.clone();
+        Task::new(move || printer.restart_job(job_id))
+    }
+
+    /**
+     * Release a held job without blocking
+     */
+    pub fn release_job_async(&self, job_id: u64) -> Task<Result<(), PrintersError>> {
+        let printer = self.clone();
+        Task::new(move || printer.release_job(job_id))
+    }
+
+    /**
+     * Cancel a job without blocking
+     */
+    pub fn cancel_job_async(&self, job_id: u64) -> Task<Result<(), PrintersError>> {
+        let printer = self.clone();
+        Task::new(move || printer.cancel_job(job_id))
+    }
+}
+
+impl PrintJobHandle {
+    /**
+     * Cancel the job without blocking
+     */
+    pub fn cancel_async(&self) -> Task<Result<(), PrintersError>> {
+        let handle = self.clone();
+        Task::new(move || handle.cancel())
+    }
+
+    /**
+     * Pause (hold) the job without blocking
+     */
+    pub fn pause_async(&self) -> Task<Result<(), PrintersError>> {
+        let handle = self.clone();
+        Task::new(move || handle.pause())
+    }
+
+    /**
+     * Resume a paused job without blocking
+     */
+    pub fn resume_async(&self) -> Task<Result<(), PrintersError>> {
+        let handle = self.clone();
+        Task::new(move || handle.resume())
+    }
+
+    /**
+     * Query the server for the current job state without blocking.
+     * The handle is only updated when the future completes
+     */
+    pub async fn refresh_async(&mut self) -> Result<PrinterJobState, PrintersError> {
+        let mut handle = self.clone();
+        let (handle, state) = Task::new(move || {
+            let state = handle.refresh();
+            (handle, state)
+        })
+        .await;
+        *self = handle;
+        state
+    }
+
+    /**
+     * Wait until the job is completed, cancelled or aborted without blocking.
+     * The state is polled between delays, dropping the future stops the polling
+     */
+    pub async fn wait_until_done_async(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<PrinterJobState, PrintersError> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let state = self.refresh_async().await?;
+            if state.is_terminal() {
+                return Ok(state);
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(self.timeout_error(timeout));
+            }
+
+            let delay = remaining.min(POLL_INTERVAL);
+            Task::new(move || thread::sleep(delay)).await;
+        }
+    }
+}
+
+impl Converter {
+    /**
+     * Convert the contents of a byte array without blocking, see Converter::convert
+     */
+    pub fn convert_async(
+        &self,
+        buffer: impl Into<Vec<u8>>,
+    ) -> Task<Result<Vec<u8>, PrintersError>> {
+        let (converter, buffer) = (self.clone(), buffer.into());
+        Task::new(move || converter.convert(&buffer))
+    }
+}
@@ -1,4 +1,7 @@
+pub mod capabilities;
 pub mod errors;
 pub mod job;
 pub mod options;
 pub mod printer;
+pub mod retry;
+pub mod supplies;
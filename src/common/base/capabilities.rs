@@ -0,0 +1,157 @@
+use std::ops::RangeInclusive;
+
+use super::options::{PrintColorMode, PrintFinishing, PrintResolution, PrintSides};
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+use crate::{
+    common::base::errors::PrintersError,
+    ipp::{IppAttributeGroup, IppGroupTag, IppMessage, IppOperation, IppResolutionUnits, IppValue},
+};
+
+/**
+ * Printer attributes requested to build the capabilities
+ */
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+pub(crate) const CAPABILITIES_ATTRIBUTES: &[&str] = &[
+    "media-supported",
+    "sides-supported",
+    "print-color-mode-supported",
+    "color-supported",
+    "printer-resolution-supported",
+    "document-format-supported",
+    "copies-supported",
+    "finishings-supported",
+];
+
+/**
+ * PrinterCapabilities describes what a printer supports, as reported by the system
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrinterCapabilities {
+    /**
+     * Supported media names (ex: iso_a4_210x297mm on cups, A4 on windows)
+     */
+    pub media: Vec<String>,
+
+    /**
+     * Supported sides
+     */
+    pub sides: Vec<PrintSides>,
+
+    /**
+     * Supported color modes
+     */
+    pub color_modes: Vec<PrintColorMode>,
+
+    /**
+     * Supported resolutions
+     */
+    pub resolutions: Vec<PrintResolution>,
+
+    /**
+     * Supported document formats as mime types (empty when unknown)
+     */
+    pub document_formats: Vec<String>,
+
+    /**
+     * Supported range of copies
+     */
+    pub copies: RangeInclusive<u32>,
+
+    /**
+     * Supported finishings
+     */
+    pub finishings: Vec<PrintFinishing>,
+}
+
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+impl PrinterCapabilities {
+    /**
+     * Build the capabilities from a Get-Printer-Attributes response
+     */
+    pub(crate) fn from_ipp_response(response: &IppMessage) -> Result<Self, PrintersError> {
+        if !response.is_successful() {
+            return Err(PrintersError::from_response(
+                IppOperation::GetPrinterAttributes,
+                response,
+                PrintersError::error,
+            ));
+        }
+
+        response
+            .group(IppGroupTag::Printer)
+            .map(Self::from_ipp_attributes)
+            .ok_or_else(|| PrintersError::error("printer attributes not found"))
+    }
+
+    fn from_ipp_attributes(attributes: &IppAttributeGroup) -> Self {
+        let mut color_modes: Vec<PrintColorMode> = attributes
+            .strings("print-color-mode-supported")
+            .iter()
+            .filter_map(|v| PrintColorMode::from_keyword(v))
+            .collect();
+
+        // Older servers only report if the printer is able to print color
+        if color_modes.is_empty() {
+            color_modes.push(PrintColorMode::Monochrome);
+            if attributes
+                .value("color-supported")
+                .and_then(|v| v.as_bool())
+                .unwrap_or_default()
+            {
+                color_modes.push(PrintColorMode::Color);
+            }
+        }
+
+        let copies = match attributes.value("copies-supported") {
+            Some(IppValue::RangeOfInteger { lower, upper }) => {
+                (*lower).max(1) as u32..=(*upper).max(1) as u32
+            }
+            _ => 1..=1,
+        };
+
+        PrinterCapabilities {
+            media: attributes.strings("media-supported"),
+            sides: attributes
+                .strings("sides-supported")
+                .iter()
+                .filter_map(|v| PrintSides::from_keyword(v))
+                .collect(),
+            color_modes,
+            resolutions: attributes
+                .get("printer-resolution-supported")
+                .map(|a| a.values.iter().filter_map(to_resolution).collect())
+                .unwrap_or_default(),
+            document_formats: attributes.strings("document-format-supported"),
+            copies,
+            finishings: attributes
+                .get("finishings-supported")
+                .map(|a| {
+                    a.values
+                        .iter()
+                        .filter_map(|v| v.as_i32())
+                        .map(PrintFinishing::from_value)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+fn to_resolution(value: &IppValue) -> Option<PrintResolution> {
+    let IppValue::Resolution {
+        cross_feed,
+        feed,
+        units,
+    } = value
+    else {
+        return None;
+    };
+
+    let to_dpi = |v: i32| match units {
+        IppResolutionUnits::DotsPerCentimeter => (v as u32 * 254 + 50) / 100,
+        _ => v as u32,
+    };
+
+    Some(PrintResolution::new(to_dpi(*cross_feed), to_dpi(*feed)))
+}
@@ -1,19 +1,49 @@
-use std::backtrace::Backtrace;
+use std::{
+    backtrace::Backtrace,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io,
+};
+
+use crate::ipp::IppStatusCode;
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+use crate::ipp::{IppGroupTag, IppMessage, IppOperation};
 
 #[derive(Debug)]
 pub struct PrintersError {
     /**
-     * Specific error message
+     * Specific error message, with the message of the server or of the platform if available
      */
     pub message: String,
     /**
      * Generic failure idenfier
      */
     pub failure: PrintersFailure,
+    /**
+     * Error code of the platform: the status of cupsLastError or of the IPP response,
+     * GetLastError on windows or the errno of an io error
+     */
+    pub code: Option<i32>,
+    /**
+     * IPP status answered by the server (ex: client-error-not-found, server-error-busy)
+     */
+    pub status: Option<IppStatusCode>,
+    /**
+     * Operation or platform function that failed (ex: Print-Job, cupsCreateJob, SetJobW)
+     */
+    pub operation: Option<Box<str>>,
+    /**
+     * Times the operation was attempted, greater than 1 when retried by a RetryPolicy
+     */
+    pub attempts: u16,
     /**
      * Available backtrace
      */
     pub backtrace: Backtrace,
+    /**
+     * Underlying error
+     */
+    source: Option<Box<dyn Error + Send + Sync>>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -23,6 +53,8 @@ pub enum PrintersFailure {
     GenericFailure,
     ConverterFailure,
     JobFailure,
+    NotAcceptingJobs,
+    AuthenticationRequired,
 }
 
 impl PrintersError {
@@ -33,7 +65,12 @@ impl PrintersError {
         Self {
             failure,
             message: error.to_string(),
+            code: None,
+            status: None,
+            operation: None,
+            attempts: 1,
             backtrace: Backtrace::capture(),
+            source: None,
         }
     }
 
@@ -65,10 +102,112 @@ impl PrintersError {
         Self::new(error, PrintersFailure::JobFailure)
     }
 
+    pub fn not_accepting_jobs_error<E>(error: E) -> Self
+    where
+        E: std::fmt::Display,
+    {
+        Self::new(error, PrintersFailure::NotAcceptingJobs)
+    }
+
+    pub fn authentication_error<E>(error: E) -> Self
+    where
+        E: std::fmt::Display,
+    {
+        Self::new(error, PrintersFailure::AuthenticationRequired)
+    }
+
     pub fn error<E>(error: E) -> Self
     where
         E: std::fmt::Display,
     {
         Self::new(error, PrintersFailure::GenericFailure)
     }
+
+    /**
+     * Set the error code of the platform
+     */
+    pub fn with_code(mut self, code: i32) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /**
+     * Set the IPP status answered by the server
+     */
+    pub fn with_status(mut self, status: IppStatusCode) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /**
+     * Set the operation that failed
+     */
+    pub fn with_operation(mut self, operation: &str) -> Self {
+        self.operation = Some(operation.into());
+        self
+    }
+
+    /**
+     * Set the underlying error, returned by source
+     */
+    pub fn with_source<E>(mut self, source: E) -> Self
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    /**
+     * Error of a failed IPP response, with its status and the status-message of the server.
+     * The failure is given by the constructor (ex: PrintersError::job_error)
+     */
+    #[cfg(any(target_family = "unix", feature = "ipp-client"))]
+    pub(crate) fn from_response(
+        operation: IppOperation,
+        response: &IppMessage,
+        error: fn(String) -> Self,
+    ) -> Self {
+        let status = response.status();
+        let status_message = response
+            .group(IppGroupTag::Operation)
+            .map(|g| g.string("status-message"))
+            .filter(|m| !m.is_empty());
+
+        let message = match status_message {
+            Some(status_message) => format!(
+                "{} failed with {}: {status_message}",
+                operation.keyword(),
+                status.keyword()
+            ),
+            None => format!("{} failed with {}", operation.keyword(), status.keyword()),
+        };
+
+        error(message)
+            .with_code(response.code as i32)
+            .with_status(status)
+            .with_operation(&operation.keyword())
+    }
+}
+
+impl Display for PrintersError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for PrintersError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn Error + 'static))
+    }
+}
+
+impl From<io::Error> for PrintersError {
+    fn from(error: io::Error) -> Self {
+        let mut printers_error = Self::file_error(&error);
+        printers_error.code = error.raw_os_error();
+        printers_error.with_source(error)
+    }
 }
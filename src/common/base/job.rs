@@ -1,23 +1,133 @@
 use std::{
     fmt::{Debug, Error, Formatter},
-    time::SystemTime,
+    fs::File,
+    io::{self, Read},
+    ops::RangeInclusive,
+    thread,
+    time::{Duration, Instant, SystemTime},
 };
 
-use crate::common::{
-    converters::Converter,
-    traits::platform::{PlatformActions, PlatformPrinterJobGetters},
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+use crate::ipp::{IppAttribute, IppGroupTag, IppMessage, IppOperation, IppValue};
+use crate::{
+    common::{
+        base::{
+            errors::PrintersError,
+            options::{
+                JobHoldUntil, JobPassword, JobPasswordEncryption, PrintColorMode, PrintOrientation,
+                PrintQuality, PrintSides,
+            },
+            retry::{RetryPolicy, retry, retry_submission},
+        },
+        converters::Converter,
+        traits::platform::{PlatformActions, PlatformPrinterJobGetters},
+    },
+    server::{PrintServer, with_server},
 };
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum PrinterJobState {
     PENDING,
+    /**
+     * Held, the job will not be scheduled until released
+     */
     PAUSED,
     PROCESSING,
+    /**
+     * Processing stopped, usually waiting for the printer (ex: offline or out of paper)
+     */
+    STOPPED,
+    /**
+     * Cancelled by a user or operator
+     */
     CANCELLED,
+    /**
+     * Aborted by the system because of an error
+     */
+    ABORTED,
     COMPLETED,
     UNKNOWN,
 }
 
+macro_rules! job_state_reasons {
+    ($($variant:ident => $keyword:expr,)*) => {
+        /**
+         * Reasons of the current job state (IPP job-state-reasons)
+         */
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum PrinterJobStateReason {
+            $($variant,)*
+            Other(String),
+        }
+
+        impl PrinterJobStateReason {
+            pub fn keyword(&self) -> &str {
+                match self {
+                    $(PrinterJobStateReason::$variant => $keyword,)*
+                    PrinterJobStateReason::Other(keyword) => keyword,
+                }
+            }
+
+            pub fn from_keyword(keyword: &str) -> Self {
+                match keyword {
+                    $($keyword => PrinterJobStateReason::$variant,)*
+                    keyword => PrinterJobStateReason::Other(keyword.to_string()),
+                }
+            }
+        }
+    };
+}
+
+job_state_reasons! {
+    JobIncoming => "job-incoming",
+    JobDataInsufficient => "job-data-insufficient",
+    DocumentAccessError => "document-access-error",
+    SubmissionInterrupted => "submission-interrupted",
+    JobOutgoing => "job-outgoing",
+    JobHoldUntilSpecified => "job-hold-until-specified",
+    ResourcesAreNotReady => "resources-are-not-ready",
+    PrinterStoppedPartly => "printer-stopped-partly",
+    PrinterStopped => "printer-stopped",
+    JobInterpreting => "job-interpreting",
+    JobQueued => "job-queued",
+    JobTransforming => "job-transforming",
+    JobQueuedForMarker => "job-queued-for-marker",
+    JobPrinting => "job-printing",
+    JobCanceledByUser => "job-canceled-by-user",
+    JobCanceledByOperator => "job-canceled-by-operator",
+    JobCanceledAtDevice => "job-canceled-at-device",
+    AbortedBySystem => "aborted-by-system",
+    UnsupportedCompression => "unsupported-compression",
+    CompressionError => "compression-error",
+    UnsupportedDocumentFormat => "unsupported-document-format",
+    DocumentFormatError => "document-format-error",
+    ProcessingToStopPoint => "processing-to-stop-point",
+    ServiceOffLine => "service-off-line",
+    JobCompletedSuccessfully => "job-completed-successfully",
+    JobCompletedWithWarnings => "job-completed-with-warnings",
+    JobCompletedWithErrors => "job-completed-with-errors",
+    JobRestartable => "job-restartable",
+    QueuedInDevice => "queued-in-device",
+    JobPasswordWait => "job-password-wait",
+    JobHeldForReview => "job-held-for-review",
+    JobSpooling => "job-spooling",
+    JobStreaming => "job-streaming",
+    AccountAuthorizationFailed => "account-authorization-failed",
+    AccountClosed => "account-closed",
+    AccountInfoNeeded => "account-info-needed",
+    AccountLimitReached => "account-limit-reached",
+    ConflictingAttributes => "conflicting-attributes",
+    DigitalSignatureDidNotVerify => "digital-signature-did-not-verify",
+    DocumentPasswordError => "document-password-error",
+    DocumentPermissionError => "document-permission-error",
+    DocumentSecurityError => "document-security-error",
+    DocumentUnprintableError => "document-unprintable-error",
+    ErrorsDetected => "errors-detected",
+    JobDelayOutputUntilSpecified => "job-delay-output-until-specified",
+    WarningsDetected => "warnings-detected",
+    CupsHeldForAuthentication => "cups-held-for-authentication",
+}
+
 pub struct PrinterJob {
     /**
      * Job ID
@@ -51,6 +161,42 @@ pub struct PrinterJob {
      * Name of printer
      */
     pub printer_name: String,
+    /**
+     * Name of the user that created the job
+     */
+    pub owner: String,
+    /**
+     * Size of the job in bytes (0 when unknown)
+     */
+    pub size: u64,
+    /**
+     * Job priority, from 1 (lowest) to 100 (highest) on cups and 1 to 99 on windows
+     */
+    pub priority: u32,
+    /**
+     * Reasons of the job state, ex JobHoldUntilSpecified
+     */
+    pub state_reasons: Vec<PrinterJobStateReason>,
+    /**
+     * Message of the printer about the job (default is an empty string)
+     */
+    pub printer_state_message: String,
+    /**
+     * Number of impressions completed
+     */
+    pub impressions_completed: u32,
+    /**
+     * Number of pages (media sheets) completed
+     */
+    pub pages_completed: u32,
+    /**
+     * Number of documents of the job
+     */
+    pub document_count: u32,
+    /**
+     * When the held job will be released, None if the job is not held or the value is not reported
+     */
+    pub hold_until: Option<JobHoldUntil>,
 }
 
 impl PrinterJob {
@@ -66,6 +212,22 @@ impl PrinterJob {
             processed_at: platform_printer_job.get_processed_at(),
             completed_at: platform_printer_job.get_completed_at(),
             printer_name: platform_printer_job.get_printer(),
+            owner: platform_printer_job.get_owner(),
+            size: platform_printer_job.get_size(),
+            priority: platform_printer_job.get_priority(),
+            state_reasons: platform_printer_job
+                .get_state_reasons()
+                .iter()
+                .map(|v| PrinterJobStateReason::from_keyword(v))
+                .collect(),
+            printer_state_message: platform_printer_job.get_printer_state_message(),
+            impressions_completed: platform_printer_job.get_impressions_completed(),
+            pages_completed: platform_printer_job.get_pages_completed(),
+            document_count: platform_printer_job.get_document_count(),
+            hold_until: platform_printer_job
+                .get_hold_until()
+                .and_then(|v| JobHoldUntil::from_keyword(&v))
+                .filter(|v| *v != JobHoldUntil::NoHold),
         }
     }
 }
@@ -83,6 +245,15 @@ impl Debug for PrinterJob {
                 \r  processed_at: {:?},
                 \r  completed_at: {:?},
                 \r  printer_name: {:?},
+                \r  owner: {:?},
+                \r  size: {:?},
+                \r  priority: {:?},
+                \r  state_reasons: {:?},
+                \r  printer_state_message: {:?},
+                \r  impressions_completed: {:?},
+                \r  pages_completed: {:?},
+                \r  document_count: {:?},
+                \r  hold_until: {:?},
             \r}}",
             self.id,
             self.name,
@@ -92,6 +263,15 @@ impl Debug for PrinterJob {
             self.processed_at,
             self.completed_at,
             self.printer_name,
+            self.owner,
+            self.size,
+            self.priority,
+            self.state_reasons,
+            self.printer_state_message,
+            self.impressions_completed,
+            self.pages_completed,
+            self.document_count,
+            self.hold_until,
         )
     }
 }
@@ -100,13 +280,526 @@ impl PrinterJobState {
     pub(crate) fn from_platform_state(platform_state: u64) -> Self {
         crate::Platform::parse_printer_job_state(platform_state)
     }
+
+    /**
+     * Return if the job reached a state it will not leave
+     */
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            PrinterJobState::CANCELLED | PrinterJobState::COMPLETED | PrinterJobState::ABORTED
+        )
+    }
+}
+
+/**
+ * Jobs of a queue affected by Printer::cancel_all_jobs and Printer::purge_all_jobs
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CancelJobsFilter<'a> {
+    /**
+     * Jobs of every user, usually requires administrator rights
+     */
+    All,
+    /**
+     * Jobs of the current user
+     */
+    Mine,
+    /**
+     * Jobs of the given user
+     */
+    User(&'a str),
+}
+
+impl CancelJobsFilter<'_> {
+    /**
+     * Return the ids of the queue jobs the request will affect, terminated jobs only when purging.
+     * Fails when the jobs cannot be listed
+     */
+    pub(crate) fn matching_jobs(
+        &self,
+        printer_name: &str,
+        user_name: &str,
+        purge: bool,
+    ) -> Result<Vec<u64>, PrintersError> {
+        let owner = match self {
+            CancelJobsFilter::All => None,
+            CancelJobsFilter::Mine => Some(user_name),
+            CancelJobsFilter::User(user_name) => Some(*user_name),
+        };
+
+        Ok(crate::Platform::get_printer_jobs(printer_name, !purge)?
+            .into_iter()
+            .filter(|j| purge || !j.state.is_terminal())
+            .filter(|j| owner.is_none_or(|owner| j.owner == owner))
+            .map(|j| j.id)
+            .collect())
+    }
+}
+
+/**
+ * Outcome of Printer::release_held_jobs, a failed release does not stop the others
+ */
+#[derive(Debug, Default)]
+pub struct ReleasedJobs {
+    /**
+     * Ids of the released jobs
+     */
+    pub released: Vec<u64>,
+    /**
+     * Jobs that could not be released, with the error of their release
+     */
+    pub failed: Vec<(u64, PrintersError)>,
+}
+
+/**
+ * JobDocument is a document of a submitted job returned by Printer::get_job_document, read it for its bytes
+ */
+pub struct JobDocument {
+    /**
+     * Format of the document (ex: application/pdf)
+     */
+    pub format: String,
+
+    /**
+     * Name of the document, if any
+     */
+    pub name: Option<String>,
+
+    reader: Box<dyn Read + Send>,
+}
+
+impl JobDocument {
+    /**
+     * Build the document from a CUPS-Get-Document response, its data is read from the reader
+     */
+    #[cfg(any(target_family = "unix", feature = "ipp-client"))]
+    pub(crate) fn from_ipp_response(
+        response: &IppMessage,
+        reader: Box<dyn Read + Send>,
+    ) -> Result<Self, PrintersError> {
+        if !response.is_successful() {
+            return Err(PrintersError::from_response(
+                IppOperation::CupsGetDocument,
+                response,
+                PrintersError::job_error,
+            ));
+        }
+
+        let operation = response.group(IppGroupTag::Operation);
+        let string = |name: &str| operation.map(|g| g.string(name)).filter(|v| !v.is_empty());
+
+        Ok(JobDocument {
+            format: string("document-format")
+                .unwrap_or_else(|| "application/octet-stream".to_string()),
+            name: string("document-name"),
+            reader,
+        })
+    }
+}
+
+impl Read for JobDocument {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl Debug for JobDocument {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        fmt.debug_struct("JobDocument")
+            .field("format", &self.format)
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/**
+ * State of a job its system does not know anymore, windows forgets the jobs once printed
+ */
+#[cfg(all(target_family = "windows", not(feature = "ipp-client")))]
+const VANISHED_JOB_STATE: PrinterJobState = PrinterJobState::COMPLETED;
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+const VANISHED_JOB_STATE: PrinterJobState = PrinterJobState::UNKNOWN;
+
+/**
+ * PrintJobHandle is a reference to a created job, returned from print and print_file
+ */
+#[derive(Debug, Clone)]
+pub struct PrintJobHandle {
+    /**
+     * Job ID
+     */
+    pub id: u64,
+
+    /**
+     * Name of printer exactly as on a system
+     */
+    pub printer_name: String,
+
+    state: PrinterJobState,
+    server: Option<PrintServer>,
+    retry: Option<RetryPolicy>,
+}
+
+impl PrintJobHandle {
+    pub(crate) fn new(printer_name: &str, id: u64, retry: Option<RetryPolicy>) -> Self {
+        PrintJobHandle {
+            id,
+            printer_name: printer_name.to_string(),
+            state: PrinterJobState::PENDING,
+            server: crate::server::current(),
+            retry,
+        }
+    }
+
+    /**
+     * Return the last known state of the job, see refresh
+     */
+    pub fn status(&self) -> PrinterJobState {
+        self.state.clone()
+    }
+
+    /**
+     * Query the current state of the job, failing when the system cannot be asked.
+     * A job unknown to the system is UNKNOWN on CUPS / IPP (ex: purged) and COMPLETED on windows,
+     * as windows only lists queued jobs
+     */
+    pub fn refresh(&mut self) -> Result<PrinterJobState, PrintersError> {
+        let job = with_server(self.server.as_ref(), || {
+            crate::Platform::get_printer_job(Some(&self.printer_name), self.id)
+        })?;
+
+        self.state = job.map_or(VANISHED_JOB_STATE, |j| j.state);
+        Ok(self.status())
+    }
+
+    /**
+     * Cancel the job
+     */
+    pub fn cancel(&self) -> Result<(), PrintersError> {
+        self.set_state(PrinterJobState::CANCELLED)
+    }
+
+    /**
+     * Pause (hold) the job
+     */
+    pub fn pause(&self) -> Result<(), PrintersError> {
+        self.set_state(PrinterJobState::PAUSED)
+    }
+
+    /**
+     * Resume a paused job
+     */
+    pub fn resume(&self) -> Result<(), PrintersError> {
+        self.set_state(PrinterJobState::PROCESSING)
+    }
+
+    /**
+     * Change the state of the job on its server, retried by the policy of the job options
+     */
+    fn set_state(&self, state: PrinterJobState) -> Result<(), PrintersError> {
+        retry(self.retry.as_ref(), || {
+            with_server(self.server.as_ref(), || {
+                crate::Platform::set_job_state(&self.printer_name, self.id, state.clone())
+            })
+        })
+    }
+
+    /**
+     * Error of a job still not done after the timeout of a wait
+     */
+    pub(crate) fn timeout_error(&self, timeout: Duration) -> PrintersError {
+        PrintersError::job_error(format!(
+            "job {} not done after {:?}, last state {:?}",
+            self.id, timeout, self.state
+        ))
+    }
+
+    /**
+     * Block until the job is completed, cancelled or aborted, returning the terminal state
+     */
+    pub fn wait_until_done(&mut self, timeout: Duration) -> Result<PrinterJobState, PrintersError> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let state = self.refresh()?;
+            if state.is_terminal() {
+                return Ok(state);
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(self.timeout_error(timeout));
+            }
+
+            thread::sleep(remaining.min(POLL_INTERVAL));
+        }
+    }
+}
+
+/**
+ * PrintJobBuilder is an open job, documents are added to it and printed together once closed.
+ * A builder dropped without being closed cancels its job.
+ */
+#[derive(Debug)]
+pub struct PrintJobBuilder {
+    /**
+     * Job ID
+     */
+    pub id: u64,
+
+    /**
+     * Name of printer exactly as on a system
+     */
+    pub printer_name: String,
+
+    name: String,
+    converter: Converter,
+    documents: u32,
+    closed: bool,
+    server: Option<PrintServer>,
+    retry: Option<RetryPolicy>,
+}
+
+impl PrintJobBuilder {
+    pub(crate) fn new(
+        printer_name: &str,
+        options: &PrinterJobOptions,
+    ) -> Result<Self, PrintersError> {
+        let id = retry_submission(
+            options.retry.as_ref(),
+            || true,
+            || crate::Platform::create_job(printer_name, options),
+        )?;
+
+        Ok(PrintJobBuilder {
+            id,
+            printer_name: printer_name.to_string(),
+            name: options.name.unwrap_or("printers").to_string(),
+            converter: options.converter.clone(),
+            documents: 0,
+            closed: false,
+            server: crate::server::current(),
+            retry: options.retry.clone(),
+        })
+    }
+
+    /**
+     * Add a document from bytes, the format is a mime type (ex: application/pdf) or None to auto detect
+     */
+    pub fn add_bytes(
+        &mut self,
+        buffer: &[u8],
+        document_format: Option<&str>,
+    ) -> Result<&mut Self, PrintersError> {
+        self.add_reader(buffer, document_format)
+    }
+
+    /**
+     * Add a document from a file
+     */
+    pub fn add_file(
+        &mut self,
+        file_path: &str,
+        document_format: Option<&str>,
+    ) -> Result<&mut Self, PrintersError> {
+        let file = File::open(file_path).map_err(PrintersError::from)?;
+        self.send(file_path, file, document_format)
+    }
+
+    /**
+     * Add a document from any reader, the data is streamed when the backend allows it
+     */
+    pub fn add_reader(
+        &mut self,
+        reader: impl Read,
+        document_format: Option<&str>,
+    ) -> Result<&mut Self, PrintersError> {
+        let document_name = format!("{} ({})", self.name, self.documents + 1);
+        self.send(&document_name, reader, document_format)
+    }
+
+    /**
+     * Close the job, the printer starts printing all its documents
+     */
+    pub fn close(mut self) -> Result<PrintJobHandle, PrintersError> {
+        self.closed = true;
+
+        // An empty last document closes the job, as cupsCloseDestJob does for older servers
+        retry_submission(
+            self.retry.as_ref(),
+            || true,
+            || {
+                with_server(self.server.as_ref(), || {
+                    crate::Platform::send_document(
+                        &self.printer_name,
+                        self.id,
+                        &self.name,
+                        &mut io::empty(),
+                        None,
+                        true,
+                    )
+                })
+            },
+        )?;
+        Ok(PrintJobHandle::new(
+            &self.printer_name,
+            self.id,
+            self.retry.take(),
+        ))
+    }
+
+    /**
+     * Cancel the job and its documents
+     */
+    pub fn cancel(mut self) -> Result<(), PrintersError> {
+        self.closed = true;
+        retry(self.retry.as_ref(), || {
+            with_server(self.server.as_ref(), || {
+                crate::Platform::set_job_state(
+                    &self.printer_name,
+                    self.id,
+                    PrinterJobState::CANCELLED,
+                )
+            })
+        })
+    }
+
+    fn send(
+        &mut self,
+        document_name: &str,
+        mut reader: impl Read,
+        document_format: Option<&str>,
+    ) -> Result<&mut Self, PrintersError> {
+        let send = |reader: &mut dyn Read| {
+            with_server(self.server.as_ref(), || {
+                crate::Platform::send_document(
+                    &self.printer_name,
+                    self.id,
+                    document_name,
+                    reader,
+                    document_format,
+                    false,
+                )
+            })
+        };
+
+        if self.converter == Converter::None {
+            send(&mut reader)?;
+        } else {
+            // Converters work on the whole document
+            let mut buffer = Vec::new();
+            reader
+                .read_to_end(&mut buffer)
+                .map_err(PrintersError::from)?;
+            let buffer = self.converter.convert(&buffer)?;
+            send(&mut buffer.as_slice())?;
+        }
+
+        self.documents += 1;
+        Ok(self)
+    }
+}
+
+impl Drop for PrintJobBuilder {
+    fn drop(&mut self) {
+        if !self.closed {
+            let _ = with_server(self.server.as_ref(), || {
+                crate::Platform::set_job_state(
+                    &self.printer_name,
+                    self.id,
+                    PrinterJobState::CANCELLED,
+                )
+            });
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct PrinterJobOptions<'a> {
+    /**
+     * Visual name/title of the job
+     */
     pub name: Option<&'a str>,
+
+    /**
+     * Options sent as is to the backend, they take precedence over the typed options with the same name
+     */
     pub raw_properties: &'a [(&'a str, &'a str)],
+
+    /**
+     * Converter applied to the document before printing
+     */
     pub converter: Converter,
+
+    /**
+     * Number of copies
+     */
+    pub copies: Option<u32>,
+
+    /**
+     * Sides of the media to print on
+     */
+    pub sides: Option<PrintSides>,
+
+    /**
+     * Media name, ex: iso_a4_210x297mm (see PrinterCapabilities::media)
+     */
+    pub media: Option<&'a str>,
+
+    /**
+     * Orientation of the content
+     */
+    pub orientation: Option<PrintOrientation>,
+
+    /**
+     * Pages to print, all pages when empty
+     */
+    pub page_ranges: &'a [RangeInclusive<u32>],
+
+    /**
+     * Number of pages imposed on each side of the media
+     */
+    pub number_up: Option<u32>,
+
+    /**
+     * Print quality
+     */
+    pub quality: Option<PrintQuality>,
+
+    /**
+     * Color mode
+     */
+    pub color_mode: Option<PrintColorMode>,
+
+    /**
+     * Collate the copies of multi page documents
+     */
+    pub collate: Option<bool>,
+
+    /**
+     * Job priority from 1 (lowest) to 100 (highest)
+     */
+    pub priority: Option<u8>,
+
+    /**
+     * Submit the job held, indefinitely or until a time or named period (CUPS / IPP only)
+     */
+    pub hold_until: Option<JobHoldUntil>,
+
+    /**
+     * Password the owner enters on the printer to release the job (IPP only),
+     * the job is held indefinitely unless hold_until is set
+     */
+    pub job_password: Option<JobPassword<'a>>,
+
+    /**
+     * Retry the submission and the control of the job after transient failures, None fails at once.
+     * Streams are only sent again while none of their data was read
+     */
+    pub retry: Option<RetryPolicy>,
 }
 
 impl PrinterJobOptions<'_> {
@@ -115,6 +808,332 @@ impl PrinterJobOptions<'_> {
             name: None,
             raw_properties: &[],
             converter: Converter::None,
+            copies: None,
+            sides: None,
+            media: None,
+            orientation: None,
+            page_ranges: &[],
+            number_up: None,
+            quality: None,
+            color_mode: None,
+            collate: None,
+            priority: None,
+            hold_until: None,
+            job_password: None,
+            retry: None,
+        }
+    }
+
+    /**
+     * Names of the attributes set by the typed options
+     */
+    pub(crate) fn typed_option_names(&self) -> Vec<&'static str> {
+        [
+            ("copies", self.copies.is_some()),
+            ("sides", self.sides.is_some()),
+            ("media", self.media.is_some()),
+            ("orientation-requested", self.orientation.is_some()),
+            ("page-ranges", !self.page_ranges.is_empty()),
+            ("number-up", self.number_up.is_some()),
+            ("print-quality", self.quality.is_some()),
+            ("print-color-mode", self.color_mode.is_some()),
+            ("multiple-document-handling", self.collate.is_some()),
+            ("job-priority", self.priority.is_some()),
+            ("job-hold-until", self.hold_until().is_some()),
+            ("job-password", self.job_password.is_some()),
+            ("job-password-encryption", self.job_password.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, set)| *set)
+        .map(|(name, _)| name)
+        .collect()
+    }
+
+    /**
+     * Translate the typed options to IPP job template attributes, skipping the ones in raw_properties
+     */
+    #[cfg(any(target_family = "unix", feature = "ipp-client"))]
+    pub(crate) fn job_attributes(&self) -> Vec<IppAttribute> {
+        let mut attributes = Vec::new();
+        let mut add = |name: &str, values: Vec<IppValue>| {
+            if !self.raw_properties.iter().any(|(n, _)| *n == name) {
+                attributes.push(IppAttribute::with_values(name, values));
+            }
+        };
+
+        if let Some(copies) = self.copies {
+            add("copies", vec![IppValue::Integer(copies.max(1) as i32)]);
+        }
+        if let Some(sides) = self.sides {
+            add("sides", vec![IppValue::Keyword(sides.keyword().into())]);
+        }
+        if let Some(media) = self.media {
+            add("media", vec![IppValue::Keyword(media.into())]);
+        }
+        if let Some(orientation) = self.orientation {
+            add(
+                "orientation-requested",
+                vec![IppValue::Enum(orientation.value())],
+            );
+        }
+        if !self.page_ranges.is_empty() {
+            add(
+                "page-ranges",
+                self.page_ranges
+                    .iter()
+                    .map(|r| IppValue::RangeOfInteger {
+                        lower: *r.start() as i32,
+                        upper: *r.end() as i32,
+                    })
+                    .collect(),
+            );
+        }
+        if let Some(number_up) = self.number_up {
+            add(
+                "number-up",
+                vec![IppValue::Integer(number_up.max(1) as i32)],
+            );
+        }
+        if let Some(quality) = self.quality {
+            add("print-quality", vec![IppValue::Enum(quality.value())]);
+        }
+        if let Some(color_mode) = self.color_mode {
+            add(
+                "print-color-mode",
+                vec![IppValue::Keyword(color_mode.keyword().into())],
+            );
+        }
+        if let Some(collate) = self.collate {
+            let handling = if collate {
+                "separate-documents-collated-copies"
+            } else {
+                "separate-documents-uncollated-copies"
+            };
+            add(
+                "multiple-document-handling",
+                vec![IppValue::Keyword(handling.into())],
+            );
+        }
+        if let Some(priority) = self.priority {
+            add(
+                "job-priority",
+                vec![IppValue::Integer(priority.clamp(1, 100) as i32)],
+            );
+        }
+        if let Some(hold_until) = self.hold_until() {
+            add("job-hold-until", vec![hold_until.to_ipp_value()]);
+        }
+
+        attributes
+    }
+
+    /**
+     * Translate the typed options sent in the operation group, skipping the ones in raw_properties
+     */
+    #[cfg(any(target_family = "unix", feature = "ipp-client"))]
+    pub(crate) fn operation_attributes(&self) -> Vec<IppAttribute> {
+        let mut attributes = Vec::new();
+        if let Some(job_password) = self.job_password {
+            attributes.push(IppAttribute::new(
+                "job-password",
+                IppValue::OctetString(job_password.password.as_bytes().to_vec()),
+            ));
+            attributes.push(IppAttribute::new(
+                "job-password-encryption",
+                IppValue::Keyword(job_password.encryption.keyword().into()),
+            ));
+        }
+
+        attributes.retain(|a| !self.raw_properties.iter().any(|(n, _)| *n == a.name));
+        attributes
+    }
+
+    /**
+     * Secure release jobs are held until the owner releases them
+     */
+    fn hold_until(&self) -> Option<JobHoldUntil> {
+        self.hold_until
+            .or(self.job_password.map(|_| JobHoldUntil::Indefinite))
+    }
+}
+
+/**
+ * OwnedPrinterJobOptions keeps a copy of PrinterJobOptions that does not borrow,
+ * so the options can be sent to another thread (ex: the async API)
+ */
+#[derive(Clone)]
+pub struct OwnedPrinterJobOptions {
+    name: Option<String>,
+    raw_properties: Vec<(String, String)>,
+    media: Option<String>,
+    page_ranges: Vec<RangeInclusive<u32>>,
+    job_password: Option<(String, JobPasswordEncryption)>,
+    /**
+     * The options without a borrowed value
+     */
+    options: PrinterJobOptions<'static>,
+}
+
+impl OwnedPrinterJobOptions {
+    /**
+     * Run a call with the options borrowed from the copy
+     */
+    pub fn with_options<T>(&self, f: impl FnOnce(PrinterJobOptions) -> T) -> T {
+        let raw_properties: Vec<(&str, &str)> = self
+            .raw_properties
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+
+        f(PrinterJobOptions {
+            name: self.name.as_deref(),
+            raw_properties: &raw_properties,
+            media: self.media.as_deref(),
+            page_ranges: &self.page_ranges,
+            job_password: self
+                .job_password
+                .as_ref()
+                .map(|(password, encryption)| JobPassword {
+                    password,
+                    encryption: *encryption,
+                }),
+            ..self.options.clone()
+        })
+    }
+}
+
+impl From<PrinterJobOptions<'_>> for OwnedPrinterJobOptions {
+    fn from(options: PrinterJobOptions<'_>) -> Self {
+        OwnedPrinterJobOptions {
+            name: options.name.map(str::to_string),
+            raw_properties: options
+                .raw_properties
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            media: options.media.map(str::to_string),
+            page_ranges: options.page_ranges.to_vec(),
+            job_password: options
+                .job_password
+                .map(|p| (p.password.to_string(), p.encryption)),
+            options: PrinterJobOptions {
+                name: None,
+                raw_properties: &[],
+                media: None,
+                page_ranges: &[],
+                job_password: None,
+                converter: options.converter,
+                copies: options.copies,
+                sides: options.sides,
+                orientation: options.orientation,
+                number_up: options.number_up,
+                quality: options.quality,
+                color_mode: options.color_mode,
+                collate: options.collate,
+                priority: options.priority,
+                hold_until: options.hold_until,
+                retry: options.retry,
+            },
+        }
+    }
+}
+
+/**
+ * Changes applied to a queued job by Printer::update_job, None keeps the current value
+ */
+#[derive(Debug, Clone)]
+pub struct JobChanges<'a> {
+    /**
+     * New visual name/title of the job
+     */
+    pub name: Option<&'a str>,
+
+    /**
+     * Job priority from 1 (lowest) to 100 (highest)
+     */
+    pub priority: Option<u8>,
+
+    /**
+     * Hold the job until a named period or a time of day, NoHold releases it
+     */
+    pub hold_until: Option<JobHoldUntil>,
+
+    /**
+     * Number of copies
+     */
+    pub copies: Option<u32>,
+
+    /**
+     * Pages to print, unchanged when empty
+     */
+    pub page_ranges: &'a [RangeInclusive<u32>],
+}
+
+impl JobChanges<'_> {
+    pub fn none() -> Self {
+        JobChanges {
+            name: None,
+            priority: None,
+            hold_until: None,
+            copies: None,
+            page_ranges: &[],
+        }
+    }
+
+    /**
+     * Translate the changes to the IPP job attributes of a Set-Job-Attributes request
+     */
+    #[cfg(any(target_family = "unix", feature = "ipp-client"))]
+    pub(crate) fn job_attributes(&self) -> Vec<IppAttribute> {
+        let mut attributes = Vec::new();
+
+        if let Some(name) = self.name {
+            attributes.push(IppAttribute::new("job-name", IppValue::Name(name.into())));
+        }
+        if let Some(priority) = self.priority {
+            attributes.push(IppAttribute::new(
+                "job-priority",
+                IppValue::Integer(priority.clamp(1, 100) as i32),
+            ));
+        }
+        if let Some(hold_until) = self.hold_until {
+            attributes.push(IppAttribute::new(
+                "job-hold-until",
+                hold_until.to_ipp_value(),
+            ));
+        }
+        if let Some(copies) = self.copies {
+            attributes.push(IppAttribute::new(
+                "copies",
+                IppValue::Integer(copies.max(1) as i32),
+            ));
+        }
+        if !self.page_ranges.is_empty() {
+            attributes.push(IppAttribute::with_values(
+                "page-ranges",
+                self.page_ranges
+                    .iter()
+                    .map(|r| IppValue::RangeOfInteger {
+                        lower: *r.start() as i32,
+                        upper: *r.end() as i32,
+                    })
+                    .collect(),
+            ));
+        }
+
+        attributes
+    }
+}
+
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+impl JobHoldUntil {
+    /**
+     * Times of day are names, the periods are keywords
+     */
+    fn to_ipp_value(self) -> IppValue {
+        match self {
+            JobHoldUntil::Time { .. } => IppValue::Name(self.keyword()),
+            _ => IppValue::Keyword(self.keyword()),
         }
     }
 }
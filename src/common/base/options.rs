@@ -1,3 +1,5 @@
+use std::fmt::{Debug, Error, Formatter};
+
 pub struct OptionsCollection<T, G> {
     _raw: Vec<(T, T)>,
     items: Vec<G>,
@@ -32,3 +34,391 @@ impl<T, G> OptionsCollection<T, G> {
         &self.items
     }
 }
+
+/**
+ * How pages are imposed on the sides of the media (IPP sides)
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrintSides {
+    OneSided,
+    TwoSidedLongEdge,
+    TwoSidedShortEdge,
+}
+
+impl PrintSides {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            PrintSides::OneSided => "one-sided",
+            PrintSides::TwoSidedLongEdge => "two-sided-long-edge",
+            PrintSides::TwoSidedShortEdge => "two-sided-short-edge",
+        }
+    }
+
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "one-sided" => Some(PrintSides::OneSided),
+            "two-sided-long-edge" => Some(PrintSides::TwoSidedLongEdge),
+            "two-sided-short-edge" => Some(PrintSides::TwoSidedShortEdge),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Color mode of the output (IPP print-color-mode)
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrintColorMode {
+    Auto,
+    AutoMonochrome,
+    BiLevel,
+    Color,
+    Highlight,
+    Monochrome,
+    ProcessBiLevel,
+    ProcessMonochrome,
+}
+
+impl PrintColorMode {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            PrintColorMode::Auto => "auto",
+            PrintColorMode::AutoMonochrome => "auto-monochrome",
+            PrintColorMode::BiLevel => "bi-level",
+            PrintColorMode::Color => "color",
+            PrintColorMode::Highlight => "highlight",
+            PrintColorMode::Monochrome => "monochrome",
+            PrintColorMode::ProcessBiLevel => "process-bi-level",
+            PrintColorMode::ProcessMonochrome => "process-monochrome",
+        }
+    }
+
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "auto" => Some(PrintColorMode::Auto),
+            "auto-monochrome" => Some(PrintColorMode::AutoMonochrome),
+            "bi-level" => Some(PrintColorMode::BiLevel),
+            "color" => Some(PrintColorMode::Color),
+            "highlight" => Some(PrintColorMode::Highlight),
+            "monochrome" => Some(PrintColorMode::Monochrome),
+            "process-bi-level" => Some(PrintColorMode::ProcessBiLevel),
+            "process-monochrome" => Some(PrintColorMode::ProcessMonochrome),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Orientation of the content on the page (IPP orientation-requested)
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrintOrientation {
+    Portrait,
+    Landscape,
+    ReverseLandscape,
+    ReversePortrait,
+}
+
+impl PrintOrientation {
+    pub fn value(&self) -> i32 {
+        match self {
+            PrintOrientation::Portrait => 3,
+            PrintOrientation::Landscape => 4,
+            PrintOrientation::ReverseLandscape => 5,
+            PrintOrientation::ReversePortrait => 6,
+        }
+    }
+
+    pub fn from_value(value: i32) -> Option<Self> {
+        match value {
+            3 => Some(PrintOrientation::Portrait),
+            4 => Some(PrintOrientation::Landscape),
+            5 => Some(PrintOrientation::ReverseLandscape),
+            6 => Some(PrintOrientation::ReversePortrait),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Quality of the output (IPP print-quality)
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrintQuality {
+    Draft,
+    Normal,
+    High,
+}
+
+impl PrintQuality {
+    pub fn value(&self) -> i32 {
+        match self {
+            PrintQuality::Draft => 3,
+            PrintQuality::Normal => 4,
+            PrintQuality::High => 5,
+        }
+    }
+
+    pub fn from_value(value: i32) -> Option<Self> {
+        match value {
+            3 => Some(PrintQuality::Draft),
+            4 => Some(PrintQuality::Normal),
+            5 => Some(PrintQuality::High),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Output resolution in dots per inch
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrintResolution {
+    /**
+     * Horizontal (cross feed) resolution
+     */
+    pub x: u32,
+
+    /**
+     * Vertical (feed) resolution
+     */
+    pub y: u32,
+}
+
+impl PrintResolution {
+    pub fn new(x: u32, y: u32) -> Self {
+        PrintResolution { x, y }
+    }
+}
+
+/**
+ * Finishing operations applied to the output (IPP finishings)
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrintFinishing {
+    None,
+    Staple,
+    Punch,
+    Cover,
+    Bind,
+    SaddleStitch,
+    EdgeStitch,
+    Fold,
+    Trim,
+    Bale,
+    BookletMaker,
+    JogOffset,
+    Coat,
+    Laminate,
+    StapleTopLeft,
+    StapleBottomLeft,
+    StapleTopRight,
+    StapleBottomRight,
+    EdgeStitchLeft,
+    EdgeStitchTop,
+    EdgeStitchRight,
+    EdgeStitchBottom,
+    StapleDualLeft,
+    StapleDualTop,
+    StapleDualRight,
+    StapleDualBottom,
+    Other(i32),
+}
+
+impl PrintFinishing {
+    pub fn value(&self) -> i32 {
+        match self {
+            PrintFinishing::None => 3,
+            PrintFinishing::Staple => 4,
+            PrintFinishing::Punch => 5,
+            PrintFinishing::Cover => 6,
+            PrintFinishing::Bind => 7,
+            PrintFinishing::SaddleStitch => 8,
+            PrintFinishing::EdgeStitch => 9,
+            PrintFinishing::Fold => 10,
+            PrintFinishing::Trim => 11,
+            PrintFinishing::Bale => 12,
+            PrintFinishing::BookletMaker => 13,
+            PrintFinishing::JogOffset => 14,
+            PrintFinishing::Coat => 15,
+            PrintFinishing::Laminate => 16,
+            PrintFinishing::StapleTopLeft => 20,
+            PrintFinishing::StapleBottomLeft => 21,
+            PrintFinishing::StapleTopRight => 22,
+            PrintFinishing::StapleBottomRight => 23,
+            PrintFinishing::EdgeStitchLeft => 24,
+            PrintFinishing::EdgeStitchTop => 25,
+            PrintFinishing::EdgeStitchRight => 26,
+            PrintFinishing::EdgeStitchBottom => 27,
+            PrintFinishing::StapleDualLeft => 28,
+            PrintFinishing::StapleDualTop => 29,
+            PrintFinishing::StapleDualRight => 30,
+            PrintFinishing::StapleDualBottom => 31,
+            PrintFinishing::Other(value) => *value,
+        }
+    }
+
+    pub fn from_value(value: i32) -> Self {
+        match value {
+            3 => PrintFinishing::None,
+            4 => PrintFinishing::Staple,
+            5 => PrintFinishing::Punch,
+            6 => PrintFinishing::Cover,
+            7 => PrintFinishing::Bind,
+            8 => PrintFinishing::SaddleStitch,
+            9 => PrintFinishing::EdgeStitch,
+            10 => PrintFinishing::Fold,
+            11 => PrintFinishing::Trim,
+            12 => PrintFinishing::Bale,
+            13 => PrintFinishing::BookletMaker,
+            14 => PrintFinishing::JogOffset,
+            15 => PrintFinishing::Coat,
+            16 => PrintFinishing::Laminate,
+            20 => PrintFinishing::StapleTopLeft,
+            21 => PrintFinishing::StapleBottomLeft,
+            22 => PrintFinishing::StapleTopRight,
+            23 => PrintFinishing::StapleBottomRight,
+            24 => PrintFinishing::EdgeStitchLeft,
+            25 => PrintFinishing::EdgeStitchTop,
+            26 => PrintFinishing::EdgeStitchRight,
+            27 => PrintFinishing::EdgeStitchBottom,
+            28 => PrintFinishing::StapleDualLeft,
+            29 => PrintFinishing::StapleDualTop,
+            30 => PrintFinishing::StapleDualRight,
+            31 => PrintFinishing::StapleDualBottom,
+            v => PrintFinishing::Other(v),
+        }
+    }
+}
+
+/**
+ * When a held job is released for printing (IPP job-hold-until)
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JobHoldUntil {
+    NoHold,
+    Indefinite,
+    DayTime,
+    Evening,
+    Night,
+    SecondShift,
+    ThirdShift,
+    Weekend,
+    /**
+     * Next occurrence of the time of day, in UTC
+     */
+    Time {
+        hour: u8,
+        minute: u8,
+    },
+}
+
+impl JobHoldUntil {
+    pub fn keyword(&self) -> String {
+        match self {
+            JobHoldUntil::NoHold => "no-hold".to_string(),
+            JobHoldUntil::Indefinite => "indefinite".to_string(),
+            JobHoldUntil::DayTime => "day-time".to_string(),
+            JobHoldUntil::Evening => "evening".to_string(),
+            JobHoldUntil::Night => "night".to_string(),
+            JobHoldUntil::SecondShift => "second-shift".to_string(),
+            JobHoldUntil::ThirdShift => "third-shift".to_string(),
+            JobHoldUntil::Weekend => "weekend".to_string(),
+            JobHoldUntil::Time { hour, minute } => format!("{hour:02}:{minute:02}"),
+        }
+    }
+
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "no-hold" => Some(JobHoldUntil::NoHold),
+            "indefinite" => Some(JobHoldUntil::Indefinite),
+            "day-time" => Some(JobHoldUntil::DayTime),
+            "evening" => Some(JobHoldUntil::Evening),
+            "night" => Some(JobHoldUntil::Night),
+            "second-shift" => Some(JobHoldUntil::SecondShift),
+            "third-shift" => Some(JobHoldUntil::ThirdShift),
+            "weekend" => Some(JobHoldUntil::Weekend),
+            time => {
+                // HH:MM or HH:MM:SS, the seconds are ignored
+                let mut parts = time.split(':').map(|v| v.parse::<u8>().ok());
+                let (hour, minute) = (parts.next()??, parts.next()??);
+                (hour < 24 && minute < 60).then_some(JobHoldUntil::Time { hour, minute })
+            }
+        }
+    }
+}
+
+/**
+ * How the job password is hashed (IPP job-password-encryption)
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JobPasswordEncryption {
+    None,
+    Md5,
+    Sha,
+    Sha2_224,
+    Sha2_256,
+    Sha2_384,
+    Sha2_512,
+}
+
+impl JobPasswordEncryption {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            JobPasswordEncryption::None => "none",
+            JobPasswordEncryption::Md5 => "md5",
+            JobPasswordEncryption::Sha => "sha",
+            JobPasswordEncryption::Sha2_224 => "sha2-224",
+            JobPasswordEncryption::Sha2_256 => "sha2-256",
+            JobPasswordEncryption::Sha2_384 => "sha2-384",
+            JobPasswordEncryption::Sha2_512 => "sha2-512",
+        }
+    }
+
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "none" => Some(JobPasswordEncryption::None),
+            "md5" => Some(JobPasswordEncryption::Md5),
+            "sha" => Some(JobPasswordEncryption::Sha),
+            "sha2-224" => Some(JobPasswordEncryption::Sha2_224),
+            "sha2-256" => Some(JobPasswordEncryption::Sha2_256),
+            "sha2-384" => Some(JobPasswordEncryption::Sha2_384),
+            "sha2-512" => Some(JobPasswordEncryption::Sha2_512),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Password (PIN) the owner enters on the printer to release a secure job (IPP job-password)
+ */
+#[derive(Clone, Copy, PartialEq)]
+pub struct JobPassword<'a> {
+    /**
+     * The password, already hashed as the printer expects when the encryption is not None
+     */
+    pub password: &'a str,
+
+    /**
+     * How the password is hashed
+     */
+    pub encryption: JobPasswordEncryption,
+}
+
+impl<'a> JobPassword<'a> {
+    pub fn new(password: &'a str) -> Self {
+        JobPassword {
+            password,
+            encryption: JobPasswordEncryption::None,
+        }
+    }
+}
+
+impl Debug for JobPassword<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.debug_struct("JobPassword")
+            .field("password", &"***")
+            .field("encryption", &self.encryption)
+            .finish()
+    }
+}
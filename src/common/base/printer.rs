@@ -1,9 +1,26 @@
-use std::fmt::{Debug, Error, Formatter};
+use std::{
+    cell::Cell,
+    fmt::{Debug, Error, Formatter},
+    io::Read,
+};
 
-use super::job::{PrinterJob, PrinterJobOptions};
-use crate::common::{
-    base::{errors::PrintersError, job::PrinterJobState},
-    traits::platform::{PlatformActions, PlatformPrinterGetters},
+use super::{
+    capabilities::PrinterCapabilities,
+    job::{
+        CancelJobsFilter, JobChanges, JobDocument, PrintJobBuilder, PrintJobHandle, PrinterJob,
+        PrinterJobOptions, PrinterJobStateReason, ReleasedJobs,
+    },
+    options::JobHoldUntil,
+    retry::{RetryPolicy, TrackedReader, retry, retry_submission},
+    supplies::Supply,
+};
+use crate::{
+    common::{
+        base::{errors::PrintersError, job::PrinterJobState},
+        traits::platform::{PlatformActions, PlatformPrinterGetters},
+    },
+    lpoptions,
+    server::{PrintServer, with_server},
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -15,6 +32,138 @@ pub enum PrinterState {
     UNKNOWN,
 }
 
+/**
+ * Scope of a setting, the current user (lpoptions in the home) or the whole system
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrinterScope {
+    User,
+    System,
+}
+
+/**
+ * Severity of a printer state reason, from the keyword suffix
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrinterStateReasonSeverity {
+    Report,
+    Warning,
+    Error,
+}
+
+macro_rules! printer_state_reasons {
+    ($($variant:ident => $keyword:expr,)*) => {
+        /**
+         * Kind of a printer state reason (IPP printer-state-reasons without the severity suffix)
+         */
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum PrinterStateReasonKind {
+            $($variant,)*
+            Other(String),
+        }
+
+        impl PrinterStateReasonKind {
+            pub fn keyword(&self) -> &str {
+                match self {
+                    $(PrinterStateReasonKind::$variant => $keyword,)*
+                    PrinterStateReasonKind::Other(keyword) => keyword,
+                }
+            }
+
+            pub fn from_keyword(keyword: &str) -> Self {
+                match keyword {
+                    $($keyword => PrinterStateReasonKind::$variant,)*
+                    keyword => PrinterStateReasonKind::Other(keyword.to_string()),
+                }
+            }
+        }
+    };
+}
+
+printer_state_reasons! {
+    ConnectingToDevice => "connecting-to-device",
+    CoverOpen => "cover-open",
+    Deactivated => "deactivated",
+    DeveloperEmpty => "developer-empty",
+    DeveloperLow => "developer-low",
+    DoorOpen => "door-open",
+    FuserOverTemp => "fuser-over-temp",
+    FuserUnderTemp => "fuser-under-temp",
+    HoldNewJobs => "hold-new-jobs",
+    IdentifyPrinterRequested => "identify-printer-requested",
+    InputTrayMissing => "input-tray-missing",
+    InterlockOpen => "interlock-open",
+    InterpreterResourceUnavailable => "interpreter-resource-unavailable",
+    MarkerSupplyEmpty => "marker-supply-empty",
+    MarkerSupplyLow => "marker-supply-low",
+    MarkerWasteAlmostFull => "marker-waste-almost-full",
+    MarkerWasteFull => "marker-waste-full",
+    MediaEmpty => "media-empty",
+    MediaJam => "media-jam",
+    MediaLow => "media-low",
+    MediaNeeded => "media-needed",
+    MovingToPaused => "moving-to-paused",
+    Offline => "offline",
+    OpcLifeOver => "opc-life-over",
+    OpcNearEol => "opc-near-eol",
+    OtherReason => "other",
+    OutputAreaAlmostFull => "output-area-almost-full",
+    OutputAreaFull => "output-area-full",
+    OutputTrayMissing => "output-tray-missing",
+    Paused => "paused",
+    Shutdown => "shutdown",
+    SpoolAreaFull => "spool-area-full",
+    StoppedPartly => "stopped-partly",
+    Stopping => "stopping",
+    TimedOut => "timed-out",
+    TonerEmpty => "toner-empty",
+    TonerLow => "toner-low",
+}
+
+/**
+ * A printer state reason, ex toner-low-warning
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrinterStateReason {
+    pub kind: PrinterStateReasonKind,
+    pub severity: PrinterStateReasonSeverity,
+}
+
+impl PrinterStateReason {
+    /**
+     * Parse a keyword, reasons without a severity suffix are errors (RFC 8011)
+     */
+    pub fn from_keyword(keyword: &str) -> Self {
+        let (keyword, severity) = if let Some(v) = keyword.strip_suffix("-report") {
+            (v, PrinterStateReasonSeverity::Report)
+        } else if let Some(v) = keyword.strip_suffix("-warning") {
+            (v, PrinterStateReasonSeverity::Warning)
+        } else {
+            (
+                keyword.strip_suffix("-error").unwrap_or(keyword),
+                PrinterStateReasonSeverity::Error,
+            )
+        };
+
+        PrinterStateReason {
+            kind: PrinterStateReasonKind::from_keyword(keyword),
+            severity,
+        }
+    }
+
+    /**
+     * Return the keyword with the severity suffix
+     */
+    pub fn keyword(&self) -> String {
+        let suffix = match self.severity {
+            PrinterStateReasonSeverity::Report => "report",
+            PrinterStateReasonSeverity::Warning => "warning",
+            PrinterStateReasonSeverity::Error => "error",
+        };
+        format!("{}-{}", self.kind.keyword(), suffix)
+    }
+}
+
 /**
  * Printer is a struct to representation the system printer
  */
@@ -80,9 +229,26 @@ pub struct Printer {
     pub state: PrinterState,
 
     /**
-     * The state reasons of the printer
+     * The state reasons of the printer (empty when there is none)
+     */
+    pub state_reasons: Vec<PrinterStateReason>,
+
+    /**
+     * The instance name (ex: duplex for office/duplex), None for the queue itself.
+     * Jobs of an instance use its lpoptions options as defaults
+     */
+    pub instance: Option<String>,
+
+    /**
+     * Server the printer was listed from, None for the default server
+     */
+    pub server: Option<PrintServer>,
+
+    /**
+     * Retry the control of the jobs of the printer (pause, cancel, move...) after transient failures,
+     * None fails at once. Jobs printed use the policy of their options
      */
-    pub state_reasons: Vec<String>,
+    pub retry: Option<RetryPolicy>,
 }
 
 impl Debug for Printer {
@@ -103,6 +269,7 @@ impl Debug for Printer {
                 \r  processor: {:?}
                 \r  data_type: {:?}
                 \r  description: {:?}
+                \r  instance: {:?}
             \r}}",
             self.name,
             self.state,
@@ -117,6 +284,7 @@ impl Debug for Printer {
             self.processor,
             self.data_type,
             self.description,
+            self.instance,
         )
     }
 }
@@ -137,6 +305,9 @@ impl Clone for Printer {
             data_type: self.data_type.clone(),
             description: self.description.clone(),
             processor: self.processor.clone(),
+            instance: self.instance.clone(),
+            server: self.server.clone(),
+            retry: self.retry.clone(),
         }
     }
 }
@@ -145,11 +316,12 @@ impl Printer {
     pub(crate) fn from_platform_printer_getters(
         platform_printer: &dyn PlatformPrinterGetters,
     ) -> Printer {
-        let mut state_reasons = platform_printer.get_state_reasons();
-
-        if state_reasons.is_empty() {
-            state_reasons.push("none".to_string());
-        }
+        let state_reasons: Vec<PrinterStateReason> = platform_printer
+            .get_state_reasons()
+            .iter()
+            .filter(|v| v.as_str() != "none")
+            .map(|v| PrinterStateReason::from_keyword(v))
+            .collect();
 
         Printer {
             name: platform_printer.get_name(),
@@ -163,19 +335,104 @@ impl Printer {
             data_type: platform_printer.get_data_type(),
             processor: platform_printer.get_processor(),
             description: platform_printer.get_description(),
-            state: PrinterState::from_platform_state(
-                platform_printer.get_state(),
-                state_reasons.join(",").as_str(),
-            ),
+            state: PrinterState::from_platform_state(platform_printer.get_state(), &state_reasons),
             state_reasons,
+            instance: platform_printer.get_instance(),
+            server: crate::server::current(),
+            retry: None,
+        }
+    }
+
+    /**
+     * Run a platform call against the server of the printer
+     */
+    fn on_server<T>(&self, f: impl FnOnce() -> T) -> T {
+        with_server(self.server.as_ref(), f)
+    }
+
+    /**
+     * Run a job control call against the server of the printer, retried by its policy
+     */
+    fn control<T>(
+        &self,
+        mut f: impl FnMut() -> Result<T, PrintersError>,
+    ) -> Result<T, PrintersError> {
+        retry(self.retry.as_ref(), || self.on_server(&mut f))
+    }
+
+    /**
+     * Destination name as used by lp, with the instance (ex: office/duplex)
+     */
+    pub fn destination_name(&self) -> String {
+        match &self.instance {
+            Some(instance) => format!("{}/{}", self.system_name, instance),
+            None => self.system_name.clone(),
         }
     }
 
+    /**
+     * Run a print on the server of the printer with the instance options added as raw properties,
+     * options of the job (raw or typed) take precedence
+     */
+    fn with_instance_options<T>(
+        &self,
+        options: PrinterJobOptions,
+        print: impl FnOnce(PrinterJobOptions) -> T,
+    ) -> T {
+        let Some(instance) = &self.instance else {
+            return self.on_server(|| print(options));
+        };
+
+        let instance_options = lpoptions::instance_options(&self.system_name, instance);
+        let typed_options = options.typed_option_names();
+        let raw_properties: Vec<(&str, &str)> = instance_options
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .filter(|(name, _)| {
+                !typed_options.contains(name)
+                    && !options.raw_properties.iter().any(|(n, _)| n == name)
+            })
+            .chain(options.raw_properties.iter().copied())
+            .collect();
+
+        self.on_server(|| {
+            print(PrinterJobOptions {
+                raw_properties: &raw_properties,
+                ..options
+            })
+        })
+    }
+
+    /**
+     * Return the supported media, sides, color modes, resolutions, formats, copies and finishings
+     */
+    pub fn capabilities(&self) -> Result<PrinterCapabilities, PrintersError> {
+        self.on_server(|| crate::Platform::get_printer_capabilities(self.system_name.as_str()))
+    }
+
+    /**
+     * Return the supplies (toner, ink, drum...) of the printer with their levels
+     */
+    pub fn supplies(&self) -> Result<Vec<Supply>, PrintersError> {
+        self.on_server(|| crate::Platform::get_printer_supplies(self.system_name.as_str()))
+    }
+
     /**
      * Print bytes
      */
-    pub fn print(&self, buffer: &[u8], options: PrinterJobOptions) -> Result<u64, PrintersError> {
-        crate::Platform::print(self.system_name.as_str(), buffer, options)
+    pub fn print(
+        &self,
+        buffer: &[u8],
+        options: PrinterJobOptions,
+    ) -> Result<PrintJobHandle, PrintersError> {
+        self.with_instance_options(options, |options| {
+            retry_submission(
+                options.retry.as_ref(),
+                || true,
+                || crate::Platform::print(self.system_name.as_str(), buffer, options.clone()),
+            )
+            .map(|id| PrintJobHandle::new(&self.system_name, id, options.retry.clone()))
+        })
     }
 
     /**
@@ -185,55 +442,269 @@ impl Printer {
         &self,
         file_path: &str,
         options: PrinterJobOptions,
-    ) -> Result<u64, PrintersError> {
-        crate::Platform::print_file(self.system_name.as_str(), file_path, options)
+    ) -> Result<PrintJobHandle, PrintersError> {
+        self.with_instance_options(options, |options| {
+            retry_submission(
+                options.retry.as_ref(),
+                || true,
+                || {
+                    crate::Platform::print_file(
+                        self.system_name.as_str(),
+                        file_path,
+                        options.clone(),
+                    )
+                },
+            )
+            .map(|id| PrintJobHandle::new(&self.system_name, id, options.retry.clone()))
+        })
+    }
+
+    /**
+     * Print from any reader, on cups the data is streamed to the server without temporary files
+     */
+    pub fn print_stream(
+        &self,
+        reader: impl Read,
+        options: PrinterJobOptions,
+    ) -> Result<PrintJobHandle, PrintersError> {
+        let touched = Cell::new(false);
+        let mut reader = TrackedReader::new(reader, &touched);
+
+        self.with_instance_options(options, |options| {
+            retry_submission(
+                options.retry.as_ref(),
+                || !touched.get(),
+                || {
+                    crate::Platform::print_stream(
+                        self.system_name.as_str(),
+                        &mut reader,
+                        options.clone(),
+                    )
+                },
+            )
+            .map(|id| PrintJobHandle::new(&self.system_name, id, options.retry.clone()))
+        })
+    }
+
+    /**
+     * Create a job to print several documents together, see PrintJobBuilder
+     */
+    pub fn create_job(&self, options: PrinterJobOptions) -> Result<PrintJobBuilder, PrintersError> {
+        self.with_instance_options(options, |options| {
+            PrintJobBuilder::new(&self.system_name, &options)
+        })
+    }
+
+    /**
+     * Pause the queue, jobs are still accepted but not printed until resumed
+     */
+    pub fn pause(&self) -> Result<(), PrintersError> {
+        self.on_server(|| {
+            crate::Platform::set_printer_state(&self.system_name, PrinterState::PAUSED)
+        })
+    }
+
+    /**
+     * Resume a paused queue
+     */
+    pub fn resume(&self) -> Result<(), PrintersError> {
+        self.on_server(|| {
+            crate::Platform::set_printer_state(&self.system_name, PrinterState::READY)
+        })
+    }
+
+    /**
+     * Accept new jobs on the queue
+     */
+    pub fn accept_jobs(&self) -> Result<(), PrintersError> {
+        self.on_server(|| {
+            crate::Platform::set_printer_accepting_jobs(&self.system_name, true, None)
+        })
+    }
+
+    /**
+     * Reject new jobs on the queue, the reason is shown as the printer state message
+     */
+    pub fn reject_jobs(&self, reason: Option<&str>) -> Result<(), PrintersError> {
+        self.on_server(|| {
+            crate::Platform::set_printer_accepting_jobs(&self.system_name, false, reason)
+        })
+    }
+
+    /**
+     * Change the state message of the queue (ex: "Out for maintenance")
+     */
+    pub fn set_state_message(&self, message: &str) -> Result<(), PrintersError> {
+        self.on_server(|| crate::Platform::set_printer_state_message(&self.system_name, message))
     }
 
     /**
      * Return active jobs
      */
     pub fn get_active_jobs(&self) -> Vec<PrinterJob> {
-        crate::Platform::get_printer_jobs(self.system_name.as_str(), true)
+        self.on_server(|| crate::Platform::get_printer_jobs(self.system_name.as_str(), true))
+            .unwrap_or_default()
     }
 
     /**
      * Return historic jobs
      */
     pub fn get_job_history(&self) -> Vec<PrinterJob> {
-        crate::Platform::get_printer_jobs(self.system_name.as_str(), false)
+        self.on_server(|| crate::Platform::get_printer_jobs(self.system_name.as_str(), false))
+            .unwrap_or_default()
+    }
+
+    /**
+     * Return a job of the printer by id
+     */
+    pub fn get_job(&self, job_id: u64) -> Option<PrinterJob> {
+        self.on_server(|| crate::Platform::get_printer_job(Some(self.system_name.as_str()), job_id))
+            .ok()
+            .flatten()
     }
 
     /**
      * Pause an printer job
      */
     pub fn pause_job(&self, job_id: u64) -> Result<(), PrintersError> {
-        crate::Platform::set_job_state(&self.system_name, job_id, PrinterJobState::PAUSED)
+        self.control(|| {
+            crate::Platform::set_job_state(&self.system_name, job_id, PrinterJobState::PAUSED)
+        })
     }
 
     /**
      * Resume an paused printer job
      */
     pub fn resume_job(&self, job_id: u64) -> Result<(), PrintersError> {
-        crate::Platform::set_job_state(&self.system_name, job_id, PrinterJobState::PROCESSING)
+        self.control(|| {
+            crate::Platform::set_job_state(&self.system_name, job_id, PrinterJobState::PROCESSING)
+        })
     }
 
     /**
      * restart an printer job
      */
     pub fn restart_job(&self, job_id: u64) -> Result<(), PrintersError> {
-        crate::Platform::set_job_state(&self.system_name, job_id, PrinterJobState::PENDING)
+        self.control(|| {
+            crate::Platform::set_job_state(&self.system_name, job_id, PrinterJobState::PENDING)
+        })
+    }
+
+    /**
+     * Release a held or scheduled printer job for printing now
+     */
+    pub fn release_job(&self, job_id: u64) -> Result<(), PrintersError> {
+        self.control(|| {
+            crate::Platform::set_job_state(&self.system_name, job_id, PrinterJobState::PROCESSING)
+        })
+    }
+
+    /**
+     * Return the jobs of an owner held until they are released, ex: secure release jobs.
+     * Jobs held until a time or a named period are not included (CUPS / IPP only)
+     */
+    pub fn get_held_jobs(&self, owner: &str) -> Vec<PrinterJob> {
+        self.held_jobs(owner).unwrap_or_default()
+    }
+
+    /**
+     * Release every held job of an owner, returning the released jobs and the ones that failed.
+     * Fails only when the held jobs cannot be listed
+     */
+    pub fn release_held_jobs(&self, owner: &str) -> Result<ReleasedJobs, PrintersError> {
+        let mut outcome = ReleasedJobs::default();
+        for job in self.held_jobs(owner)? {
+            match self.release_job(job.id) {
+                Ok(()) => outcome.released.push(job.id),
+                Err(error) => outcome.failed.push((job.id, error)),
+            }
+        }
+        Ok(outcome)
+    }
+
+    fn held_jobs(&self, owner: &str) -> Result<Vec<PrinterJob>, PrintersError> {
+        let jobs = self.on_server(|| crate::Platform::get_printer_jobs(&self.system_name, true))?;
+        Ok(jobs
+            .into_iter()
+            .filter(|j| j.owner == owner && j.state == PrinterJobState::PAUSED)
+            .filter(|j| {
+                j.hold_until == Some(JobHoldUntil::Indefinite)
+                    || j.state_reasons
+                        .contains(&PrinterJobStateReason::JobPasswordWait)
+            })
+            .collect())
     }
 
     /**
      * Cancel an printer job
      */
     pub fn cancel_job(&self, job_id: u64) -> Result<(), PrintersError> {
-        crate::Platform::set_job_state(&self.system_name, job_id, PrinterJobState::CANCELLED)
+        self.control(|| {
+            crate::Platform::set_job_state(&self.system_name, job_id, PrinterJobState::CANCELLED)
+        })
+    }
+
+    /**
+     * Return a document of a queued or retained job, numbered from 1, with its format and a reader of its bytes.
+     * On CUPS the server keeps the documents of completed jobs only with PreserveJobFiles
+     */
+    pub fn get_job_document(
+        &self,
+        job_id: u64,
+        document_number: u32,
+    ) -> Result<JobDocument, PrintersError> {
+        self.on_server(|| {
+            crate::Platform::get_job_document(&self.system_name, job_id, document_number)
+        })
+    }
+
+    /**
+     * Change the priority, hold, copies, name or page ranges of a job that has not started (CUPS / IPP only)
+     */
+    pub fn update_job(&self, job_id: u64, changes: JobChanges) -> Result<(), PrintersError> {
+        self.control(|| crate::Platform::set_job_attributes(&self.system_name, job_id, &changes))
+    }
+
+    /**
+     * Cancel the active jobs of the printer matching the filter in a single request,
+     * returning the ids of the jobs affected
+     */
+    pub fn cancel_all_jobs(&self, filter: CancelJobsFilter) -> Result<Vec<u64>, PrintersError> {
+        self.control(|| crate::Platform::cancel_jobs(&self.system_name, &filter, false))
+    }
+
+    /**
+     * Cancel the jobs of the printer matching the filter and remove them from the history,
+     * returning the ids of the jobs affected
+     */
+    pub fn purge_all_jobs(&self, filter: CancelJobsFilter) -> Result<Vec<u64>, PrintersError> {
+        self.control(|| crate::Platform::cancel_jobs(&self.system_name, &filter, true))
+    }
+
+    /**
+     * Move a job of the printer to the target queue (CUPS / IPP only),
+     * fails with PrintersFailure::NotAcceptingJobs when the target rejects jobs
+     */
+    pub fn move_job(&self, job_id: u64, target: &str) -> Result<(), PrintersError> {
+        let (target, _) = crate::lpoptions::split_name(target);
+        self.control(|| crate::Platform::move_jobs(&self.system_name, Some(job_id), target))
+    }
+
+    /**
+     * Move every job of the printer to the target queue (CUPS / IPP only),
+     * fails with PrintersFailure::NotAcceptingJobs when the target rejects jobs
+     */
+    pub fn move_all_jobs(&self, target: &str) -> Result<(), PrintersError> {
+        let (target, _) = crate::lpoptions::split_name(target);
+        self.control(|| crate::Platform::move_jobs(&self.system_name, None, target))
     }
 }
 
 impl PrinterState {
-    pub(crate) fn from_platform_state(platform_state: u64, state_reasons: &str) -> Self {
+    pub(crate) fn from_platform_state(
+        platform_state: u64,
+        state_reasons: &[PrinterStateReason],
+    ) -> Self {
         crate::Platform::parse_printer_state(platform_state, state_reasons)
     }
 }
@@ -0,0 +1,255 @@
+use std::{
+    cell::Cell,
+    error::Error,
+    io::{self, ErrorKind, Read},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    common::base::errors::{PrintersError, PrintersFailure},
+    ipp::IppStatusCode,
+};
+
+/**
+ * Transient failures a RetryPolicy can retry
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RetryClass {
+    /**
+     * The server is busy or temporarily failing (server-error-busy, server-error-temporary-error)
+     */
+    ServerBusy,
+    /**
+     * The server could not be reached or dropped the connection, ex: while cupsd restarts.
+     * A submission is only retried when it failed to connect, as a connection lost later
+     * may come after the server received the job
+     */
+    Unreachable,
+    /**
+     * The queue is not accepting jobs (server-error-not-accepting-jobs)
+     */
+    NotAcceptingJobs,
+}
+
+impl RetryClass {
+    /**
+     * Check if an error belongs to the class
+     */
+    pub fn matches(&self, error: &PrintersError) -> bool {
+        match self {
+            RetryClass::ServerBusy => matches!(
+                error.status,
+                Some(IppStatusCode::ServerErrorBusy | IppStatusCode::ServerErrorTemporaryError)
+            ),
+            RetryClass::Unreachable => {
+                // libcups reports connection failures as server-error-service-unavailable
+                error.status == Some(IppStatusCode::ServerErrorServiceUnavailable)
+                    || io_error_kind(error).is_some_and(|kind| {
+                        matches!(
+                            kind,
+                            ErrorKind::ConnectionRefused
+                                | ErrorKind::ConnectionReset
+                                | ErrorKind::ConnectionAborted
+                                | ErrorKind::NotConnected
+                                | ErrorKind::BrokenPipe
+                                | ErrorKind::TimedOut
+                                | ErrorKind::UnexpectedEof
+                        )
+                    })
+            }
+            RetryClass::NotAcceptingJobs => {
+                error.failure == PrintersFailure::NotAcceptingJobs
+                    || error.status == Some(IppStatusCode::ServerErrorNotAcceptingJobs)
+            }
+        }
+    }
+
+    /**
+     * Check if a failed submission belongs to the class and was never received by the server,
+     * so sending it again cannot print the job twice
+     */
+    pub fn matches_unsent(&self, error: &PrintersError) -> bool {
+        match self {
+            // Timeouts, resets and closed connections may come after the server got the job
+            RetryClass::Unreachable => io_error_kind(error).is_some_and(|kind| {
+                matches!(kind, ErrorKind::ConnectionRefused | ErrorKind::NotConnected)
+            }),
+            // The other classes are answers of the server refusing the request
+            _ => self.matches(error),
+        }
+    }
+}
+
+/**
+ * Kind of the io error an error comes from
+ */
+fn io_error_kind(error: &PrintersError) -> Option<ErrorKind> {
+    error
+        .source()
+        .and_then(|e| e.downcast_ref::<io::Error>())
+        .map(|e| e.kind())
+}
+
+/**
+ * RetryPolicy retries the submission and the control of a job after transient failures,
+ * waiting an exponential backoff between the attempts.
+ * A job may be printed twice if its submission was sent again after the server received it,
+ * so submissions are only retried for failures happening before the request is sent
+ * (see RetryClass::matches_unsent). Controlling a job has the same effect when repeated,
+ * it is retried for every failure of the classes
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /**
+     * Attempts of an operation, including the first one
+     */
+    pub max_attempts: u16,
+
+    /**
+     * Wait before the second attempt, doubled before each next one
+     */
+    pub initial_backoff: Duration,
+
+    /**
+     * Longest wait between two attempts
+     */
+    pub max_backoff: Duration,
+
+    /**
+     * Failures that are retried, the others are returned at once
+     */
+    pub retry_on: Vec<RetryClass>,
+
+    /**
+     * Time from the first attempt after which no new attempt is made
+     */
+    pub deadline: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /**
+     * Retry busy and unreachable servers, waiting from 500 milliseconds up to 30 seconds
+     */
+    pub fn new(max_attempts: u16) -> Self {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            retry_on: vec![RetryClass::ServerBusy, RetryClass::Unreachable],
+            deadline: None,
+        }
+    }
+
+    /**
+     * Check if an error is retried by the policy
+     */
+    pub fn is_retryable(&self, error: &PrintersError) -> bool {
+        self.retry_on.iter().any(|class| class.matches(error))
+    }
+
+    /**
+     * Check if a failed submission is sent again by the policy
+     */
+    pub fn is_retryable_submission(&self, error: &PrintersError) -> bool {
+        self.retry_on
+            .iter()
+            .any(|class| class.matches_unsent(error))
+    }
+
+    /**
+     * Wait after a failed attempt, from 1
+     */
+    pub fn backoff(&self, attempt: u16) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1) as u32);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/**
+ * Run an operation controlling a job with a policy, or once without.
+ * The error of the last attempt is returned with the number of attempts made
+ */
+pub(crate) fn retry<T>(
+    policy: Option<&RetryPolicy>,
+    operation: impl FnMut() -> Result<T, PrintersError>,
+) -> Result<T, PrintersError> {
+    run(policy, RetryPolicy::is_retryable, operation)
+}
+
+/**
+ * Run a submission with a policy, failed attempts are only sent again while can_retry returns true
+ * and when the server did not receive them
+ */
+pub(crate) fn retry_submission<T>(
+    policy: Option<&RetryPolicy>,
+    can_retry: impl Fn() -> bool,
+    operation: impl FnMut() -> Result<T, PrintersError>,
+) -> Result<T, PrintersError> {
+    run(
+        policy,
+        |policy, error| can_retry() && policy.is_retryable_submission(error),
+        operation,
+    )
+}
+
+fn run<T>(
+    policy: Option<&RetryPolicy>,
+    is_retryable: impl Fn(&RetryPolicy, &PrintersError) -> bool,
+    mut operation: impl FnMut() -> Result<T, PrintersError>,
+) -> Result<T, PrintersError> {
+    let Some(policy) = policy else {
+        return operation();
+    };
+
+    let start = Instant::now();
+    let mut attempt = 1;
+    loop {
+        let mut error = match operation() {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+
+        let backoff = policy.backoff(attempt);
+        let expired = policy
+            .deadline
+            .is_some_and(|deadline| start.elapsed() + backoff >= deadline);
+
+        if attempt >= policy.max_attempts || expired || !is_retryable(policy, &error) {
+            if attempt > 1 {
+                error.message = format!("{} (after {attempt} attempts)", error.message);
+            }
+            error.attempts = attempt;
+            return Err(error);
+        }
+
+        thread::sleep(backoff);
+        attempt += 1;
+    }
+}
+
+/**
+ * Reader flagging when data was read, a stream can only be sent again while untouched
+ */
+pub(crate) struct TrackedReader<'a, R> {
+    reader: R,
+    touched: &'a Cell<bool>,
+}
+
+impl<'a, R: Read> TrackedReader<'a, R> {
+    pub(crate) fn new(reader: R, touched: &'a Cell<bool>) -> Self {
+        TrackedReader { reader, touched }
+    }
+}
+
+impl<R: Read> Read for TrackedReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.reader.read(buf)?;
+        if count > 0 {
+            self.touched.set(true);
+        }
+        Ok(count)
+    }
+}
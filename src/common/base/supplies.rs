@@ -0,0 +1,227 @@
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+use crate::{
+    common::base::errors::PrintersError,
+    ipp::{IppAttributeGroup, IppGroupTag, IppMessage, IppOperation},
+};
+
+/**
+ * Printer attributes requested to build the supplies
+ */
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+pub(crate) const SUPPLIES_ATTRIBUTES: &[&str] = &[
+    "marker-names",
+    "marker-colors",
+    "marker-levels",
+    "marker-low-levels",
+    "marker-high-levels",
+    "marker-types",
+];
+
+/**
+ * Type of a printer supply (IPP marker-types)
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum SupplyType {
+    Toner,
+    TonerCartridge,
+    WasteToner,
+    Ink,
+    InkCartridge,
+    InkRibbon,
+    WasteInk,
+    Opc,
+    Developer,
+    Fuser,
+    FuserOil,
+    TransferUnit,
+    CleanerUnit,
+    SolidWax,
+    RibbonWax,
+    WasteWax,
+    Staples,
+    Other(String),
+}
+
+impl SupplyType {
+    pub fn keyword(&self) -> &str {
+        match self {
+            SupplyType::Toner => "toner",
+            SupplyType::TonerCartridge => "toner-cartridge",
+            SupplyType::WasteToner => "waste-toner",
+            SupplyType::Ink => "ink",
+            SupplyType::InkCartridge => "ink-cartridge",
+            SupplyType::InkRibbon => "ink-ribbon",
+            SupplyType::WasteInk => "waste-ink",
+            SupplyType::Opc => "opc",
+            SupplyType::Developer => "developer",
+            SupplyType::Fuser => "fuser",
+            SupplyType::FuserOil => "fuser-oil",
+            SupplyType::TransferUnit => "transfer-unit",
+            SupplyType::CleanerUnit => "cleaner-unit",
+            SupplyType::SolidWax => "solid-wax",
+            SupplyType::RibbonWax => "ribbon-wax",
+            SupplyType::WasteWax => "waste-wax",
+            SupplyType::Staples => "staples",
+            SupplyType::Other(keyword) => keyword,
+        }
+    }
+
+    pub fn from_keyword(keyword: &str) -> Self {
+        match keyword {
+            "toner" => SupplyType::Toner,
+            "toner-cartridge" => SupplyType::TonerCartridge,
+            "waste-toner" => SupplyType::WasteToner,
+            "ink" => SupplyType::Ink,
+            "ink-cartridge" => SupplyType::InkCartridge,
+            "ink-ribbon" => SupplyType::InkRibbon,
+            "waste-ink" => SupplyType::WasteInk,
+            "opc" => SupplyType::Opc,
+            "developer" => SupplyType::Developer,
+            "fuser" => SupplyType::Fuser,
+            "fuser-oil" => SupplyType::FuserOil,
+            "transfer-unit" => SupplyType::TransferUnit,
+            "cleaner-unit" => SupplyType::CleanerUnit,
+            "solid-wax" => SupplyType::SolidWax,
+            "ribbon-wax" => SupplyType::RibbonWax,
+            "waste-wax" => SupplyType::WasteWax,
+            "staples" => SupplyType::Staples,
+            keyword => SupplyType::Other(keyword.to_string()),
+        }
+    }
+}
+
+/**
+ * Level of a printer supply
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SupplyLevel {
+    /**
+     * Percentage of the supply remaining (0-100)
+     */
+    Percent(u8),
+    /**
+     * The printer cannot report a level for this supply
+     */
+    Unavailable,
+    /**
+     * The level is currently unknown
+     */
+    Unknown,
+    /**
+     * Some supply remains, but the amount is unknown
+     */
+    SomeRemaining,
+}
+
+impl SupplyLevel {
+    /**
+     * Parse a marker-levels value (-1 unavailable, -2 unknown, -3 some remaining)
+     */
+    pub fn from_value(value: i32) -> Self {
+        match value {
+            -3 => SupplyLevel::SomeRemaining,
+            -1 => SupplyLevel::Unavailable,
+            v if v >= 0 => SupplyLevel::Percent(v.min(100) as u8),
+            _ => SupplyLevel::Unknown,
+        }
+    }
+}
+
+/**
+ * Supply is a printer marker (toner, ink, drum...) with its current level
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Supply {
+    /**
+     * Visible name of the supply (ex: Black Toner)
+     */
+    pub name: String,
+
+    /**
+     * Color of the supply as #RRGGBB, may contain several colors or "none"
+     */
+    pub color: String,
+
+    /**
+     * Current level of the supply
+     */
+    pub level: SupplyLevel,
+
+    /**
+     * Level at or below which the supply is low (percent)
+     */
+    pub low_level: Option<u8>,
+
+    /**
+     * Level at or above which the supply is full, or almost full for waste supplies (percent)
+     */
+    pub high_level: Option<u8>,
+
+    /**
+     * Type of the supply
+     */
+    pub supply_type: SupplyType,
+}
+
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+impl Supply {
+    /**
+     * Build the supplies from a Get-Printer-Attributes response
+     */
+    pub(crate) fn from_ipp_response(response: &IppMessage) -> Result<Vec<Self>, PrintersError> {
+        if !response.is_successful() {
+            return Err(PrintersError::from_response(
+                IppOperation::GetPrinterAttributes,
+                response,
+                PrintersError::error,
+            ));
+        }
+
+        response
+            .group(IppGroupTag::Printer)
+            .map(Self::from_ipp_attributes)
+            .ok_or_else(|| PrintersError::error("printer attributes not found"))
+    }
+
+    fn from_ipp_attributes(attributes: &IppAttributeGroup) -> Vec<Self> {
+        let integers = |name: &str| -> Vec<i32> {
+            attributes
+                .get(name)
+                .map(|a| a.values.iter().filter_map(|v| v.as_i32()).collect())
+                .unwrap_or_default()
+        };
+        let percent = |values: &[i32], index: usize| {
+            values
+                .get(index)
+                .filter(|v| **v >= 0)
+                .map(|v| (*v).min(100) as u8)
+        };
+
+        let colors = attributes.strings("marker-colors");
+        let types = attributes.strings("marker-types");
+        let levels = integers("marker-levels");
+        let low_levels = integers("marker-low-levels");
+        let high_levels = integers("marker-high-levels");
+
+        // All the marker-* attributes are parallel arrays indexed by the marker names
+        attributes
+            .strings("marker-names")
+            .into_iter()
+            .enumerate()
+            .map(|(index, name)| Supply {
+                name,
+                color: colors.get(index).cloned().unwrap_or_default(),
+                level: levels
+                    .get(index)
+                    .map(|v| SupplyLevel::from_value(*v))
+                    .unwrap_or(SupplyLevel::Unknown),
+                low_level: percent(&low_levels, index),
+                high_level: percent(&high_levels, index),
+                supply_type: types
+                    .get(index)
+                    .map(|v| SupplyType::from_keyword(v))
+                    .unwrap_or(SupplyType::Other("unknown".to_string())),
+            })
+            .collect()
+    }
+}
@@ -1,9 +1,17 @@
-use crate::common::base::{
-    errors::PrintersError,
-    job::{PrinterJobOptions, PrinterJobState},
-    printer::{Printer, PrinterState},
+use crate::{
+    admin::PrinterConfig,
+    common::base::{
+        capabilities::PrinterCapabilities,
+        errors::PrintersError,
+        job::{
+            CancelJobsFilter, JobChanges, JobDocument, PrinterJob, PrinterJobOptions,
+            PrinterJobState,
+        },
+        printer::{Printer, PrinterScope, PrinterState, PrinterStateReason},
+        supplies::Supply,
+    },
 };
-use std::time::SystemTime;
+use std::{io::Read, time::SystemTime};
 
 pub trait PlatformPrinterGetters {
     fn get_name(&self) -> String;
@@ -19,6 +27,7 @@ pub trait PlatformPrinterGetters {
     fn get_processor(&self) -> String;
     fn get_description(&self) -> String;
     fn get_data_type(&self) -> String;
+    fn get_instance(&self) -> Option<String>;
 }
 
 pub trait PlatformPrinterJobGetters {
@@ -30,6 +39,15 @@ pub trait PlatformPrinterJobGetters {
     fn get_created_at(&self) -> SystemTime;
     fn get_processed_at(&self) -> Option<SystemTime>;
     fn get_completed_at(&self) -> Option<SystemTime>;
+    fn get_owner(&self) -> String;
+    fn get_size(&self) -> u64;
+    fn get_priority(&self) -> u32;
+    fn get_state_reasons(&self) -> Vec<String>;
+    fn get_printer_state_message(&self) -> String;
+    fn get_impressions_completed(&self) -> u32;
+    fn get_pages_completed(&self) -> u32;
+    fn get_document_count(&self) -> u32;
+    fn get_hold_until(&self) -> Option<String>;
 }
 
 pub trait PlatformActions {
@@ -44,17 +62,77 @@ pub trait PlatformActions {
         file_path: &str,
         options: PrinterJobOptions,
     ) -> Result<u64, PrintersError>;
+    fn print_stream(
+        printer_system_name: &str,
+        reader: &mut dyn Read,
+        options: PrinterJobOptions,
+    ) -> Result<u64, PrintersError>;
+    fn create_job(
+        printer_system_name: &str,
+        options: &PrinterJobOptions,
+    ) -> Result<u64, PrintersError>;
+    fn send_document(
+        printer_system_name: &str,
+        job_id: u64,
+        document_name: &str,
+        reader: &mut dyn Read,
+        document_format: Option<&str>,
+        last_document: bool,
+    ) -> Result<(), PrintersError>;
     fn get_printer_jobs(
         printer_name: &str,
         active_only: bool,
-    ) -> Vec<crate::common::base::job::PrinterJob>;
+    ) -> Result<Vec<PrinterJob>, PrintersError>;
+    fn get_printer_job(
+        printer_name: Option<&str>,
+        job_id: u64,
+    ) -> Result<Option<PrinterJob>, PrintersError>;
     fn get_default_printer() -> Option<Printer>;
     fn get_printer_by_name(printer_name: &str) -> Option<Printer>;
-    fn parse_printer_state(platform_state: u64, state_reasons: &str) -> PrinterState;
+    fn get_printer_capabilities(printer_name: &str) -> Result<PrinterCapabilities, PrintersError>;
+    fn get_printer_supplies(printer_name: &str) -> Result<Vec<Supply>, PrintersError>;
+    fn set_printer_state(printer_name: &str, state: PrinterState) -> Result<(), PrintersError>;
+    fn set_printer_accepting_jobs(
+        printer_name: &str,
+        accepting: bool,
+        reason: Option<&str>,
+    ) -> Result<(), PrintersError>;
+    fn set_printer_state_message(printer_name: &str, message: &str) -> Result<(), PrintersError>;
+    fn set_default_printer(
+        printer_name: &str,
+        instance: Option<&str>,
+        scope: PrinterScope,
+    ) -> Result<(), PrintersError>;
+    fn add_modify_printer(printer_name: &str, config: &PrinterConfig) -> Result<(), PrintersError>;
+    fn delete_printer(printer_name: &str) -> Result<(), PrintersError>;
+    fn parse_printer_state(
+        platform_state: u64,
+        state_reasons: &[PrinterStateReason],
+    ) -> PrinterState;
     fn parse_printer_job_state(platform_state: u64) -> PrinterJobState;
     fn set_job_state(
         printer_name: &str,
         job_id: u64,
         state: PrinterJobState,
     ) -> Result<(), PrintersError>;
+    fn get_job_document(
+        printer_name: &str,
+        job_id: u64,
+        document_number: u32,
+    ) -> Result<JobDocument, PrintersError>;
+    fn set_job_attributes(
+        printer_name: &str,
+        job_id: u64,
+        changes: &JobChanges,
+    ) -> Result<(), PrintersError>;
+    fn cancel_jobs(
+        printer_name: &str,
+        filter: &CancelJobsFilter,
+        purge: bool,
+    ) -> Result<Vec<u64>, PrintersError>;
+    fn move_jobs(
+        printer_name: &str,
+        job_id: Option<u64>,
+        target_name: &str,
+    ) -> Result<(), PrintersError>;
 }
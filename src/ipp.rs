@@ -0,0 +1,34 @@
+//! Encoder and decoder of IPP messages (RFC 8010) with typed values.
+//!
+//!```rust
+//! use printers::ipp::{IppAttribute, IppGroupTag, IppMessage, IppOperation, IppValue};
+//!
+//! let mut request = IppMessage::request(IppOperation::GetPrinterAttributes, 1);
+//! request.add(
+//!     IppGroupTag::Operation,
+//!     IppAttribute::new("printer-uri", IppValue::Uri("ipp://localhost/printers/my_printer".into())),
+//! );
+//!
+//! let bytes = request.encode();
+//! let decoded = IppMessage::decode(&bytes).unwrap();
+//! assert_eq!(decoded.operation(), IppOperation::GetPrinterAttributes);
+//! ```
+
+mod attribute;
+mod date;
+mod encoding;
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+pub(crate) mod job;
+mod message;
+mod operation;
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+pub(crate) mod requests;
+mod status;
+mod value;
+
+pub use attribute::{IppAttribute, IppAttributeGroup, IppGroupTag};
+pub use date::IppDateTime;
+pub use message::{IppMessage, IppVersion};
+pub use operation::IppOperation;
+pub use status::IppStatusCode;
+pub use value::{IppResolutionUnits, IppValue};
@@ -0,0 +1,157 @@
+use crate::ipp::value::IppValue;
+
+/**
+ * Delimiter tags that start an attribute group
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IppGroupTag {
+    Operation,
+    Job,
+    Printer,
+    Unsupported,
+    Subscription,
+    EventNotification,
+    Resource,
+    Document,
+    System,
+    Other(u8),
+}
+
+impl IppGroupTag {
+    pub(crate) fn from_byte(value: u8) -> Self {
+        match value {
+            0x01 => IppGroupTag::Operation,
+            0x02 => IppGroupTag::Job,
+            0x04 => IppGroupTag::Printer,
+            0x05 => IppGroupTag::Unsupported,
+            0x06 => IppGroupTag::Subscription,
+            0x07 => IppGroupTag::EventNotification,
+            0x08 => IppGroupTag::Resource,
+            0x09 => IppGroupTag::Document,
+            0x0A => IppGroupTag::System,
+            v => IppGroupTag::Other(v),
+        }
+    }
+
+    pub(crate) fn to_byte(self) -> u8 {
+        match self {
+            IppGroupTag::Operation => 0x01,
+            IppGroupTag::Job => 0x02,
+            IppGroupTag::Printer => 0x04,
+            IppGroupTag::Unsupported => 0x05,
+            IppGroupTag::Subscription => 0x06,
+            IppGroupTag::EventNotification => 0x07,
+            IppGroupTag::Resource => 0x08,
+            IppGroupTag::Document => 0x09,
+            IppGroupTag::System => 0x0A,
+            IppGroupTag::Other(v) => v,
+        }
+    }
+}
+
+/**
+ * A named IPP attribute with one or more values
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct IppAttribute {
+    pub name: String,
+    pub values: Vec<IppValue>,
+}
+
+impl IppAttribute {
+    pub fn new(name: &str, value: IppValue) -> Self {
+        Self::with_values(name, vec![value])
+    }
+
+    pub fn with_values(name: &str, values: Vec<IppValue>) -> Self {
+        IppAttribute {
+            name: name.to_string(),
+            values,
+        }
+    }
+
+    /**
+     * Return the first value of the attribute
+     */
+    pub fn value(&self) -> Option<&IppValue> {
+        self.values.first()
+    }
+}
+
+/**
+ * A group of attributes delimited by a group tag
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct IppAttributeGroup {
+    pub tag: IppGroupTag,
+    pub attributes: Vec<IppAttribute>,
+}
+
+impl IppAttributeGroup {
+    pub fn new(tag: IppGroupTag) -> Self {
+        IppAttributeGroup {
+            tag,
+            attributes: Vec::new(),
+        }
+    }
+
+    /**
+     * Find an attribute by name
+     */
+    pub fn get(&self, name: &str) -> Option<&IppAttribute> {
+        self.attributes.iter().find(|a| a.name == name)
+    }
+
+    /**
+     * Return the first value of an attribute by name
+     */
+    pub fn value(&self, name: &str) -> Option<&IppValue> {
+        self.get(name).and_then(|a| a.value())
+    }
+
+    /**
+     * Return the first value of an attribute as a string, or an empty string
+     */
+    pub fn string(&self, name: &str) -> String {
+        self.value(name)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /**
+     * Return all string values of an attribute
+     */
+    pub fn strings(&self, name: &str) -> Vec<String> {
+        self.get(name)
+            .map(|a| {
+                a.values
+                    .iter()
+                    .filter_map(|v| v.as_str())
+                    .map(|v| v.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /**
+     * Return the first value of an attribute as an integer
+     */
+    pub fn integer(&self, name: &str) -> Option<i32> {
+        self.value(name).and_then(|v| v.as_i32())
+    }
+
+    /**
+     * Add or replace an attribute
+     */
+    pub fn set(&mut self, attribute: IppAttribute) {
+        match self
+            .attributes
+            .iter_mut()
+            .find(|a| a.name == attribute.name)
+        {
+            Some(current) => *current = attribute,
+            None => self.attributes.push(attribute),
+        }
+    }
+}
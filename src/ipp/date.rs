@@ -0,0 +1,123 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/**
+ * The dateTime syntax (RFC 2579 DateAndTime)
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IppDateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub deci_seconds: u8,
+    /**
+     * Offset from UTC in minutes, negative for west of UTC
+     */
+    pub utc_offset: i16,
+}
+
+impl IppDateTime {
+    /**
+     * Create a UTC date from a system time
+     */
+    pub fn from_system_time(time: SystemTime) -> Self {
+        let elapsed = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let seconds = elapsed.as_secs() as i64;
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+        let seconds_of_day = seconds.rem_euclid(86400);
+
+        IppDateTime {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: (seconds_of_day / 3600) as u8,
+            minutes: (seconds_of_day % 3600 / 60) as u8,
+            seconds: (seconds_of_day % 60) as u8,
+            deci_seconds: (elapsed.subsec_millis() / 100) as u8,
+            utc_offset: 0,
+        }
+    }
+
+    /**
+     * Convert the date to a system time, dates before the unix epoch are clamped to it
+     */
+    pub fn to_system_time(&self) -> SystemTime {
+        let days = days_from_civil(self.year as i64, self.month as i64, self.day as i64);
+        let seconds =
+            days * 86400 + self.hour as i64 * 3600 + self.minutes as i64 * 60 + self.seconds as i64
+                - self.utc_offset as i64 * 60;
+
+        if seconds < 0 {
+            return UNIX_EPOCH;
+        }
+
+        UNIX_EPOCH
+            + Duration::from_secs(seconds as u64)
+            + Duration::from_millis(self.deci_seconds as u64 * 100)
+    }
+
+    pub(crate) fn to_bytes(self) -> [u8; 11] {
+        let year = self.year.to_be_bytes();
+        let offset = self.utc_offset.unsigned_abs();
+        [
+            year[0],
+            year[1],
+            self.month,
+            self.day,
+            self.hour,
+            self.minutes,
+            self.seconds,
+            self.deci_seconds,
+            if self.utc_offset < 0 { b'-' } else { b'+' },
+            (offset / 60) as u8,
+            (offset % 60) as u8,
+        ]
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 11 {
+            return None;
+        }
+
+        let offset = bytes[9] as i16 * 60 + bytes[10] as i16;
+        Some(IppDateTime {
+            year: u16::from_be_bytes([bytes[0], bytes[1]]),
+            month: bytes[2],
+            day: bytes[3],
+            hour: bytes[4],
+            minutes: bytes[5],
+            seconds: bytes[6],
+            deci_seconds: bytes[7],
+            utc_offset: if bytes[8] == b'-' { -offset } else { offset },
+        })
+    }
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
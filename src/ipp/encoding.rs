@@ -0,0 +1,69 @@
+use std::io::{Read, Write};
+
+use crate::common::base::errors::PrintersError;
+
+pub fn write_bytes(writer: &mut dyn Write, bytes: &[u8]) -> Result<(), PrintersError> {
+    writer.write_all(bytes).map_err(PrintersError::error)
+}
+
+pub fn write_u16(writer: &mut dyn Write, value: u16) -> Result<(), PrintersError> {
+    write_bytes(writer, &value.to_be_bytes())
+}
+
+pub fn write_i32(writer: &mut dyn Write, value: i32) -> Result<(), PrintersError> {
+    write_bytes(writer, &value.to_be_bytes())
+}
+
+/**
+ * Write a two bytes length followed by the bytes
+ */
+pub fn write_sized(writer: &mut dyn Write, bytes: &[u8]) -> Result<(), PrintersError> {
+    let size = u16::try_from(bytes.len())
+        .map_err(|_| PrintersError::error("IPP value exceeds 65535 bytes"))?;
+    write_u16(writer, size)?;
+    write_bytes(writer, bytes)
+}
+
+pub fn read_bytes<const N: usize>(reader: &mut dyn Read) -> Result<[u8; N], PrintersError> {
+    let mut buffer = [0; N];
+    reader
+        .read_exact(&mut buffer)
+        .map_err(|e| PrintersError::error(format!("Truncated IPP message: {e}")))?;
+    Ok(buffer)
+}
+
+pub fn read_u8(reader: &mut dyn Read) -> Result<u8, PrintersError> {
+    Ok(read_bytes::<1>(reader)?[0])
+}
+
+pub fn read_u16(reader: &mut dyn Read) -> Result<u16, PrintersError> {
+    Ok(u16::from_be_bytes(read_bytes(reader)?))
+}
+
+pub fn read_u32(reader: &mut dyn Read) -> Result<u32, PrintersError> {
+    Ok(u32::from_be_bytes(read_bytes(reader)?))
+}
+
+/**
+ * Read a two bytes length followed by the bytes
+ */
+pub fn read_sized(reader: &mut dyn Read) -> Result<Vec<u8>, PrintersError> {
+    let size = read_u16(reader)? as usize;
+    let mut buffer = vec![0; size];
+    reader
+        .read_exact(&mut buffer)
+        .map_err(|e| PrintersError::error(format!("Truncated IPP message: {e}")))?;
+    Ok(buffer)
+}
+
+pub fn bytes_to_i32(bytes: &[u8], offset: usize) -> Result<i32, PrintersError> {
+    bytes
+        .get(offset..offset + 4)
+        .and_then(|v| v.try_into().ok())
+        .map(i32::from_be_bytes)
+        .ok_or_else(|| PrintersError::error("Invalid IPP integer value"))
+}
+
+pub fn bytes_to_string(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).to_string())
+}
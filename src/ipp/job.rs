@@ -0,0 +1,217 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{
+    common::{base::errors::PrintersError, traits::platform::PlatformPrinterJobGetters},
+    ipp::{
+        IppAttribute, IppAttributeGroup, IppGroupTag, IppMessage, IppOperation, IppStatusCode,
+        IppValue,
+        requests::{job_request, job_uri_request, percent_decode, printer_request},
+    },
+};
+
+/**
+ * Job attributes requested to build a PrinterJob
+ */
+pub const JOB_ATTRIBUTES: &[&str] = &[
+    "job-id",
+    "job-name",
+    "job-state",
+    "job-state-reasons",
+    "job-printer-uri",
+    "job-printer-state-message",
+    "job-originating-user-name",
+    "job-k-octets",
+    "job-priority",
+    "job-impressions-completed",
+    "job-media-sheets-completed",
+    "number-of-documents",
+    "job-hold-until",
+    "document-format",
+    "time-at-creation",
+    "time-at-processing",
+    "time-at-completed",
+];
+
+/**
+ * Attributes of a job returned by an IPP server
+ */
+pub struct IppJob {
+    pub attributes: IppAttributeGroup,
+}
+
+impl IppJob {
+    fn get_time(&self, name: &str) -> Option<SystemTime> {
+        self.attributes
+            .integer(name)
+            .filter(|v| *v > 0)
+            .map(|v| UNIX_EPOCH + Duration::from_secs(v as u64))
+    }
+
+    fn get_count(&self, name: &str) -> u32 {
+        self.attributes.integer(name).unwrap_or_default().max(0) as u32
+    }
+}
+
+impl PlatformPrinterJobGetters for IppJob {
+    fn get_id(&self) -> u64 {
+        self.attributes.integer("job-id").unwrap_or_default() as u64
+    }
+
+    fn get_name(&self) -> String {
+        self.attributes.string("job-name")
+    }
+
+    fn get_state(&self) -> u64 {
+        self.attributes.integer("job-state").unwrap_or_default() as u64
+    }
+
+    fn get_printer(&self) -> String {
+        percent_decode(
+            self.attributes
+                .string("job-printer-uri")
+                .rsplit('/')
+                .next()
+                .unwrap_or_default(),
+        )
+    }
+
+    fn get_media_type(&self) -> String {
+        self.attributes.string("document-format")
+    }
+
+    fn get_created_at(&self) -> SystemTime {
+        self.get_time("time-at-creation")
+            .unwrap_or(SystemTime::UNIX_EPOCH)
+    }
+
+    fn get_processed_at(&self) -> Option<SystemTime> {
+        self.get_time("time-at-processing")
+    }
+
+    fn get_completed_at(&self) -> Option<SystemTime> {
+        self.get_time("time-at-completed")
+    }
+
+    fn get_owner(&self) -> String {
+        self.attributes.string("job-originating-user-name")
+    }
+
+    fn get_size(&self) -> u64 {
+        self.get_count("job-k-octets") as u64 * 1024
+    }
+
+    fn get_priority(&self) -> u32 {
+        self.get_count("job-priority")
+    }
+
+    fn get_state_reasons(&self) -> Vec<String> {
+        self.attributes
+            .strings("job-state-reasons")
+            .into_iter()
+            .filter(|v| v != "none")
+            .collect()
+    }
+
+    fn get_printer_state_message(&self) -> String {
+        self.attributes.string("job-printer-state-message")
+    }
+
+    fn get_impressions_completed(&self) -> u32 {
+        self.get_count("job-impressions-completed")
+    }
+
+    fn get_pages_completed(&self) -> u32 {
+        self.get_count("job-media-sheets-completed")
+    }
+
+    fn get_document_count(&self) -> u32 {
+        self.get_count("number-of-documents")
+    }
+
+    fn get_hold_until(&self) -> Option<String> {
+        Some(self.attributes.string("job-hold-until")).filter(|v| !v.is_empty())
+    }
+}
+
+/**
+ * Create a Get-Job-Attributes request, addressed by job-uri when the printer is unknown
+ */
+pub fn get_job_request(printer_name: Option<&str>, job_id: u64, user_name: &str) -> IppMessage {
+    let operation = IppOperation::GetJobAttributes;
+    let mut request = match printer_name {
+        Some(printer_name) => job_request(operation, printer_name, job_id as i32, user_name),
+        None => job_uri_request(operation, job_id as i32, user_name),
+    };
+    request.add(IppGroupTag::Operation, requested_attributes());
+    request
+}
+
+/**
+ * Create a Get-Jobs request listing the active jobs of a printer, or all of them
+ */
+pub fn get_jobs_request(printer_name: &str, active_only: bool, user_name: &str) -> IppMessage {
+    let mut request = printer_request(IppOperation::GetJobs, printer_name, user_name);
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::new(
+            "which-jobs",
+            IppValue::Keyword(if active_only { "not-completed" } else { "all" }.to_string()),
+        ),
+    );
+    request.add(IppGroupTag::Operation, requested_attributes());
+    request
+}
+
+/**
+ * The requested-attributes of job queries
+ */
+pub fn requested_attributes() -> IppAttribute {
+    IppAttribute::with_values(
+        "requested-attributes",
+        JOB_ATTRIBUTES
+            .iter()
+            .map(|v| IppValue::Keyword(v.to_string()))
+            .collect(),
+    )
+}
+
+/**
+ * Return the job of a Get-Job-Attributes response, None when the server does not know the job
+ */
+pub fn from_response(response: &IppMessage) -> Result<Option<IppJob>, PrintersError> {
+    if response.status() == IppStatusCode::ClientErrorNotFound {
+        return Ok(None);
+    }
+
+    if !response.is_successful() {
+        return Err(PrintersError::from_response(
+            IppOperation::GetJobAttributes,
+            response,
+            PrintersError::job_error,
+        ));
+    }
+
+    Ok(response.group(IppGroupTag::Job).map(|attributes| IppJob {
+        attributes: attributes.clone(),
+    }))
+}
+
+/**
+ * Return the jobs of a Get-Jobs response
+ */
+pub fn jobs_from_response(response: &IppMessage) -> Result<Vec<IppJob>, PrintersError> {
+    if !response.is_successful() {
+        return Err(PrintersError::from_response(
+            IppOperation::GetJobs,
+            response,
+            PrintersError::job_error,
+        ));
+    }
+
+    Ok(response
+        .groups(IppGroupTag::Job)
+        .map(|attributes| IppJob {
+            attributes: attributes.clone(),
+        })
+        .collect())
+}
@@ -0,0 +1,246 @@
+use std::io::{Read, Write};
+
+use crate::{
+    common::base::errors::PrintersError,
+    ipp::{
+        attribute::{IppAttribute, IppAttributeGroup, IppGroupTag},
+        encoding,
+        operation::IppOperation,
+        status::IppStatusCode,
+        value::IppValue,
+    },
+};
+
+const TAG_END_OF_ATTRIBUTES: u8 = 0x03;
+
+/**
+ * IPP protocol version of a message
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IppVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl IppVersion {
+    pub const V1_1: IppVersion = IppVersion { major: 1, minor: 1 };
+    pub const V2_0: IppVersion = IppVersion { major: 2, minor: 0 };
+}
+
+/**
+ * An IPP request or response
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct IppMessage {
+    /**
+     * Protocol version
+     */
+    pub version: IppVersion,
+    /**
+     * Operation id on requests, status code on responses
+     */
+    pub code: u16,
+    /**
+     * Request id, echoed back on responses
+     */
+    pub request_id: u32,
+    /**
+     * Attribute groups in the order they are encoded
+     */
+    pub groups: Vec<IppAttributeGroup>,
+    /**
+     * Data following the attributes, ex the document of a Print-Job request
+     */
+    pub data: Vec<u8>,
+}
+
+impl IppMessage {
+    /**
+     * Create a request with the required operation attributes (charset and natural language)
+     */
+    pub fn request(operation: IppOperation, request_id: u32) -> Self {
+        let mut message = IppMessage {
+            version: IppVersion::V2_0,
+            code: operation.into(),
+            request_id,
+            groups: Vec::new(),
+            data: Vec::new(),
+        };
+
+        message.add(
+            IppGroupTag::Operation,
+            IppAttribute::new("attributes-charset", IppValue::Charset("utf-8".to_string())),
+        );
+        message.add(
+            IppGroupTag::Operation,
+            IppAttribute::new(
+                "attributes-natural-language",
+                IppValue::NaturalLanguage("en".to_string()),
+            ),
+        );
+
+        message
+    }
+
+    /**
+     * Create a response with the required operation attributes (charset and natural language)
+     */
+    pub fn response(status: IppStatusCode, request_id: u32) -> Self {
+        let mut message = Self::request(IppOperation::Other(0), request_id);
+        message.code = status.into();
+        message
+    }
+
+    /**
+     * Return the operation of a request
+     */
+    pub fn operation(&self) -> IppOperation {
+        IppOperation::from(self.code)
+    }
+
+    /**
+     * Return the status of a response
+     */
+    pub fn status(&self) -> IppStatusCode {
+        IppStatusCode::from(self.code)
+    }
+
+    /**
+     * Check if the status code of a response is in the successful range
+     */
+    pub fn is_successful(&self) -> bool {
+        self.code < 0x0100
+    }
+
+    /**
+     * Add an attribute to the last group with the given tag, creating the group if needed
+     */
+    pub fn add(&mut self, tag: IppGroupTag, attribute: IppAttribute) {
+        match self.groups.iter_mut().rev().find(|g| g.tag == tag) {
+            Some(group) => group.attributes.push(attribute),
+            None => {
+                let mut group = IppAttributeGroup::new(tag);
+                group.attributes.push(attribute);
+                self.groups.push(group);
+            }
+        }
+    }
+
+    /**
+     * Return the first group with the given tag
+     */
+    pub fn group(&self, tag: IppGroupTag) -> Option<&IppAttributeGroup> {
+        self.groups.iter().find(|g| g.tag == tag)
+    }
+
+    /**
+     * Return all groups with the given tag, ex one job group per job on Get-Jobs responses
+     */
+    pub fn groups(&self, tag: IppGroupTag) -> impl Iterator<Item = &IppAttributeGroup> {
+        self.groups.iter().filter(move |g| g.tag == tag)
+    }
+
+    /**
+     * Encode the message and its data
+     */
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        // Writing into a Vec cannot fail
+        let _ = self.write_to(&mut buffer);
+        buffer.extend_from_slice(&self.data);
+        buffer
+    }
+
+    /**
+     * Write the message header and attributes, without data
+     */
+    pub fn write_to(&self, writer: &mut dyn Write) -> Result<(), PrintersError> {
+        encoding::write_bytes(writer, &[self.version.major, self.version.minor])?;
+        encoding::write_u16(writer, self.code)?;
+        encoding::write_bytes(writer, &self.request_id.to_be_bytes())?;
+
+        for group in &self.groups {
+            encoding::write_bytes(writer, &[group.tag.to_byte()])?;
+            for attribute in &group.attributes {
+                write_attribute(writer, attribute)?;
+            }
+        }
+
+        encoding::write_bytes(writer, &[TAG_END_OF_ATTRIBUTES])
+    }
+
+    /**
+     * Decode a message, any bytes after the attributes are kept as data
+     */
+    pub fn decode(bytes: &[u8]) -> Result<Self, PrintersError> {
+        let mut reader = bytes;
+        let mut message = Self::read_from(&mut reader)?;
+        message.data = reader.to_vec();
+        Ok(message)
+    }
+
+    /**
+     * Read the message header and attributes, leaving any data in the reader
+     */
+    pub fn read_from(reader: &mut dyn Read) -> Result<Self, PrintersError> {
+        let version = encoding::read_bytes::<2>(reader)?;
+        let code = encoding::read_u16(reader)?;
+        let request_id = encoding::read_u32(reader)?;
+        let mut groups: Vec<IppAttributeGroup> = Vec::new();
+
+        loop {
+            let tag = encoding::read_u8(reader)?;
+            if tag == TAG_END_OF_ATTRIBUTES {
+                break;
+            }
+
+            if tag < 0x10 {
+                groups.push(IppAttributeGroup::new(IppGroupTag::from_byte(tag)));
+                continue;
+            }
+
+            let group = groups
+                .last_mut()
+                .ok_or_else(|| PrintersError::error("IPP attribute outside of a group"))?;
+
+            let name = encoding::bytes_to_string(encoding::read_sized(reader)?);
+            let value = IppValue::read_from(tag, reader)?;
+
+            if name.is_empty() {
+                group
+                    .attributes
+                    .last_mut()
+                    .ok_or_else(|| PrintersError::error("IPP value without attribute name"))?
+                    .values
+                    .push(value);
+            } else {
+                group.attributes.push(IppAttribute::new(&name, value));
+            }
+        }
+
+        Ok(IppMessage {
+            version: IppVersion {
+                major: version[0],
+                minor: version[1],
+            },
+            code,
+            request_id,
+            groups,
+            data: Vec::new(),
+        })
+    }
+}
+
+fn write_attribute(writer: &mut dyn Write, attribute: &IppAttribute) -> Result<(), PrintersError> {
+    for (index, value) in attribute.values.iter().enumerate() {
+        let name = if index == 0 {
+            attribute.name.as_str()
+        } else {
+            ""
+        };
+        encoding::write_bytes(writer, &[value.tag()])?;
+        encoding::write_sized(writer, name.as_bytes())?;
+        value.write_to(writer)?;
+    }
+    Ok(())
+}
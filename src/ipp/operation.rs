@@ -0,0 +1,75 @@
+macro_rules! ipp_operations {
+    ($($variant:ident = $code:expr => $keyword:expr,)*) => {
+        /**
+         * IPP and CUPS operation identifiers
+         */
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub enum IppOperation {
+            $($variant,)*
+            Other(u16),
+        }
+
+        impl From<u16> for IppOperation {
+            fn from(value: u16) -> Self {
+                match value {
+                    $($code => IppOperation::$variant,)*
+                    v => IppOperation::Other(v),
+                }
+            }
+        }
+
+        impl From<IppOperation> for u16 {
+            fn from(value: IppOperation) -> Self {
+                match value {
+                    $(IppOperation::$variant => $code,)*
+                    IppOperation::Other(v) => v,
+                }
+            }
+        }
+
+        impl IppOperation {
+            /**
+             * Return the registered name of the operation, ex Print-Job
+             */
+            pub fn keyword(&self) -> String {
+                match self {
+                    $(IppOperation::$variant => $keyword.to_string(),)*
+                    IppOperation::Other(v) => format!("{v:#06x}"),
+                }
+            }
+        }
+    };
+}
+
+ipp_operations! {
+    PrintJob = 0x0002 => "Print-Job",
+    PrintUri = 0x0003 => "Print-URI",
+    ValidateJob = 0x0004 => "Validate-Job",
+    CreateJob = 0x0005 => "Create-Job",
+    SendDocument = 0x0006 => "Send-Document",
+    SendUri = 0x0007 => "Send-URI",
+    CancelJob = 0x0008 => "Cancel-Job",
+    GetJobAttributes = 0x0009 => "Get-Job-Attributes",
+    GetJobs = 0x000A => "Get-Jobs",
+    GetPrinterAttributes = 0x000B => "Get-Printer-Attributes",
+    HoldJob = 0x000C => "Hold-Job",
+    ReleaseJob = 0x000D => "Release-Job",
+    RestartJob = 0x000E => "Restart-Job",
+    PausePrinter = 0x0010 => "Pause-Printer",
+    ResumePrinter = 0x0011 => "Resume-Printer",
+    PurgeJobs = 0x0012 => "Purge-Jobs",
+    SetPrinterAttributes = 0x0013 => "Set-Printer-Attributes",
+    SetJobAttributes = 0x0014 => "Set-Job-Attributes",
+    CancelJobs = 0x0038 => "Cancel-Jobs",
+    CancelMyJobs = 0x0039 => "Cancel-My-Jobs",
+    CloseJob = 0x003B => "Close-Job",
+    CupsGetDefault = 0x4001 => "CUPS-Get-Default",
+    CupsGetPrinters = 0x4002 => "CUPS-Get-Printers",
+    CupsAddModifyPrinter = 0x4003 => "CUPS-Add-Modify-Printer",
+    CupsDeletePrinter = 0x4004 => "CUPS-Delete-Printer",
+    CupsAcceptJobs = 0x4008 => "CUPS-Accept-Jobs",
+    CupsRejectJobs = 0x4009 => "CUPS-Reject-Jobs",
+    CupsSetDefault = 0x400A => "CUPS-Set-Default",
+    CupsMoveJob = 0x400D => "CUPS-Move-Job",
+    CupsGetDocument = 0x4027 => "CUPS-Get-Document",
+}
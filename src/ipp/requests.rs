@@ -0,0 +1,319 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::{
+    admin::PrinterConfig,
+    common::base::job::{CancelJobsFilter, JobChanges},
+    ipp::{IppAttribute, IppGroupTag, IppMessage, IppOperation, IppValue},
+    server,
+};
+
+static REQUEST_ID: AtomicU32 = AtomicU32::new(1);
+
+/**
+ * Return a new request id
+ */
+pub fn next_request_id() -> u32 {
+    REQUEST_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/**
+ * The host[:port] of the current server used in uris, localhost for the default server
+ * and for domain sockets
+ */
+fn authority() -> String {
+    match server::current() {
+        Some(server) if server.host.starts_with('/') => "localhost".to_string(),
+        Some(server) if server.host.contains(':') => format!("[{}]:{}", server.host, server.port),
+        Some(server) => format!("{}:{}", server.host, server.port),
+        None => "localhost".to_string(),
+    }
+}
+
+/**
+ * The printer-uri of a queue on the current server
+ */
+pub fn printer_uri(printer_name: &str) -> String {
+    format!(
+        "ipp://{}/printers/{}",
+        authority(),
+        percent_encode(printer_name)
+    )
+}
+
+/**
+ * Percent-encode a segment of an uri path, every byte but the unreserved characters of RFC 3986
+ */
+pub fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/**
+ * Decode a percent-encoded segment of an uri path, invalid escapes are kept as is
+ */
+pub fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes[i] {
+            b'%' => value
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/**
+ * The job-uri of a job on the current server
+ */
+pub fn job_uri(job_id: i32) -> String {
+    format!("ipp://{}/jobs/{job_id}", authority())
+}
+
+/**
+ * Create a request addressed to a printer
+ */
+pub fn printer_request(operation: IppOperation, printer_name: &str, user_name: &str) -> IppMessage {
+    let mut request = IppMessage::request(operation, next_request_id());
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::new("printer-uri", IppValue::Uri(printer_uri(printer_name))),
+    );
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::new(
+            "requesting-user-name",
+            IppValue::Name(user_name.to_string()),
+        ),
+    );
+    request
+}
+
+/**
+ * Create a request addressed to a job of a printer
+ */
+pub fn job_request(
+    operation: IppOperation,
+    printer_name: &str,
+    job_id: i32,
+    user_name: &str,
+) -> IppMessage {
+    let mut request = printer_request(operation, printer_name, user_name);
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::new("job-id", IppValue::Integer(job_id)),
+    );
+    request
+}
+
+/**
+ * Create a Get-Printer-Attributes request of a printer
+ */
+pub fn printer_attributes_request(
+    printer_name: &str,
+    user_name: &str,
+    attributes: &[&str],
+) -> IppMessage {
+    let mut request = printer_request(IppOperation::GetPrinterAttributes, printer_name, user_name);
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::with_values(
+            "requested-attributes",
+            attributes
+                .iter()
+                .map(|v| IppValue::Keyword(v.to_string()))
+                .collect(),
+        ),
+    );
+    request
+}
+
+/**
+ * Create a request addressed to a job by its job-uri
+ */
+pub fn job_uri_request(operation: IppOperation, job_id: i32, user_name: &str) -> IppMessage {
+    let mut request = IppMessage::request(operation, next_request_id());
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::new("job-uri", IppValue::Uri(job_uri(job_id))),
+    );
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::new(
+            "requesting-user-name",
+            IppValue::Name(user_name.to_string()),
+        ),
+    );
+    request
+}
+
+/**
+ * Create a CUPS-Get-Document request of a document of a job, numbered from 1
+ */
+pub fn get_document_request(
+    printer_name: &str,
+    job_id: i32,
+    document_number: u32,
+    user_name: &str,
+) -> IppMessage {
+    let mut request = job_request(
+        IppOperation::CupsGetDocument,
+        printer_name,
+        job_id,
+        user_name,
+    );
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::new(
+            "document-number",
+            IppValue::Integer(document_number.max(1) as i32),
+        ),
+    );
+    request
+}
+
+/**
+ * Create a Set-Job-Attributes request changing the job template attributes of a job
+ */
+pub fn set_job_attributes_request(
+    printer_name: &str,
+    job_id: i32,
+    user_name: &str,
+    changes: &JobChanges,
+) -> IppMessage {
+    let mut request = job_request(
+        IppOperation::SetJobAttributes,
+        printer_name,
+        job_id,
+        user_name,
+    );
+    for attribute in changes.job_attributes() {
+        request.add(IppGroupTag::Job, attribute);
+    }
+    request
+}
+
+/**
+ * Create a Cancel-Jobs, Cancel-My-Jobs or Purge-Jobs request of a queue, as the cancel command does.
+ * Only the listed jobs are affected, jobs submitted meanwhile are kept. The jobs are already
+ * filtered by owner, the request is always sent as the current user
+ */
+pub fn cancel_jobs_request(
+    printer_name: &str,
+    filter: &CancelJobsFilter,
+    purge: bool,
+    job_ids: &[u64],
+    user_name: &str,
+) -> IppMessage {
+    let operation = match filter {
+        _ if purge => IppOperation::PurgeJobs,
+        CancelJobsFilter::Mine => IppOperation::CancelMyJobs,
+        _ => IppOperation::CancelJobs,
+    };
+
+    let mut request = printer_request(operation, printer_name, user_name);
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::with_values(
+            "job-ids",
+            job_ids
+                .iter()
+                .map(|id| IppValue::Integer(*id as i32))
+                .collect(),
+        ),
+    );
+    request
+}
+
+/**
+ * Create a CUPS-Move-Job request moving a job, or every job of the queue when job_id is None,
+ * to another queue
+ */
+pub fn move_job_request(
+    printer_name: &str,
+    job_id: Option<i32>,
+    target_name: &str,
+    user_name: &str,
+) -> IppMessage {
+    let mut request = match job_id {
+        Some(job_id) => job_request(IppOperation::CupsMoveJob, printer_name, job_id, user_name),
+        None => printer_request(IppOperation::CupsMoveJob, printer_name, user_name),
+    };
+    request.add(
+        IppGroupTag::Job,
+        IppAttribute::new("job-printer-uri", IppValue::Uri(printer_uri(target_name))),
+    );
+    request
+}
+
+/**
+ * Create a queue administration request (ex: Pause-Printer, CUPS-Reject-Jobs),
+ * the message is shown as the printer-state-message
+ */
+pub fn printer_admin_request(
+    operation: IppOperation,
+    printer_name: &str,
+    user_name: &str,
+    message: Option<&str>,
+) -> IppMessage {
+    let mut request = printer_request(operation, printer_name, user_name);
+    if let Some(message) = message {
+        request.add(
+            IppGroupTag::Operation,
+            IppAttribute::new("printer-state-message", IppValue::Text(message.to_string())),
+        );
+    }
+    request
+}
+
+/**
+ * Create a CUPS-Add-Modify-Printer request creating or changing a queue
+ */
+pub fn add_modify_printer_request(
+    printer_name: &str,
+    user_name: &str,
+    config: &PrinterConfig,
+) -> IppMessage {
+    let mut request = printer_request(IppOperation::CupsAddModifyPrinter, printer_name, user_name);
+    for attribute in config.printer_attributes() {
+        request.add(IppGroupTag::Printer, attribute);
+    }
+    request
+}
+
+/**
+ * Create a CUPS-Add-Modify-Printer request changing the printer-state-message of a queue
+ */
+pub fn printer_state_message_request(
+    printer_name: &str,
+    user_name: &str,
+    message: &str,
+) -> IppMessage {
+    let mut request = printer_request(IppOperation::CupsAddModifyPrinter, printer_name, user_name);
+    request.add(
+        IppGroupTag::Printer,
+        IppAttribute::new("printer-state-message", IppValue::Text(message.to_string())),
+    );
+    request
+}
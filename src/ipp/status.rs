@@ -0,0 +1,84 @@
+macro_rules! ipp_status_codes {
+    ($($variant:ident = $code:expr => $keyword:expr,)*) => {
+        /**
+         * IPP status codes of a response
+         */
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub enum IppStatusCode {
+            $($variant,)*
+            Other(u16),
+        }
+
+        impl From<u16> for IppStatusCode {
+            fn from(value: u16) -> Self {
+                match value {
+                    $($code => IppStatusCode::$variant,)*
+                    v => IppStatusCode::Other(v),
+                }
+            }
+        }
+
+        impl From<IppStatusCode> for u16 {
+            fn from(value: IppStatusCode) -> Self {
+                match value {
+                    $(IppStatusCode::$variant => $code,)*
+                    IppStatusCode::Other(v) => v,
+                }
+            }
+        }
+
+        impl IppStatusCode {
+            /**
+             * Return the registered keyword of the status, ex client-error-not-found
+             */
+            pub fn keyword(&self) -> String {
+                match self {
+                    $(IppStatusCode::$variant => $keyword.to_string(),)*
+                    IppStatusCode::Other(v) => format!("{v:#06x}"),
+                }
+            }
+        }
+    };
+}
+
+ipp_status_codes! {
+    SuccessfulOk = 0x0000 => "successful-ok",
+    SuccessfulOkIgnoredOrSubstitutedAttributes = 0x0001 => "successful-ok-ignored-or-substituted-attributes",
+    SuccessfulOkConflictingAttributes = 0x0002 => "successful-ok-conflicting-attributes",
+    ClientErrorBadRequest = 0x0400 => "client-error-bad-request",
+    ClientErrorForbidden = 0x0401 => "client-error-forbidden",
+    ClientErrorNotAuthenticated = 0x0402 => "client-error-not-authenticated",
+    ClientErrorNotAuthorized = 0x0403 => "client-error-not-authorized",
+    ClientErrorNotPossible = 0x0404 => "client-error-not-possible",
+    ClientErrorTimeout = 0x0405 => "client-error-timeout",
+    ClientErrorNotFound = 0x0406 => "client-error-not-found",
+    ClientErrorGone = 0x0407 => "client-error-gone",
+    ClientErrorRequestEntityTooLarge = 0x0408 => "client-error-request-entity-too-large",
+    ClientErrorRequestValueTooLong = 0x0409 => "client-error-request-value-too-long",
+    ClientErrorDocumentFormatNotSupported = 0x040A => "client-error-document-format-not-supported",
+    ClientErrorAttributesOrValuesNotSupported = 0x040B => "client-error-attributes-or-values-not-supported",
+    ClientErrorUriSchemeNotSupported = 0x040C => "client-error-uri-scheme-not-supported",
+    ClientErrorCharsetNotSupported = 0x040D => "client-error-charset-not-supported",
+    ClientErrorConflictingAttributes = 0x040E => "client-error-conflicting-attributes",
+    ClientErrorCompressionNotSupported = 0x040F => "client-error-compression-not-supported",
+    ClientErrorCompressionError = 0x0410 => "client-error-compression-error",
+    ClientErrorDocumentFormatError = 0x0411 => "client-error-document-format-error",
+    ClientErrorDocumentAccessError = 0x0412 => "client-error-document-access-error",
+    ClientErrorAttributesNotSettable = 0x0413 => "client-error-attributes-not-settable",
+    ClientErrorIgnoredAllSubscriptions = 0x0414 => "client-error-ignored-all-subscriptions",
+    ClientErrorTooManySubscriptions = 0x0415 => "client-error-too-many-subscriptions",
+    ClientErrorDocumentPasswordError = 0x0418 => "client-error-document-password-error",
+    ClientErrorDocumentPermissionError = 0x0419 => "client-error-document-permission-error",
+    ClientErrorDocumentSecurityError = 0x041A => "client-error-document-security-error",
+    ClientErrorDocumentUnprintableError = 0x041B => "client-error-document-unprintable-error",
+    ServerErrorInternalError = 0x0500 => "server-error-internal-error",
+    ServerErrorOperationNotSupported = 0x0501 => "server-error-operation-not-supported",
+    ServerErrorServiceUnavailable = 0x0502 => "server-error-service-unavailable",
+    ServerErrorVersionNotSupported = 0x0503 => "server-error-version-not-supported",
+    ServerErrorDeviceError = 0x0504 => "server-error-device-error",
+    ServerErrorTemporaryError = 0x0505 => "server-error-temporary-error",
+    ServerErrorNotAcceptingJobs = 0x0506 => "server-error-not-accepting-jobs",
+    ServerErrorBusy = 0x0507 => "server-error-busy",
+    ServerErrorJobCanceled = 0x0508 => "server-error-job-canceled",
+    ServerErrorMultipleDocumentJobsNotSupported = 0x0509 => "server-error-multiple-document-jobs-not-supported",
+}
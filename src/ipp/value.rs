@@ -0,0 +1,401 @@
+use std::{
+    io::{Read, Write},
+    time::SystemTime,
+};
+
+use crate::{
+    common::base::errors::PrintersError,
+    ipp::{attribute::IppAttribute, date::IppDateTime, encoding},
+};
+
+pub const TAG_UNSUPPORTED: u8 = 0x10;
+pub const TAG_UNKNOWN: u8 = 0x12;
+pub const TAG_NO_VALUE: u8 = 0x13;
+pub const TAG_NOT_SETTABLE: u8 = 0x15;
+pub const TAG_DELETE_ATTRIBUTE: u8 = 0x16;
+pub const TAG_ADMIN_DEFINE: u8 = 0x17;
+pub const TAG_INTEGER: u8 = 0x21;
+pub const TAG_BOOLEAN: u8 = 0x22;
+pub const TAG_ENUM: u8 = 0x23;
+pub const TAG_OCTET_STRING: u8 = 0x30;
+pub const TAG_DATE_TIME: u8 = 0x31;
+pub const TAG_RESOLUTION: u8 = 0x32;
+pub const TAG_RANGE_OF_INTEGER: u8 = 0x33;
+pub const TAG_BEGIN_COLLECTION: u8 = 0x34;
+pub const TAG_TEXT_WITH_LANGUAGE: u8 = 0x35;
+pub const TAG_NAME_WITH_LANGUAGE: u8 = 0x36;
+pub const TAG_END_COLLECTION: u8 = 0x37;
+pub const TAG_TEXT: u8 = 0x41;
+pub const TAG_NAME: u8 = 0x42;
+pub const TAG_KEYWORD: u8 = 0x44;
+pub const TAG_URI: u8 = 0x45;
+pub const TAG_URI_SCHEME: u8 = 0x46;
+pub const TAG_CHARSET: u8 = 0x47;
+pub const TAG_NATURAL_LANGUAGE: u8 = 0x48;
+pub const TAG_MIME_MEDIA_TYPE: u8 = 0x49;
+pub const TAG_MEMBER_ATTR_NAME: u8 = 0x4A;
+
+/**
+ * Units of a resolution value
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IppResolutionUnits {
+    DotsPerInch,
+    DotsPerCentimeter,
+    Other(i8),
+}
+
+impl IppResolutionUnits {
+    fn from_byte(value: i8) -> Self {
+        match value {
+            3 => IppResolutionUnits::DotsPerInch,
+            4 => IppResolutionUnits::DotsPerCentimeter,
+            v => IppResolutionUnits::Other(v),
+        }
+    }
+
+    fn to_byte(self) -> i8 {
+        match self {
+            IppResolutionUnits::DotsPerInch => 3,
+            IppResolutionUnits::DotsPerCentimeter => 4,
+            IppResolutionUnits::Other(v) => v,
+        }
+    }
+}
+
+/**
+ * A single value of an IPP attribute
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum IppValue {
+    Integer(i32),
+    Boolean(bool),
+    Enum(i32),
+    OctetString(Vec<u8>),
+    DateTime(IppDateTime),
+    Resolution {
+        cross_feed: i32,
+        feed: i32,
+        units: IppResolutionUnits,
+    },
+    RangeOfInteger {
+        lower: i32,
+        upper: i32,
+    },
+    TextWithLanguage {
+        language: String,
+        text: String,
+    },
+    NameWithLanguage {
+        language: String,
+        name: String,
+    },
+    Text(String),
+    Name(String),
+    Keyword(String),
+    Uri(String),
+    UriScheme(String),
+    Charset(String),
+    NaturalLanguage(String),
+    MimeMediaType(String),
+    /**
+     * A collection, each member is an attribute with its own values
+     */
+    Collection(Vec<IppAttribute>),
+    Unsupported,
+    Unknown,
+    NoValue,
+    NotSettable,
+    DeleteAttribute,
+    AdminDefine,
+    /**
+     * Any value whose tag has no typed representation, kept as raw bytes
+     */
+    Other {
+        tag: u8,
+        data: Vec<u8>,
+    },
+}
+
+impl IppValue {
+    /**
+     * Return the value tag used to encode this value
+     */
+    pub fn tag(&self) -> u8 {
+        match self {
+            IppValue::Integer(_) => TAG_INTEGER,
+            IppValue::Boolean(_) => TAG_BOOLEAN,
+            IppValue::Enum(_) => TAG_ENUM,
+            IppValue::OctetString(_) => TAG_OCTET_STRING,
+            IppValue::DateTime(_) => TAG_DATE_TIME,
+            IppValue::Resolution { .. } => TAG_RESOLUTION,
+            IppValue::RangeOfInteger { .. } => TAG_RANGE_OF_INTEGER,
+            IppValue::TextWithLanguage { .. } => TAG_TEXT_WITH_LANGUAGE,
+            IppValue::NameWithLanguage { .. } => TAG_NAME_WITH_LANGUAGE,
+            IppValue::Text(_) => TAG_TEXT,
+            IppValue::Name(_) => TAG_NAME,
+            IppValue::Keyword(_) => TAG_KEYWORD,
+            IppValue::Uri(_) => TAG_URI,
+            IppValue::UriScheme(_) => TAG_URI_SCHEME,
+            IppValue::Charset(_) => TAG_CHARSET,
+            IppValue::NaturalLanguage(_) => TAG_NATURAL_LANGUAGE,
+            IppValue::MimeMediaType(_) => TAG_MIME_MEDIA_TYPE,
+            IppValue::Collection(_) => TAG_BEGIN_COLLECTION,
+            IppValue::Unsupported => TAG_UNSUPPORTED,
+            IppValue::Unknown => TAG_UNKNOWN,
+            IppValue::NoValue => TAG_NO_VALUE,
+            IppValue::NotSettable => TAG_NOT_SETTABLE,
+            IppValue::DeleteAttribute => TAG_DELETE_ATTRIBUTE,
+            IppValue::AdminDefine => TAG_ADMIN_DEFINE,
+            IppValue::Other { tag, .. } => *tag,
+        }
+    }
+
+    /**
+     * Return the value as an integer, for integer and enum values
+     */
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            IppValue::Integer(v) | IppValue::Enum(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /**
+     * Return the value as a boolean
+     */
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            IppValue::Boolean(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /**
+     * Return the value as a string slice, for all string based values
+     */
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            IppValue::TextWithLanguage { text: v, .. }
+            | IppValue::NameWithLanguage { name: v, .. }
+            | IppValue::Text(v)
+            | IppValue::Name(v)
+            | IppValue::Keyword(v)
+            | IppValue::Uri(v)
+            | IppValue::UriScheme(v)
+            | IppValue::Charset(v)
+            | IppValue::NaturalLanguage(v)
+            | IppValue::MimeMediaType(v) => Some(v.as_str()),
+            _ => None,
+        }
+    }
+
+    /**
+     * Return the members of a collection value
+     */
+    pub fn as_collection(&self) -> Option<&[IppAttribute]> {
+        match self {
+            IppValue::Collection(v) => Some(v.as_slice()),
+            _ => None,
+        }
+    }
+
+    /**
+     * Return the value as a system time, for dateTime values
+     */
+    pub fn as_system_time(&self) -> Option<SystemTime> {
+        match self {
+            IppValue::DateTime(v) => Some(v.to_system_time()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn write_to(&self, writer: &mut dyn Write) -> Result<(), PrintersError> {
+        match self {
+            IppValue::Integer(v) | IppValue::Enum(v) => {
+                encoding::write_u16(writer, 4)?;
+                encoding::write_i32(writer, *v)
+            }
+            IppValue::Boolean(v) => {
+                encoding::write_u16(writer, 1)?;
+                encoding::write_bytes(writer, &[*v as u8])
+            }
+            IppValue::OctetString(data) | IppValue::Other { data, .. } => {
+                encoding::write_sized(writer, data)
+            }
+            IppValue::DateTime(v) => encoding::write_sized(writer, &v.to_bytes()),
+            IppValue::Resolution {
+                cross_feed,
+                feed,
+                units,
+            } => {
+                encoding::write_u16(writer, 9)?;
+                encoding::write_i32(writer, *cross_feed)?;
+                encoding::write_i32(writer, *feed)?;
+                encoding::write_bytes(writer, &[units.to_byte() as u8])
+            }
+            IppValue::RangeOfInteger { lower, upper } => {
+                encoding::write_u16(writer, 8)?;
+                encoding::write_i32(writer, *lower)?;
+                encoding::write_i32(writer, *upper)
+            }
+            IppValue::TextWithLanguage {
+                language,
+                text: value,
+            }
+            | IppValue::NameWithLanguage {
+                language,
+                name: value,
+            } => {
+                let mut data = Vec::new();
+                encoding::write_sized(&mut data, language.as_bytes())?;
+                encoding::write_sized(&mut data, value.as_bytes())?;
+                encoding::write_sized(writer, &data)
+            }
+            IppValue::Collection(members) => {
+                encoding::write_u16(writer, 0)?;
+                for member in members {
+                    encoding::write_bytes(writer, &[TAG_MEMBER_ATTR_NAME])?;
+                    encoding::write_u16(writer, 0)?;
+                    encoding::write_sized(writer, member.name.as_bytes())?;
+                    for value in &member.values {
+                        encoding::write_bytes(writer, &[value.tag()])?;
+                        encoding::write_u16(writer, 0)?;
+                        value.write_to(writer)?;
+                    }
+                }
+                encoding::write_bytes(writer, &[TAG_END_COLLECTION])?;
+                encoding::write_u16(writer, 0)?;
+                encoding::write_u16(writer, 0)
+            }
+            IppValue::Unsupported
+            | IppValue::Unknown
+            | IppValue::NoValue
+            | IppValue::NotSettable
+            | IppValue::DeleteAttribute
+            | IppValue::AdminDefine => encoding::write_u16(writer, 0),
+            value => encoding::write_sized(writer, value.as_str().unwrap_or_default().as_bytes()),
+        }
+    }
+
+    pub(crate) fn read_from(tag: u8, reader: &mut dyn Read) -> Result<Self, PrintersError> {
+        Self::read_nested(tag, reader, 0)
+    }
+
+    /**
+     * Read a value inside depth collections
+     */
+    fn read_nested(tag: u8, reader: &mut dyn Read, depth: usize) -> Result<Self, PrintersError> {
+        let data = encoding::read_sized(reader)?;
+        if tag == TAG_BEGIN_COLLECTION {
+            return Ok(IppValue::Collection(read_collection(reader, depth + 1)?));
+        }
+        Self::from_bytes(tag, data)
+    }
+
+    fn from_bytes(tag: u8, data: Vec<u8>) -> Result<Self, PrintersError> {
+        let value = match tag {
+            TAG_INTEGER => IppValue::Integer(encoding::bytes_to_i32(&data, 0)?),
+            TAG_ENUM => IppValue::Enum(encoding::bytes_to_i32(&data, 0)?),
+            TAG_BOOLEAN => IppValue::Boolean(data.first().is_some_and(|v| *v != 0)),
+            TAG_OCTET_STRING => IppValue::OctetString(data),
+            TAG_DATE_TIME => IppValue::DateTime(
+                IppDateTime::from_bytes(&data)
+                    .ok_or_else(|| PrintersError::error("Invalid IPP dateTime value"))?,
+            ),
+            TAG_RESOLUTION if data.len() == 9 => IppValue::Resolution {
+                cross_feed: encoding::bytes_to_i32(&data, 0)?,
+                feed: encoding::bytes_to_i32(&data, 4)?,
+                units: IppResolutionUnits::from_byte(data[8] as i8),
+            },
+            TAG_RANGE_OF_INTEGER => IppValue::RangeOfInteger {
+                lower: encoding::bytes_to_i32(&data, 0)?,
+                upper: encoding::bytes_to_i32(&data, 4)?,
+            },
+            TAG_TEXT_WITH_LANGUAGE | TAG_NAME_WITH_LANGUAGE => {
+                let mut reader = data.as_slice();
+                let language = encoding::bytes_to_string(encoding::read_sized(&mut reader)?);
+                let value = encoding::bytes_to_string(encoding::read_sized(&mut reader)?);
+                if tag == TAG_TEXT_WITH_LANGUAGE {
+                    IppValue::TextWithLanguage {
+                        language,
+                        text: value,
+                    }
+                } else {
+                    IppValue::NameWithLanguage {
+                        language,
+                        name: value,
+                    }
+                }
+            }
+            TAG_TEXT => IppValue::Text(encoding::bytes_to_string(data)),
+            TAG_NAME => IppValue::Name(encoding::bytes_to_string(data)),
+            TAG_KEYWORD => IppValue::Keyword(encoding::bytes_to_string(data)),
+            TAG_URI => IppValue::Uri(encoding::bytes_to_string(data)),
+            TAG_URI_SCHEME => IppValue::UriScheme(encoding::bytes_to_string(data)),
+            TAG_CHARSET => IppValue::Charset(encoding::bytes_to_string(data)),
+            TAG_NATURAL_LANGUAGE => IppValue::NaturalLanguage(encoding::bytes_to_string(data)),
+            TAG_MIME_MEDIA_TYPE => IppValue::MimeMediaType(encoding::bytes_to_string(data)),
+            TAG_UNSUPPORTED => IppValue::Unsupported,
+            TAG_UNKNOWN => IppValue::Unknown,
+            TAG_NO_VALUE => IppValue::NoValue,
+            TAG_NOT_SETTABLE => IppValue::NotSettable,
+            TAG_DELETE_ATTRIBUTE => IppValue::DeleteAttribute,
+            TAG_ADMIN_DEFINE => IppValue::AdminDefine,
+            tag => IppValue::Other { tag, data },
+        };
+
+        Ok(value)
+    }
+}
+
+/**
+ * Deepest nesting of collections decoded, deeper messages are rejected instead of exhausting the stack
+ */
+const MAX_COLLECTION_DEPTH: usize = 32;
+
+/**
+ * Read the members of a collection up to its endCollection, depth counts the collection itself
+ */
+fn read_collection(
+    reader: &mut dyn Read,
+    depth: usize,
+) -> Result<Vec<IppAttribute>, PrintersError> {
+    if depth > MAX_COLLECTION_DEPTH {
+        return Err(PrintersError::error(format!(
+            "IPP collections nested deeper than {MAX_COLLECTION_DEPTH}"
+        )));
+    }
+
+    let mut members: Vec<IppAttribute> = Vec::new();
+
+    loop {
+        let tag = encoding::read_u8(reader)?;
+        // Member values have no names, the name comes from memberAttrName
+        encoding::read_sized(reader)?;
+
+        match tag {
+            TAG_END_COLLECTION => {
+                encoding::read_sized(reader)?;
+                return Ok(members);
+            }
+            TAG_MEMBER_ATTR_NAME => {
+                let name = encoding::bytes_to_string(encoding::read_sized(reader)?);
+                members.push(IppAttribute::with_values(&name, Vec::new()));
+            }
+            tag if tag < 0x10 => {
+                return Err(PrintersError::error("Unterminated IPP collection"));
+            }
+            tag => {
+                let value = IppValue::read_nested(tag, reader, depth)?;
+                members
+                    .last_mut()
+                    .ok_or_else(|| {
+                        PrintersError::error("IPP collection value without member name")
+                    })?
+                    .values
+                    .push(value);
+            }
+        }
+    }
+}
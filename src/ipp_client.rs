@@ -0,0 +1,297 @@
+use std::io::Read;
+
+use crate::{
+    admin::PrinterConfig,
+    common::{
+        base::{
+            capabilities::{CAPABILITIES_ATTRIBUTES, PrinterCapabilities},
+            errors::PrintersError,
+            job::{
+                CancelJobsFilter, JobChanges, JobDocument, PrinterJob, PrinterJobOptions,
+                PrinterJobState,
+            },
+            printer::{
+                Printer, PrinterScope, PrinterState, PrinterStateReason, PrinterStateReasonKind,
+            },
+            supplies::{SUPPLIES_ATTRIBUTES, Supply},
+        },
+        converters::Converter,
+        traits::platform::{PlatformActions, PlatformPrinterGetters},
+        utils::file,
+    },
+    ipp::requests::printer_attributes_request,
+    lpoptions,
+};
+
+mod http;
+mod jobs;
+mod printers;
+mod utils;
+
+impl PlatformActions for crate::Platform {
+    fn get_printers() -> Vec<Printer> {
+        printers::get_printers()
+            .unwrap_or_default()
+            .iter()
+            .map(|p| Printer::from_platform_printer_getters(p))
+            .collect()
+    }
+
+    fn print(
+        printer_system_name: &str,
+        buffer: &[u8],
+        options: PrinterJobOptions,
+    ) -> Result<u64, PrintersError> {
+        let buffer = options.converter.convert(buffer)?;
+        jobs::print_buffer(printer_system_name, buffer.as_slice(), &options)
+    }
+
+    fn print_file(
+        printer_system_name: &str,
+        file_path: &str,
+        options: PrinterJobOptions,
+    ) -> Result<u64, PrintersError> {
+        let buffer = file::get_file_as_bytes(file_path)?;
+        Self::print(
+            printer_system_name,
+            buffer.as_slice(),
+            PrinterJobOptions {
+                name: options.name.or(Some(file_path)),
+                ..options
+            },
+        )
+    }
+
+    fn print_stream(
+        printer_system_name: &str,
+        reader: &mut dyn Read,
+        options: PrinterJobOptions,
+    ) -> Result<u64, PrintersError> {
+        // Converters work on the whole document
+        if options.converter != Converter::None {
+            let mut buffer = Vec::new();
+            reader
+                .read_to_end(&mut buffer)
+                .map_err(PrintersError::from)?;
+            return Self::print(printer_system_name, buffer.as_slice(), options);
+        }
+
+        jobs::print_stream(printer_system_name, reader, &options)
+    }
+
+    fn create_job(
+        printer_system_name: &str,
+        options: &PrinterJobOptions,
+    ) -> Result<u64, PrintersError> {
+        jobs::create_job(printer_system_name, options)
+    }
+
+    fn send_document(
+        printer_system_name: &str,
+        job_id: u64,
+        document_name: &str,
+        reader: &mut dyn Read,
+        document_format: Option<&str>,
+        last_document: bool,
+    ) -> Result<(), PrintersError> {
+        jobs::send_document(
+            printer_system_name,
+            job_id,
+            document_name,
+            reader,
+            document_format,
+            last_document,
+        )
+    }
+
+    fn get_printer_jobs(
+        printer_name: &str,
+        active_only: bool,
+    ) -> Result<Vec<PrinterJob>, PrintersError> {
+        Ok(jobs::get_printer_jobs(printer_name, active_only)?
+            .iter()
+            .map(|j| PrinterJob::from_platform_printer_job_getters(j))
+            .collect())
+    }
+
+    fn get_printer_job(
+        printer_name: Option<&str>,
+        job_id: u64,
+    ) -> Result<Option<PrinterJob>, PrintersError> {
+        jobs::get_job(printer_name, job_id)
+            .map(|j| j.map(|j| PrinterJob::from_platform_printer_job_getters(&j)))
+    }
+
+    fn get_default_printer() -> Option<Printer> {
+        printers::get_printers()
+            .ok()?
+            .iter()
+            .find(|p| p.get_is_default())
+            .map(|p| Printer::from_platform_printer_getters(p))
+    }
+
+    fn get_printer_by_name(printer_name: &str) -> Option<Printer> {
+        printers::get_printers()
+            .ok()?
+            .iter()
+            .find(|p| match p.get_instance() {
+                Some(instance) => format!("{}/{}", p.get_system_name(), instance) == printer_name,
+                None => p.get_name() == printer_name || p.get_system_name() == printer_name,
+            })
+            .map(|p| Printer::from_platform_printer_getters(p))
+    }
+
+    fn get_printer_capabilities(printer_name: &str) -> Result<PrinterCapabilities, PrintersError> {
+        let request = printer_attributes_request(
+            printer_name,
+            &utils::requesting_user_name(),
+            CAPABILITIES_ATTRIBUTES,
+        );
+        let response = http::do_request(&request, &utils::printer_resource(printer_name))?;
+        PrinterCapabilities::from_ipp_response(&response)
+    }
+
+    fn get_printer_supplies(printer_name: &str) -> Result<Vec<Supply>, PrintersError> {
+        let request = printer_attributes_request(
+            printer_name,
+            &utils::requesting_user_name(),
+            SUPPLIES_ATTRIBUTES,
+        );
+        let response = http::do_request(&request, &utils::printer_resource(printer_name))?;
+        Supply::from_ipp_response(&response)
+    }
+
+    fn set_printer_state(printer_name: &str, state: PrinterState) -> Result<(), PrintersError> {
+        match state {
+            PrinterState::PAUSED => printers::pause_printer(printer_name),
+            PrinterState::READY => printers::resume_printer(printer_name),
+            state => Err(PrintersError::error(format!(
+                "printer state cannot be set to {state:?}"
+            ))),
+        }
+    }
+
+    fn set_printer_accepting_jobs(
+        printer_name: &str,
+        accepting: bool,
+        reason: Option<&str>,
+    ) -> Result<(), PrintersError> {
+        if accepting {
+            printers::accept_jobs(printer_name)
+        } else {
+            printers::reject_jobs(printer_name, reason)
+        }
+    }
+
+    fn set_printer_state_message(printer_name: &str, message: &str) -> Result<(), PrintersError> {
+        printers::set_state_message(printer_name, message)
+    }
+
+    fn set_default_printer(
+        printer_name: &str,
+        instance: Option<&str>,
+        scope: PrinterScope,
+    ) -> Result<(), PrintersError> {
+        match (scope, instance) {
+            (PrinterScope::User, instance) => {
+                lpoptions::set_default(PrinterScope::User, printer_name, instance)
+            }
+            (PrinterScope::System, None) => printers::set_default(printer_name),
+            (PrinterScope::System, Some(_)) => Err(PrintersError::error(
+                "an instance can only be the default printer of the user scope",
+            )),
+        }
+    }
+
+    fn add_modify_printer(printer_name: &str, config: &PrinterConfig) -> Result<(), PrintersError> {
+        printers::add_modify_printer(printer_name, config)
+    }
+
+    fn delete_printer(printer_name: &str) -> Result<(), PrintersError> {
+        printers::delete_printer(printer_name)
+    }
+
+    fn parse_printer_state(
+        platform_state: u64,
+        state_reasons: &[PrinterStateReason],
+    ) -> PrinterState {
+        if state_reasons
+            .iter()
+            .any(|r| r.kind == PrinterStateReasonKind::Offline)
+        {
+            return PrinterState::OFFLINE;
+        }
+
+        match platform_state {
+            3 => PrinterState::READY,
+            4 => PrinterState::PRINTING,
+            5 => PrinterState::PAUSED,
+            _ => PrinterState::UNKNOWN,
+        }
+    }
+
+    fn parse_printer_job_state(platform_state: u64) -> PrinterJobState {
+        match platform_state {
+            3 => PrinterJobState::PENDING,
+            4 => PrinterJobState::PAUSED,
+            5 => PrinterJobState::PROCESSING,
+            6 => PrinterJobState::STOPPED,
+            7 => PrinterJobState::CANCELLED,
+            8 => PrinterJobState::ABORTED,
+            9 => PrinterJobState::COMPLETED,
+            _ => PrinterJobState::UNKNOWN,
+        }
+    }
+
+    fn set_job_state(
+        printer_name: &str,
+        job_id: u64,
+        state: PrinterJobState,
+    ) -> Result<(), PrintersError> {
+        match state {
+            PrinterJobState::PENDING => jobs::restart_job(printer_name, job_id as i32),
+            PrinterJobState::PROCESSING => jobs::release_job(printer_name, job_id as i32),
+            PrinterJobState::PAUSED => jobs::hold_job(printer_name, job_id as i32),
+            PrinterJobState::CANCELLED => jobs::cancel_job(printer_name, job_id as i32),
+            state => Err(PrintersError::job_error(format!(
+                "cannot change a job to {state:?}"
+            ))),
+        }
+    }
+
+    fn get_job_document(
+        printer_name: &str,
+        job_id: u64,
+        document_number: u32,
+    ) -> Result<JobDocument, PrintersError> {
+        jobs::get_job_document(printer_name, job_id as i32, document_number)
+    }
+
+    fn set_job_attributes(
+        printer_name: &str,
+        job_id: u64,
+        changes: &JobChanges,
+    ) -> Result<(), PrintersError> {
+        jobs::set_job_attributes(printer_name, job_id as i32, changes)
+    }
+
+    fn cancel_jobs(
+        printer_name: &str,
+        filter: &CancelJobsFilter,
+        purge: bool,
+    ) -> Result<Vec<u64>, PrintersError> {
+        let job_ids = filter.matching_jobs(printer_name, &utils::requesting_user_name(), purge)?;
+        if !job_ids.is_empty() {
+            jobs::cancel_jobs(printer_name, filter, purge, &job_ids)?;
+        }
+        Ok(job_ids)
+    }
+
+    fn move_jobs(
+        printer_name: &str,
+        job_id: Option<u64>,
+        target_name: &str,
+    ) -> Result<(), PrintersError> {
+        jobs::move_job(printer_name, job_id.map(|id| id as i32), target_name)
+    }
+}
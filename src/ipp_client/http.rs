@@ -0,0 +1,639 @@
+use std::{
+    env,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{IpAddr, TcpStream},
+    time::Duration,
+};
+
+#[cfg(target_family = "unix")]
+use std::os::unix::net::UnixStream;
+
+use crate::{
+    common::base::errors::PrintersError,
+    ipp::{IppMessage, IppStatusCode, IppVersion},
+    server::{self, CredentialsRequest, Encryption},
+};
+
+const DEFAULT_PORT: u16 = 631;
+const TIMEOUT: Duration = Duration::from_secs(30);
+const IPP_STATUS_VERSION_NOT_SUPPORTED: u16 = 0x0503;
+const HTTP_STATUS_OK: u16 = 200;
+const HTTP_STATUS_UNAUTHORIZED: u16 = 401;
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+/**
+ * Time to wait for the 100-continue of the server before sending a streamed document anyway
+ */
+const CONTINUE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/**
+ * Post of a request to the server with the timeout and the authorization header
+ */
+type Post<'a> =
+    dyn FnMut(&ServerAddress, Duration, Option<&str>) -> Result<HttpResponse, PrintersError> + 'a;
+
+trait Stream: Read + Write {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+impl Stream for TcpStream {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+}
+
+#[cfg(target_family = "unix")]
+impl Stream for UnixStream {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        UnixStream::set_read_timeout(self, timeout)
+    }
+}
+
+/**
+ * Status, authentication challenge and body of an HTTP response
+ */
+struct HttpResponse {
+    status: u16,
+    authenticate: Option<String>,
+    body: Vec<u8>,
+}
+
+/**
+ * Address of the IPP server, resolved like libcups does with the CUPS_SERVER variable
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerAddress {
+    Tcp(String, u16),
+    #[cfg(target_family = "unix")]
+    Socket(String),
+}
+
+impl ServerAddress {
+    /**
+     * Address and timeout of the current server, the CUPS_SERVER one by default
+     */
+    pub fn current() -> Result<(Self, Duration), PrintersError> {
+        let Some(server) = server::current() else {
+            return Ok((Self::from_env(), TIMEOUT));
+        };
+
+        if matches!(server.encryption, Encryption::Required | Encryption::Always) {
+            return Err(PrintersError::error(
+                "TLS is not supported by the ipp-client backend",
+            ));
+        }
+
+        #[cfg(target_family = "unix")]
+        if server.host.starts_with('/') {
+            return Ok((ServerAddress::Socket(server.host), server.timeout));
+        }
+
+        Ok((ServerAddress::Tcp(server.host, server.port), server.timeout))
+    }
+
+    pub fn from_env() -> Self {
+        let server = env::var("CUPS_SERVER").unwrap_or_default();
+        Self::parse(server.trim())
+    }
+
+    fn parse(server: &str) -> Self {
+        #[cfg(target_family = "unix")]
+        if server.starts_with('/') {
+            return ServerAddress::Socket(server.to_string());
+        }
+
+        if server.is_empty() {
+            return ServerAddress::Tcp("localhost".to_string(), DEFAULT_PORT);
+        }
+
+        match server.rsplit_once(':') {
+            Some((host, port)) if !host.ends_with(':') => ServerAddress::Tcp(
+                host.trim_matches(['[', ']']).to_string(),
+                port.parse().unwrap_or(DEFAULT_PORT),
+            ),
+            _ => ServerAddress::Tcp(server.to_string(), DEFAULT_PORT),
+        }
+    }
+
+    fn host(&self) -> &str {
+        match self {
+            ServerAddress::Tcp(host, _) => host,
+            #[cfg(target_family = "unix")]
+            ServerAddress::Socket(_) => "localhost",
+        }
+    }
+
+    /**
+     * Return if the server runs on this machine, the only one credentials are sent to in clear text
+     */
+    fn is_local(&self) -> bool {
+        match self {
+            ServerAddress::Tcp(host, _) => {
+                host.eq_ignore_ascii_case("localhost")
+                    || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+            }
+            #[cfg(target_family = "unix")]
+            ServerAddress::Socket(_) => true,
+        }
+    }
+
+    fn host_header(&self) -> String {
+        match self {
+            ServerAddress::Tcp(host, port) if host.contains(':') => format!("[{host}]:{port}"),
+            ServerAddress::Tcp(host, port) => format!("{host}:{port}"),
+            #[cfg(target_family = "unix")]
+            ServerAddress::Socket(_) => "localhost".to_string(),
+        }
+    }
+
+    fn connect(&self, timeout: Duration) -> Result<Box<dyn Stream>, PrintersError> {
+        match self {
+            ServerAddress::Tcp(host, port) => {
+                let stream = TcpStream::connect((host.as_str(), *port)).map_err(|e| {
+                    PrintersError::error(format!("cannot connect to {host}:{port}: {e}"))
+                        .with_source(e)
+                })?;
+                stream.set_read_timeout(Some(timeout)).map_err(io_error)?;
+                stream.set_write_timeout(Some(timeout)).map_err(io_error)?;
+                // The head, the chunks and the end of a streamed request are separate writes
+                stream.set_nodelay(true).map_err(io_error)?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(target_family = "unix")]
+            ServerAddress::Socket(path) => {
+                let stream = UnixStream::connect(path).map_err(io_error)?;
+                stream.set_read_timeout(Some(timeout)).map_err(io_error)?;
+                Ok(Box::new(stream))
+            }
+        }
+    }
+}
+
+/**
+ * Send an IPP request to a resource of the server and decode the response.
+ * Servers that only speak IPP/1.1 are retried with that version, and
+ * the ones requiring authentication with the credentials of the server.
+ */
+pub fn do_request(request: &IppMessage, resource: &str) -> Result<IppMessage, PrintersError> {
+    let body = request.encode();
+    let response = exchange(request, resource, &mut |server, timeout, authorization| {
+        post(server, timeout, resource, &body, authorization)
+    })?;
+
+    if needs_downgrade(request, &response) {
+        let mut request = request.clone();
+        request.version = IppVersion::V1_1;
+        return do_request(&request, resource);
+    }
+
+    Ok(response)
+}
+
+/**
+ * Send an IPP request followed by a document read in chunks, the document is never held in memory.
+ * The server accepts the request before the document is read (Expect: 100-continue), so the
+ * authentication is answered and a refused request can be retried with the reader untouched.
+ * A server only speaking IPP/1.1 is retried with that version while the document was not read
+ * or is short, a longer document cannot be sent again and fails.
+ */
+pub fn do_streamed_request(
+    request: &IppMessage,
+    resource: &str,
+    reader: &mut dyn Read,
+) -> Result<IppMessage, PrintersError> {
+    let header = request.encode();
+    let mut document = Document::new(reader);
+    let response = exchange(request, resource, &mut |server, timeout, authorization| {
+        if document.touched {
+            return Err(PrintersError::authentication_error(format!(
+                "{resource} requested authentication after the document was sent"
+            )));
+        }
+        post_stream(
+            server,
+            timeout,
+            resource,
+            &header,
+            &mut document,
+            authorization,
+        )
+    })?;
+
+    if needs_downgrade(request, &response) {
+        let mut request = request.clone();
+        request.version = IppVersion::V1_1;
+        if !document.touched {
+            return do_streamed_request(&request, resource, document.reader);
+        }
+        if let Some(data) = document.copy {
+            request.data = data;
+            return do_request(&request, resource);
+        }
+        return Err(PrintersError::from_response(
+            request.operation(),
+            &response,
+            PrintersError::print_error,
+        ));
+    }
+
+    Ok(response)
+}
+
+/**
+ * Post a request with the credentials of the server until authenticated, then decode the response
+ */
+fn exchange(
+    request: &IppMessage,
+    resource: &str,
+    post: &mut Post,
+) -> Result<IppMessage, PrintersError> {
+    let (server, timeout) = ServerAddress::current()?;
+
+    let mut authorization = None;
+    let mut attempt = 0;
+    let response = loop {
+        let response = post(&server, timeout, authorization.as_deref())?;
+        if response.status != HTTP_STATUS_UNAUTHORIZED {
+            break response;
+        }
+
+        attempt += 1;
+        authorization = Some(basic_authorization(&server, resource, &response, attempt)?);
+    };
+
+    if response.status != HTTP_STATUS_OK {
+        return Err(PrintersError::error(format!(
+            "HTTP request failed with status {}",
+            response.status
+        ))
+        .with_code(response.status as i32)
+        .with_operation(&request.operation().keyword()));
+    }
+
+    let response = IppMessage::decode(&response.body)?;
+
+    if response.status() == IppStatusCode::ClientErrorNotAuthenticated {
+        return Err(PrintersError::from_response(
+            request.operation(),
+            &response,
+            PrintersError::authentication_error,
+        ));
+    }
+
+    Ok(response)
+}
+
+/**
+ * Return if the server only speaks IPP/1.1 and the request must be sent again with that version
+ */
+fn needs_downgrade(request: &IppMessage, response: &IppMessage) -> bool {
+    response.code == IPP_STATUS_VERSION_NOT_SUPPORTED && request.version != IppVersion::V1_1
+}
+
+/**
+ * Answer an authentication challenge with the credentials of the server, only the Basic scheme
+ * is supported. Without TLS the password would cross the network in clear text, so it is only
+ * sent to a server on this machine
+ */
+fn basic_authorization(
+    server: &ServerAddress,
+    resource: &str,
+    response: &HttpResponse,
+    attempt: u32,
+) -> Result<String, PrintersError> {
+    let basic = response
+        .authenticate
+        .as_deref()
+        .is_some_and(|v| v.to_ascii_lowercase().starts_with("basic"));
+    if !basic {
+        return Err(PrintersError::authentication_error(format!(
+            "{resource} requires an authentication scheme not supported by the ipp-client backend"
+        )));
+    }
+    if !server.is_local() {
+        return Err(PrintersError::authentication_error(format!(
+            "{resource} requires Basic authentication, the ipp-client backend does not send \
+            a password in clear text to {}",
+            server.host()
+        )));
+    }
+
+    let request = CredentialsRequest {
+        host: server.host(),
+        resource,
+        attempt,
+    };
+    let Some((user_name, password)) = server::credentials().and_then(|c| c.resolve(&request))
+    else {
+        return Err(PrintersError::authentication_error(if attempt > 1 {
+            format!("credentials rejected for {resource}")
+        } else {
+            format!("{resource} requires authentication")
+        }));
+    };
+
+    Ok(format!(
+        "Basic {}",
+        base64(format!("{user_name}:{password}").as_bytes())
+    ))
+}
+
+/**
+ * Standard base64 with padding
+ */
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let value = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(value >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/**
+ * Document of a streamed request, its beginning is kept while short enough to be sent again
+ */
+struct Document<'a> {
+    reader: &'a mut dyn Read,
+    touched: bool,
+    copy: Option<Vec<u8>>,
+}
+
+impl<'a> Document<'a> {
+    fn new(reader: &'a mut dyn Read) -> Self {
+        Document {
+            reader,
+            touched: false,
+            copy: Some(Vec::new()),
+        }
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize, PrintersError> {
+        let count = loop {
+            match self.reader.read(buffer) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result.map_err(PrintersError::from)?,
+            }
+        };
+
+        self.touched |= count > 0;
+        if let Some(copy) = &mut self.copy {
+            if copy.len() + count <= STREAM_CHUNK_SIZE {
+                copy.extend_from_slice(&buffer[..count]);
+            } else {
+                self.copy = None;
+            }
+        }
+        Ok(count)
+    }
+}
+
+/**
+ * Post an application/ipp body and return the response
+ */
+fn post(
+    server: &ServerAddress,
+    timeout: Duration,
+    resource: &str,
+    body: &[u8],
+    authorization: Option<&str>,
+) -> Result<HttpResponse, PrintersError> {
+    let length = format!("Content-Length: {}\r\n", body.len());
+    let mut reader = open(server, timeout, resource, &length, authorization)?;
+
+    let stream = reader.get_mut();
+    stream
+        .write_all(body)
+        .and_then(|_| stream.flush())
+        .map_err(io_error)?;
+
+    read_response(&mut reader)
+}
+
+/**
+ * Post the encoded request then the document in HTTP chunks once the server accepted the request
+ */
+fn post_stream(
+    server: &ServerAddress,
+    timeout: Duration,
+    resource: &str,
+    header: &[u8],
+    document: &mut Document,
+    authorization: Option<&str>,
+) -> Result<HttpResponse, PrintersError> {
+    let chunked = "Transfer-Encoding: chunked\r\nExpect: 100-continue\r\n";
+    let mut reader = open(server, timeout, resource, chunked, authorization)?;
+
+    // A refused request (ex: authentication required) is answered before the document is read
+    if let Some(response) = wait_continue(&mut reader, timeout)? {
+        return Ok(response);
+    }
+
+    write_chunk(reader.get_mut(), header)?;
+    let mut buffer = vec![0; STREAM_CHUNK_SIZE];
+    loop {
+        let count = document.read(&mut buffer)?;
+        write_chunk(reader.get_mut(), &buffer[..count])?;
+        if count == 0 {
+            break;
+        }
+    }
+    reader.get_mut().flush().map_err(io_error)?;
+
+    read_response(&mut reader)
+}
+
+/**
+ * Connect to the server and send the head of a POST request
+ */
+fn open(
+    server: &ServerAddress,
+    timeout: Duration,
+    resource: &str,
+    content: &str,
+    authorization: Option<&str>,
+) -> Result<BufReader<Box<dyn Stream>>, PrintersError> {
+    let mut stream = server.connect(timeout)?;
+
+    let authorization = authorization
+        .map(|v| format!("Authorization: {v}\r\n"))
+        .unwrap_or_default();
+    let head = format!(
+        "POST {resource} HTTP/1.1\r\n\
+        Host: {}\r\n\
+        Content-Type: application/ipp\r\n\
+        {content}\
+        Connection: close\r\n\
+        {authorization}\
+        User-Agent: printers/{}\r\n\r\n",
+        server.host_header(),
+        env!("CARGO_PKG_VERSION"),
+    );
+    stream.write_all(head.as_bytes()).map_err(io_error)?;
+
+    Ok(BufReader::new(stream))
+}
+
+/**
+ * Wait for the 100-continue of the server, returning its final response when it refused the request.
+ * Servers that ignore the expectation get the document after a short delay
+ */
+fn wait_continue(
+    reader: &mut BufReader<Box<dyn Stream>>,
+    timeout: Duration,
+) -> Result<Option<HttpResponse>, PrintersError> {
+    reader.get_mut().flush().map_err(io_error)?;
+    reader
+        .get_ref()
+        .set_read_timeout(Some(CONTINUE_TIMEOUT))
+        .map_err(io_error)?;
+    let answered = match reader.fill_buf() {
+        Ok(_) => true,
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+            ) =>
+        {
+            false
+        }
+        Err(e) => return Err(io_error(e)),
+    };
+    reader
+        .get_ref()
+        .set_read_timeout(Some(timeout))
+        .map_err(io_error)?;
+
+    if !answered {
+        return Ok(None);
+    }
+    let (status, headers) = read_head(reader)?;
+    if status < 200 {
+        return Ok(None);
+    }
+    read_body(reader, status, headers).map(Some)
+}
+
+/**
+ * Write an HTTP chunk in a single write, an empty one ends the body
+ */
+fn write_chunk(stream: &mut dyn Write, data: &[u8]) -> Result<(), PrintersError> {
+    let mut chunk = format!("{:x}\r\n", data.len()).into_bytes();
+    chunk.extend_from_slice(data);
+    chunk.extend_from_slice(b"\r\n");
+    stream.write_all(&chunk).map_err(io_error)
+}
+
+fn read_response(reader: &mut dyn BufRead) -> Result<HttpResponse, PrintersError> {
+    loop {
+        let (status, headers) = read_head(reader)?;
+        // Skip interim responses like 100-continue
+        if status >= 200 {
+            return read_body(reader, status, headers);
+        }
+    }
+}
+
+/**
+ * Read the status and the headers of a response
+ */
+fn read_head(reader: &mut dyn BufRead) -> Result<(u16, Vec<(String, String)>), PrintersError> {
+    let status = read_line(reader)?;
+    let status = status
+        .split_whitespace()
+        .nth(1)
+        .and_then(|v| v.parse::<u16>().ok())
+        .ok_or_else(|| PrintersError::error(format!("Invalid HTTP status line: {status}")))?;
+
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            return Ok((status, headers));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+}
+
+fn read_body(
+    reader: &mut dyn BufRead,
+    status: u16,
+    headers: Vec<(String, String)>,
+) -> Result<HttpResponse, PrintersError> {
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    };
+
+    let body = if header("transfer-encoding").is_some_and(|v| v.contains("chunked")) {
+        read_chunked(reader)?
+    } else if let Some(length) = header("content-length").and_then(|v| v.parse().ok()) {
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).map_err(io_error)?;
+        body
+    } else {
+        let mut body = Vec::new();
+        reader.read_to_end(&mut body).map_err(io_error)?;
+        body
+    };
+
+    Ok(HttpResponse {
+        status,
+        authenticate: header("www-authenticate").map(str::to_string),
+        body,
+    })
+}
+
+fn read_chunked(reader: &mut dyn BufRead) -> Result<Vec<u8>, PrintersError> {
+    let mut body = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        let size = usize::from_str_radix(line.split(';').next().unwrap_or_default().trim(), 16)
+            .map_err(|_| PrintersError::error(format!("Invalid HTTP chunk size: {line}")))?;
+
+        if size == 0 {
+            // Discard trailers
+            while !read_line(reader)?.is_empty() {}
+            return Ok(body);
+        }
+
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..]).map_err(io_error)?;
+        read_line(reader)?;
+    }
+}
+
+fn read_line(reader: &mut dyn BufRead) -> Result<String, PrintersError> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).map_err(io_error)? == 0 {
+        return Err(PrintersError::error("Connection closed by the server"));
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/**
+ * Error of the connection to the server, keeping the io error as source
+ */
+fn io_error(error: io::Error) -> PrintersError {
+    let mut printers_error = PrintersError::error(&error);
+    printers_error.code = error.raw_os_error();
+    printers_error.with_source(error)
+}
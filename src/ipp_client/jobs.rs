@@ -0,0 +1,455 @@
+use std::io::{Cursor, Read};
+
+use crate::{
+    common::base::{
+        errors::PrintersError,
+        job::{CancelJobsFilter, JobChanges, JobDocument, PrinterJobOptions},
+    },
+    ipp::{
+        IppAttribute, IppGroupTag, IppMessage, IppOperation, IppResolutionUnits, IppStatusCode,
+        IppValue,
+        job::{IppJob, from_response, get_job_request, get_jobs_request, jobs_from_response},
+        requests::{
+            cancel_jobs_request, get_document_request, job_request, move_job_request,
+            printer_request, set_job_attributes_request,
+        },
+    },
+    ipp_client::{
+        http,
+        utils::{printer_resource, requesting_user_name},
+    },
+};
+
+/**
+ * Options sent in the operation group, everything else is a job template attribute
+ */
+const OPERATION_OPTIONS: &[&str] = &[
+    "compression",
+    "document-name",
+    "ipp-attribute-fidelity",
+    "job-impressions",
+    "job-k-octets",
+    "job-media-sheets",
+    "job-password",
+    "job-password-encryption",
+];
+
+const ENUM_OPTIONS: &[&str] = &["finishings", "orientation-requested", "print-quality"];
+
+/**
+ * Return the printer jobs using Get-Jobs
+ */
+pub fn get_printer_jobs(
+    printer_name: &str,
+    active_only: bool,
+) -> Result<Vec<IppJob>, PrintersError> {
+    let request = get_jobs_request(printer_name, active_only, &requesting_user_name());
+    let response = http::do_request(&request, &printer_resource(printer_name))?;
+    jobs_from_response(&response)
+}
+
+/**
+ * Return a job using Get-Job-Attributes
+ */
+pub fn get_job(printer_name: Option<&str>, job_id: u64) -> Result<Option<IppJob>, PrintersError> {
+    let request = get_job_request(printer_name, job_id, &requesting_user_name());
+    let resource = printer_name.map_or("/jobs".to_string(), printer_resource);
+    let response = http::do_request(&request, &resource)?;
+    from_response(&response)
+}
+
+/**
+ * Send a buffer to the printer using Print-Job
+ */
+pub fn print_buffer(
+    printer_name: &str,
+    buffer: &[u8],
+    options: &PrinterJobOptions,
+) -> Result<u64, PrintersError> {
+    let mut request = job_creation_request(IppOperation::PrintJob, printer_name, options);
+    add_document_format(&mut request, document_format(options));
+    request.data = buffer.to_vec();
+
+    let response = http::do_request(&request, &printer_resource(printer_name))?;
+    created_job_id(&request, &response)
+}
+
+/**
+ * Print a document read in chunks with Print-Job, the document is never held in memory
+ */
+pub fn print_stream(
+    printer_name: &str,
+    reader: &mut dyn Read,
+    options: &PrinterJobOptions,
+) -> Result<u64, PrintersError> {
+    let mut request = job_creation_request(IppOperation::PrintJob, printer_name, options);
+    add_document_format(&mut request, document_format(options));
+
+    let response = http::do_streamed_request(&request, &printer_resource(printer_name), reader)?;
+    created_job_id(&request, &response)
+}
+
+/**
+ * Create a job without documents using Create-Job
+ */
+pub fn create_job(printer_name: &str, options: &PrinterJobOptions) -> Result<u64, PrintersError> {
+    let request = job_creation_request(IppOperation::CreateJob, printer_name, options);
+    let response = http::do_request(&request, &printer_resource(printer_name))?;
+    created_job_id(&request, &response)
+}
+
+/**
+ * Send a document of a created job using Send-Document, read in chunks
+ */
+pub fn send_document(
+    printer_name: &str,
+    job_id: u64,
+    document_name: &str,
+    reader: &mut dyn Read,
+    document_format: Option<&str>,
+    last_document: bool,
+) -> Result<(), PrintersError> {
+    let mut request = job_request(
+        IppOperation::SendDocument,
+        printer_name,
+        job_id as i32,
+        &requesting_user_name(),
+    );
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::new("document-name", IppValue::Name(document_name.to_string())),
+    );
+    add_document_format(&mut request, document_format);
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::new("last-document", IppValue::Boolean(last_document)),
+    );
+
+    let response = http::do_streamed_request(&request, &printer_resource(printer_name), reader)?;
+    if !response.is_successful() {
+        return Err(PrintersError::from_response(
+            IppOperation::SendDocument,
+            &response,
+            PrintersError::print_error,
+        ));
+    }
+
+    Ok(())
+}
+
+/**
+ * Create a Print-Job or Create-Job request with the job options
+ */
+fn job_creation_request(
+    operation: IppOperation,
+    printer_name: &str,
+    options: &PrinterJobOptions,
+) -> IppMessage {
+    let mut request = new_request(operation, printer_name);
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::new(
+            "job-name",
+            IppValue::Name(options.name.unwrap_or("printers").to_string()),
+        ),
+    );
+
+    for attribute in options.operation_attributes() {
+        request.add(IppGroupTag::Operation, attribute);
+    }
+    for attribute in options.job_attributes() {
+        request.add(IppGroupTag::Job, attribute);
+    }
+
+    for (name, value) in options.raw_properties {
+        if *name == "document-format" {
+            continue;
+        }
+
+        let group = if OPERATION_OPTIONS.contains(name) {
+            IppGroupTag::Operation
+        } else {
+            IppGroupTag::Job
+        };
+
+        request.add(group, option_to_attribute(name, value));
+    }
+
+    request
+}
+
+/**
+ * Return the job id of a Print-Job or Create-Job response
+ */
+fn created_job_id(request: &IppMessage, response: &IppMessage) -> Result<u64, PrintersError> {
+    let job_id = response
+        .group(IppGroupTag::Job)
+        .and_then(|g| g.integer("job-id"));
+
+    match job_id {
+        Some(job_id) if response.is_successful() => Ok(job_id as u64),
+        _ => Err(PrintersError::from_response(
+            request.operation(),
+            response,
+            PrintersError::print_error,
+        )),
+    }
+}
+
+/**
+ * The document-format raw property of the options
+ */
+fn document_format<'a>(options: &PrinterJobOptions<'a>) -> Option<&'a str> {
+    options
+        .raw_properties
+        .iter()
+        .find(|(name, _)| *name == "document-format")
+        .map(|(_, value)| *value)
+}
+
+fn add_document_format(request: &mut IppMessage, document_format: Option<&str>) {
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::new(
+            "document-format",
+            IppValue::MimeMediaType(
+                document_format
+                    .unwrap_or("application/octet-stream")
+                    .to_string(),
+            ),
+        ),
+    );
+}
+
+/**
+ * Send hold job request
+ */
+pub fn hold_job(printer_name: &str, job_id: i32) -> Result<(), PrintersError> {
+    do_request(printer_name, job_id, IppOperation::HoldJob)
+}
+
+/**
+ * Send release job request
+ */
+pub fn release_job(printer_name: &str, job_id: i32) -> Result<(), PrintersError> {
+    do_request(printer_name, job_id, IppOperation::ReleaseJob)
+}
+
+/**
+ * Send restart job request
+ */
+pub fn restart_job(printer_name: &str, job_id: i32) -> Result<(), PrintersError> {
+    do_request(printer_name, job_id, IppOperation::RestartJob)
+}
+
+/**
+ * Send cancel job request
+ */
+pub fn cancel_job(printer_name: &str, job_id: i32) -> Result<(), PrintersError> {
+    do_request(printer_name, job_id, IppOperation::CancelJob)
+}
+
+/**
+ * Return a document of a job (CUPS-Get-Document), the server must keep the job files
+ */
+pub fn get_job_document(
+    printer_name: &str,
+    job_id: i32,
+    document_number: u32,
+) -> Result<JobDocument, PrintersError> {
+    let request = get_document_request(
+        printer_name,
+        job_id,
+        document_number,
+        &requesting_user_name(),
+    );
+    let mut response = http::do_request(&request, "/")?;
+    let data = std::mem::take(&mut response.data);
+    JobDocument::from_ipp_response(&response, Box::new(Cursor::new(data)))
+}
+
+/**
+ * Change the attributes of a job that has not started (Set-Job-Attributes)
+ */
+pub fn set_job_attributes(
+    printer_name: &str,
+    job_id: i32,
+    changes: &JobChanges,
+) -> Result<(), PrintersError> {
+    let request =
+        set_job_attributes_request(printer_name, job_id, &requesting_user_name(), changes);
+    let response = http::do_request(&request, &printer_resource(printer_name))?;
+    if response.is_successful() {
+        Ok(())
+    } else {
+        Err(PrintersError::from_response(
+            IppOperation::SetJobAttributes,
+            &response,
+            PrintersError::job_error,
+        ))
+    }
+}
+
+/**
+ * Cancel the listed jobs of a queue matching the filter, purging their history too if requested
+ */
+pub fn cancel_jobs(
+    printer_name: &str,
+    filter: &CancelJobsFilter,
+    purge: bool,
+    job_ids: &[u64],
+) -> Result<(), PrintersError> {
+    let request = cancel_jobs_request(
+        printer_name,
+        filter,
+        purge,
+        job_ids,
+        &requesting_user_name(),
+    );
+    let resource = match request.operation() {
+        IppOperation::CancelMyJobs => "/jobs/",
+        _ => "/admin/",
+    };
+
+    let response = http::do_request(&request, resource)?;
+    if response.is_successful() {
+        Ok(())
+    } else {
+        Err(PrintersError::from_response(
+            request.operation(),
+            &response,
+            PrintersError::job_error,
+        ))
+    }
+}
+
+/**
+ * Move a job, or every job of the queue when job_id is None, to another queue (CUPS-Move-Job)
+ */
+pub fn move_job(
+    printer_name: &str,
+    job_id: Option<i32>,
+    target_name: &str,
+) -> Result<(), PrintersError> {
+    let request = move_job_request(printer_name, job_id, target_name, &requesting_user_name());
+    let response = http::do_request(&request, "/jobs/")?;
+
+    match response.status() {
+        _ if response.is_successful() => Ok(()),
+        IppStatusCode::ServerErrorNotAcceptingJobs => Err(PrintersError::from_response(
+            IppOperation::CupsMoveJob,
+            &response,
+            PrintersError::not_accepting_jobs_error,
+        )),
+        _ => Err(PrintersError::from_response(
+            IppOperation::CupsMoveJob,
+            &response,
+            PrintersError::job_error,
+        )),
+    }
+}
+
+/**
+ * Send a job operation request
+ */
+fn do_request(
+    printer_name: &str,
+    job_id: i32,
+    operation: IppOperation,
+) -> Result<(), PrintersError> {
+    let request = job_request(operation, printer_name, job_id, &requesting_user_name());
+
+    let response = http::do_request(&request, &printer_resource(printer_name))?;
+    if response.is_successful() {
+        Ok(())
+    } else {
+        Err(PrintersError::from_response(
+            operation,
+            &response,
+            PrintersError::job_error,
+        ))
+    }
+}
+
+/**
+ * Create a request addressed to a printer
+ */
+fn new_request(operation: IppOperation, printer_name: &str) -> IppMessage {
+    printer_request(operation, printer_name, &requesting_user_name())
+}
+
+/**
+ * Encode a string option with the IPP syntax its value looks like, as cupsEncodeOptions does
+ */
+fn option_to_attribute(name: &str, value: &str) -> IppAttribute {
+    let values = value
+        .split(',')
+        .map(|value| {
+            let value = value.trim();
+
+            if let Ok(number) = value.parse::<i32>() {
+                return if ENUM_OPTIONS.contains(&name) {
+                    IppValue::Enum(number)
+                } else if name == "page-ranges" {
+                    IppValue::RangeOfInteger {
+                        lower: number,
+                        upper: number,
+                    }
+                } else {
+                    IppValue::Integer(number)
+                };
+            }
+
+            if value == "true" || value == "false" {
+                return IppValue::Boolean(value == "true");
+            }
+
+            if let Some(range) = parse_range(value) {
+                return range;
+            }
+
+            if let Some(resolution) = parse_resolution(value) {
+                return resolution;
+            }
+
+            let is_keyword = !value.is_empty()
+                && value
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "-_.".contains(c));
+
+            if is_keyword {
+                IppValue::Keyword(value.to_string())
+            } else {
+                IppValue::Name(value.to_string())
+            }
+        })
+        .collect();
+
+    IppAttribute::with_values(name, values)
+}
+
+fn parse_range(value: &str) -> Option<IppValue> {
+    let (lower, upper) = value.split_once('-')?;
+    Some(IppValue::RangeOfInteger {
+        lower: lower.parse().ok()?,
+        upper: upper.parse().ok()?,
+    })
+}
+
+fn parse_resolution(value: &str) -> Option<IppValue> {
+    let (value, units) = if let Some(value) = value.strip_suffix("dpi") {
+        (value, IppResolutionUnits::DotsPerInch)
+    } else {
+        (
+            value.strip_suffix("dpcm")?,
+            IppResolutionUnits::DotsPerCentimeter,
+        )
+    };
+
+    let (cross_feed, feed) = value.split_once('x').unwrap_or((value, value));
+    Some(IppValue::Resolution {
+        cross_feed: cross_feed.parse().ok()?,
+        feed: feed.parse().ok()?,
+        units,
+    })
+}
@@ -0,0 +1,283 @@
+use crate::{
+    admin::PrinterConfig,
+    common::{base::errors::PrintersError, traits::platform::PlatformPrinterGetters},
+    ipp::{
+        IppAttribute, IppAttributeGroup, IppGroupTag, IppMessage, IppOperation, IppStatusCode,
+        IppValue,
+        requests::{
+            add_modify_printer_request, next_request_id, printer_admin_request,
+            printer_state_message_request,
+        },
+    },
+    ipp_client::{http, utils::requesting_user_name},
+    lpoptions,
+};
+
+const PRINTER_ATTRIBUTES: &[&str] = &[
+    "printer-name",
+    "printer-info",
+    "printer-location",
+    "printer-make-and-model",
+    "printer-uri-supported",
+    "printer-state",
+    "printer-state-reasons",
+    "printer-is-shared",
+    "device-uri",
+    "media-default",
+];
+
+/**
+ * Attributes of a printer returned by the IPP server
+ */
+pub struct IppPrinter {
+    attributes: IppAttributeGroup,
+    is_default: bool,
+    instance: Option<String>,
+}
+
+impl PlatformPrinterGetters for IppPrinter {
+    fn get_name(&self) -> String {
+        let info = self.attributes.string("printer-info");
+        if info.trim().is_empty() {
+            self.get_system_name()
+        } else {
+            info.trim().to_string()
+        }
+    }
+
+    fn get_is_default(&self) -> bool {
+        self.is_default
+    }
+
+    fn get_system_name(&self) -> String {
+        self.attributes.string("printer-name")
+    }
+
+    fn get_marker_and_model(&self) -> String {
+        self.attributes.string("printer-make-and-model")
+    }
+
+    fn get_is_shared(&self) -> bool {
+        self.attributes
+            .value("printer-is-shared")
+            .and_then(|v| v.as_bool())
+            .unwrap_or_default()
+    }
+
+    fn get_uri(&self) -> String {
+        self.attributes.string("printer-uri-supported")
+    }
+
+    fn get_location(&self) -> String {
+        self.attributes.string("printer-location")
+    }
+
+    fn get_state(&self) -> u64 {
+        self.attributes.integer("printer-state").unwrap_or_default() as u64
+    }
+
+    fn get_state_reasons(&self) -> Vec<String> {
+        self.attributes
+            .strings("printer-state-reasons")
+            .into_iter()
+            .filter(|v| v != "none")
+            .collect()
+    }
+
+    fn get_port_name(&self) -> String {
+        self.attributes.string("device-uri")
+    }
+
+    fn get_processor(&self) -> String {
+        "".to_string()
+    }
+
+    fn get_description(&self) -> String {
+        "".to_string()
+    }
+
+    fn get_data_type(&self) -> String {
+        self.attributes.string("media-default")
+    }
+
+    fn get_instance(&self) -> Option<String> {
+        self.instance.clone()
+    }
+}
+
+/**
+ * Return all printers of the server using CUPS-Get-Printers,
+ * followed by their instances and with the default of the lpoptions files, as libcups does
+ */
+pub fn get_printers() -> Result<Vec<IppPrinter>, PrintersError> {
+    let default_name = get_default_printer_name();
+
+    let mut request = IppMessage::request(IppOperation::CupsGetPrinters, next_request_id());
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::new(
+            "requesting-user-name",
+            IppValue::Name(requesting_user_name()),
+        ),
+    );
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::with_values(
+            "requested-attributes",
+            PRINTER_ATTRIBUTES
+                .iter()
+                .map(|v| IppValue::Keyword(v.to_string()))
+                .collect(),
+        ),
+    );
+
+    let response = http::do_request(&request, "/")?;
+    // CUPS answers client-error-not-found when there are no printers
+    if response.status() == IppStatusCode::ClientErrorNotFound {
+        return Ok(Vec::new());
+    }
+    if !response.is_successful() {
+        return Err(PrintersError::from_response(
+            IppOperation::CupsGetPrinters,
+            &response,
+            PrintersError::error,
+        ));
+    }
+
+    let destinations = lpoptions::destinations();
+    let local_default = destinations.iter().find(|d| d.is_default);
+    let is_default = |name: &str, instance: Option<&str>| match local_default {
+        Some(d) => d.name.eq_ignore_ascii_case(name) && d.instance.as_deref() == instance,
+        None => instance.is_none() && default_name.as_deref() == Some(name),
+    };
+
+    let mut printers = Vec::new();
+    for attributes in response.groups(IppGroupTag::Printer) {
+        let name = attributes.string("printer-name");
+        printers.push(IppPrinter {
+            is_default: is_default(&name, None),
+            attributes: attributes.clone(),
+            instance: None,
+        });
+
+        for instance in destinations
+            .iter()
+            .filter(|d| d.name.eq_ignore_ascii_case(&name))
+            .filter_map(|d| d.instance.as_deref())
+        {
+            printers.push(IppPrinter {
+                is_default: is_default(&name, Some(instance)),
+                attributes: attributes.clone(),
+                instance: Some(instance.to_string()),
+            });
+        }
+    }
+
+    Ok(printers)
+}
+
+/**
+ * Return the name of the server default printer using CUPS-Get-Default
+ */
+fn get_default_printer_name() -> Option<String> {
+    let mut request = IppMessage::request(IppOperation::CupsGetDefault, next_request_id());
+    request.add(
+        IppGroupTag::Operation,
+        IppAttribute::new(
+            "requested-attributes",
+            IppValue::Keyword("printer-name".to_string()),
+        ),
+    );
+
+    let response = http::do_request(&request, "/").ok()?;
+    if !response.is_successful() {
+        return None;
+    }
+
+    response
+        .group(IppGroupTag::Printer)
+        .map(|g| g.string("printer-name"))
+}
+
+/**
+ * Stop processing the jobs of the queue (Pause-Printer)
+ */
+pub fn pause_printer(printer_name: &str) -> Result<(), PrintersError> {
+    do_admin_request(IppOperation::PausePrinter, printer_name, None)
+}
+
+/**
+ * Start processing the jobs of the queue again (Resume-Printer)
+ */
+pub fn resume_printer(printer_name: &str) -> Result<(), PrintersError> {
+    do_admin_request(IppOperation::ResumePrinter, printer_name, None)
+}
+
+/**
+ * Accept new jobs on the queue (CUPS-Accept-Jobs)
+ */
+pub fn accept_jobs(printer_name: &str) -> Result<(), PrintersError> {
+    do_admin_request(IppOperation::CupsAcceptJobs, printer_name, None)
+}
+
+/**
+ * Reject new jobs on the queue (CUPS-Reject-Jobs)
+ */
+pub fn reject_jobs(printer_name: &str, reason: Option<&str>) -> Result<(), PrintersError> {
+    do_admin_request(IppOperation::CupsRejectJobs, printer_name, reason)
+}
+
+/**
+ * Change the printer-state-message of the queue (CUPS-Add-Modify-Printer)
+ */
+pub fn set_state_message(printer_name: &str, message: &str) -> Result<(), PrintersError> {
+    let request = printer_state_message_request(printer_name, &requesting_user_name(), message);
+    send_admin_request(&request)
+}
+
+/**
+ * Make the queue the server default (CUPS-Set-Default)
+ */
+pub fn set_default(printer_name: &str) -> Result<(), PrintersError> {
+    do_admin_request(IppOperation::CupsSetDefault, printer_name, None)
+}
+
+/**
+ * Create or change a queue (CUPS-Add-Modify-Printer)
+ */
+pub fn add_modify_printer(printer_name: &str, config: &PrinterConfig) -> Result<(), PrintersError> {
+    let request = add_modify_printer_request(printer_name, &requesting_user_name(), config);
+    send_admin_request(&request)
+}
+
+/**
+ * Delete a queue (CUPS-Delete-Printer)
+ */
+pub fn delete_printer(printer_name: &str) -> Result<(), PrintersError> {
+    do_admin_request(IppOperation::CupsDeletePrinter, printer_name, None)
+}
+
+fn do_admin_request(
+    operation: IppOperation,
+    printer_name: &str,
+    message: Option<&str>,
+) -> Result<(), PrintersError> {
+    let request = printer_admin_request(operation, printer_name, &requesting_user_name(), message);
+    send_admin_request(&request)
+}
+
+/**
+ * Send a queue administration request to the admin resource, as cupsdisable and cupsreject do
+ */
+fn send_admin_request(request: &IppMessage) -> Result<(), PrintersError> {
+    let response = http::do_request(request, "/admin/")?;
+    if response.is_successful() {
+        Ok(())
+    } else {
+        Err(PrintersError::from_response(
+            request.operation(),
+            &response,
+            PrintersError::error,
+        ))
+    }
+}
@@ -0,0 +1,24 @@
+use std::env;
+
+use crate::{ipp::requests::percent_encode, server};
+
+/**
+ * Name sent as requesting-user-name, resolved like libcups does
+ */
+pub fn requesting_user_name() -> String {
+    if let Some(user_name) = server::current().and_then(|s| s.user_name) {
+        return user_name;
+    }
+
+    ["CUPS_USER", "USER", "LOGNAME", "USERNAME"]
+        .iter()
+        .find_map(|v| env::var(v).ok().filter(|v| !v.is_empty()))
+        .unwrap_or_else(|| "anonymous".to_string())
+}
+
+/**
+ * The HTTP resource of a queue, with the name percent-encoded
+ */
+pub fn printer_resource(printer_name: &str) -> String {
+    format!("/printers/{}", percent_encode(printer_name))
+}
@@ -24,20 +24,21 @@
 //! // Get a printer by the name
 //! let my_printer = get_printer_by_name("my_printer");
 //! if my_printer.is_some() {
-//!     let _job_id = my_printer.unwrap().print_file("notes.txt", PrinterJobOptions::none());
+//!     let _job = my_printer.unwrap().print_file("notes.txt", PrinterJobOptions::none());
 //!     // Err("...") or Ok(())
 //! }
 //!
 //! // Use the default printer
 //! let default_printer = get_default_printer();
 //! if default_printer.is_some() {
-//!     let _job_id = default_printer.unwrap().print(b"hello world", PrinterJobOptions {
+//!     let _job = default_printer.unwrap().print(b"hello world", PrinterJobOptions {
 //!         name: None,
 //!         raw_properties: &[
 //!             ("document-format", "application/vnd.cups-raw"),
 //!             ("copies", "2"),
 //!         ],
 //!         converter: Converter::Ghostscript(GhostscriptConverterOptions::ps2write()),
+//!         ..PrinterJobOptions::none()
 //!     });
 //!     // Err("...") or Ok(())
 //! }
@@ -46,15 +47,32 @@
 
 struct Platform;
 
+pub mod admin;
 pub mod common;
+pub mod ipp;
+pub mod lpoptions;
+pub mod server;
 
-#[cfg(target_family = "unix")]
+#[cfg(feature = "async")]
+pub mod asynchronous;
+
+#[cfg(feature = "ipp-client")]
+mod ipp_client;
+
+#[cfg(all(target_family = "unix", not(feature = "ipp-client")))]
 mod unix;
 
-#[cfg(target_family = "windows")]
+#[cfg(all(target_family = "windows", not(feature = "ipp-client")))]
 mod windows;
 
-use common::{base::printer::Printer, traits::platform::PlatformActions};
+use common::{
+    base::{
+        errors::PrintersError,
+        job::PrinterJob,
+        printer::{Printer, PrinterScope},
+    },
+    traits::platform::PlatformActions,
+};
 
 /**
  * Return all available printers on a system
@@ -76,3 +94,25 @@ pub fn get_printer_by_name(printer_name: &str) -> Option<Printer> {
 pub fn get_default_printer() -> Option<Printer> {
     Platform::get_default_printer()
 }
+
+/**
+ * Return a job of any printer by id
+ */
+pub fn get_job(job_id: u64) -> Option<PrinterJob> {
+    Platform::get_printer_job(None, job_id).ok().flatten()
+}
+
+/**
+ * Change the default printer, the name may include an instance (ex: office/duplex).
+ * On cups the user scope is written to ~/.cups/lpoptions and the system scope is the server default.
+ */
+pub fn set_default_printer(printer_name: &str, scope: PrinterScope) -> Result<(), PrintersError> {
+    if Platform::get_printer_by_name(printer_name).is_none() {
+        return Err(PrintersError::error(format!(
+            "printer {printer_name} not found"
+        )));
+    }
+
+    let (printer_name, instance) = lpoptions::split_name(printer_name);
+    Platform::set_default_printer(printer_name, instance, scope)
+}
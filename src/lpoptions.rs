@@ -0,0 +1,358 @@
+use std::{
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use crate::common::base::{errors::PrintersError, printer::PrinterScope};
+
+/**
+ * A destination of an lpoptions file, a queue or a named instance of a queue (ex: office/duplex)
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct LpDestination {
+    /**
+     * Name of the queue
+     */
+    pub name: String,
+
+    /**
+     * Name of the instance, None for the queue itself
+     */
+    pub instance: Option<String>,
+
+    /**
+     * Destination written as Default instead of Dest
+     */
+    pub is_default: bool,
+
+    /**
+     * Default job options of the destination
+     */
+    pub options: Vec<(String, String)>,
+}
+
+impl LpDestination {
+    fn is(&self, name: &str, instance: Option<&str>) -> bool {
+        self.name.eq_ignore_ascii_case(name) && self.instance.as_deref() == instance
+    }
+}
+
+/**
+ * Path of the lpoptions file of a scope, ~/.cups/lpoptions or $CUPS_SERVERROOT/lpoptions.
+ * Fails for the user scope when the home directory is unknown
+ */
+pub fn file_path(scope: PrinterScope) -> Result<PathBuf, PrintersError> {
+    match scope {
+        PrinterScope::User => home_dir()
+            .map(|home| home.join(".cups").join("lpoptions"))
+            .ok_or_else(|| PrintersError::file_error("cannot find the home directory of the user")),
+        PrinterScope::System => Ok(env::var_os("CUPS_SERVERROOT")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("/etc/cups"))
+            .join("lpoptions")),
+    }
+}
+
+/**
+ * Home directory from HOME, or from the password database as libcups does
+ */
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+        .or_else(passwd_home_dir)
+}
+
+#[cfg(target_family = "unix")]
+fn passwd_home_dir() -> Option<PathBuf> {
+    use std::{
+        ffi::{CStr, OsStr},
+        mem,
+        os::unix::ffi::OsStrExt,
+        ptr,
+    };
+
+    let mut passwd: libc::passwd = unsafe { mem::zeroed() };
+    let mut buffer = vec![0 as libc::c_char; 16384];
+    let mut result = ptr::null_mut();
+
+    let status = unsafe {
+        libc::getpwuid_r(
+            libc::getuid(),
+            &mut passwd,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &mut result,
+        )
+    };
+    if status != 0 || result.is_null() || passwd.pw_dir.is_null() {
+        return None;
+    }
+
+    let home = unsafe { CStr::from_ptr(passwd.pw_dir) };
+    Some(PathBuf::from(OsStr::from_bytes(home.to_bytes()))).filter(|home| home.is_absolute())
+}
+
+#[cfg(not(target_family = "unix"))]
+fn passwd_home_dir() -> Option<PathBuf> {
+    None
+}
+
+/**
+ * Read the destinations of a scope, a missing file has no destinations
+ */
+pub fn read(scope: PrinterScope) -> Result<Vec<LpDestination>, PrintersError> {
+    read_file(&file_path(scope)?)
+}
+
+/**
+ * Replace the destinations of a scope
+ */
+pub fn write(scope: PrinterScope, destinations: &[LpDestination]) -> Result<(), PrintersError> {
+    let path = file_path(scope)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(PrintersError::from)?;
+    }
+
+    let content: String = destinations
+        .iter()
+        .map(|d| {
+            let mut line = if d.is_default { "Default " } else { "Dest " }.to_string();
+            line.push_str(&d.name);
+            if let Some(instance) = &d.instance {
+                line.push('/');
+                line.push_str(instance);
+            }
+            for (name, value) in &d.options {
+                line.push_str(&format!(" {}={}", name, escape(value)));
+            }
+            line + "\n"
+        })
+        .collect();
+
+    fs::write(path, content).map_err(PrintersError::from)
+}
+
+/**
+ * Return the options of a queue or instance in a scope
+ */
+pub fn get_options(
+    scope: PrinterScope,
+    printer_name: &str,
+    instance: Option<&str>,
+) -> Result<Vec<(String, String)>, PrintersError> {
+    Ok(read(scope)?
+        .into_iter()
+        .find(|d| d.is(printer_name, instance))
+        .map(|d| d.options)
+        .unwrap_or_default())
+}
+
+/**
+ * Replace the options of a queue or instance in a scope, creating the instance if needed
+ */
+pub fn set_options(
+    scope: PrinterScope,
+    printer_name: &str,
+    instance: Option<&str>,
+    options: &[(&str, &str)],
+) -> Result<(), PrintersError> {
+    let options: Vec<(String, String)> = options
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+
+    let mut destinations = read(scope)?;
+    match destinations
+        .iter_mut()
+        .find(|d| d.is(printer_name, instance))
+    {
+        Some(destination) => destination.options = options,
+        None => destinations.push(LpDestination {
+            name: printer_name.to_string(),
+            instance: instance.map(str::to_string),
+            is_default: false,
+            options,
+        }),
+    }
+
+    write(scope, &destinations)
+}
+
+/**
+ * Remove a queue or instance from a scope
+ */
+pub fn remove(
+    scope: PrinterScope,
+    printer_name: &str,
+    instance: Option<&str>,
+) -> Result<(), PrintersError> {
+    let mut destinations = read(scope)?;
+    destinations.retain(|d| !d.is(printer_name, instance));
+    write(scope, &destinations)
+}
+
+/**
+ * Make a queue or instance the default of a scope
+ */
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+pub(crate) fn set_default(
+    scope: PrinterScope,
+    printer_name: &str,
+    instance: Option<&str>,
+) -> Result<(), PrintersError> {
+    let mut destinations = read(scope)?;
+    for destination in destinations.iter_mut() {
+        destination.is_default = false;
+    }
+
+    match destinations
+        .iter_mut()
+        .find(|d| d.is(printer_name, instance))
+    {
+        Some(destination) => destination.is_default = true,
+        None => destinations.push(LpDestination {
+            name: printer_name.to_string(),
+            instance: instance.map(str::to_string),
+            is_default: true,
+            options: Vec::new(),
+        }),
+    }
+
+    write(scope, &destinations)
+}
+
+/**
+ * Destinations of the system and user scopes merged as libcups does, user options win
+ */
+pub(crate) fn destinations() -> Vec<LpDestination> {
+    let user = read(PrinterScope::User).unwrap_or_default();
+    let has_user_default = user.iter().any(|d| d.is_default);
+    let mut destinations = read(PrinterScope::System).unwrap_or_default();
+
+    if has_user_default {
+        for destination in destinations.iter_mut() {
+            destination.is_default = false;
+        }
+    }
+
+    for destination in user {
+        let index = destinations
+            .iter()
+            .position(|d| d.is(&destination.name, destination.instance.as_deref()));
+
+        match index {
+            Some(index) => {
+                let merged = &mut destinations[index];
+                merged.is_default |= destination.is_default;
+                for (name, value) in destination.options {
+                    merged.options.retain(|(n, _)| *n != name);
+                    merged.options.push((name, value));
+                }
+            }
+            None => destinations.push(destination),
+        }
+    }
+
+    destinations
+}
+
+/**
+ * Options of an instance from the merged scopes
+ */
+pub(crate) fn instance_options(printer_name: &str, instance: &str) -> Vec<(String, String)> {
+    destinations()
+        .into_iter()
+        .find(|d| d.is(printer_name, Some(instance)))
+        .map(|d| d.options)
+        .unwrap_or_default()
+}
+
+/**
+ * Split a destination name like office/duplex into the queue and the instance
+ */
+pub(crate) fn split_name(name: &str) -> (&str, Option<&str>) {
+    match name.split_once('/') {
+        Some((name, instance)) if !instance.is_empty() => (name, Some(instance)),
+        _ => (name.trim_end_matches('/'), None),
+    }
+}
+
+fn read_file(path: &Path) -> Result<Vec<LpDestination>, PrintersError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(PrintersError::file_error(error)),
+    };
+
+    Ok(content.lines().filter_map(parse_line).collect())
+}
+
+fn parse_line(line: &str) -> Option<LpDestination> {
+    let mut words = split_words(line).into_iter();
+    let is_default = match words.next()?.to_ascii_lowercase().as_str() {
+        "dest" => false,
+        "default" => true,
+        _ => return None,
+    };
+
+    let destination = words.next()?;
+    let (name, instance) = split_name(&destination);
+    let name = name.to_string();
+    let instance = instance.map(str::to_string);
+
+    let options = words
+        .map(|word| match word.split_once('=') {
+            Some((name, value)) => (name.to_string(), value.to_string()),
+            None => (word, "true".to_string()),
+        })
+        .collect();
+
+    Some(LpDestination {
+        name,
+        instance,
+        is_default,
+        options,
+    })
+}
+
+/**
+ * Split a line on spaces, honoring quotes and backslash escapes like cupsParseOptions
+ */
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => word.extend(chars.next()),
+            c if Some(c) == quote => quote = None,
+            '"' | '\'' if quote.is_none() => quote = Some(c),
+            c if c.is_whitespace() && quote.is_none() => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c.is_whitespace() || matches!(c, '\\' | '"' | '\'') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
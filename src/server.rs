@@ -0,0 +1,345 @@
+use std::{
+    cell::RefCell,
+    fmt::{Debug, Error, Formatter},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use crate::common::base::{
+    errors::PrintersError,
+    job::PrinterJob,
+    printer::{Printer, PrinterScope},
+};
+
+thread_local! {
+    /**
+     * Server the requests of the current thread are sent to, None for the default server
+     */
+    static CURRENT: RefCell<Option<PrintServer>> = const { RefCell::new(None) };
+}
+
+/**
+ * Credentials of the default server
+ */
+static DEFAULT_CREDENTIALS: RwLock<Option<Credentials>> = RwLock::new(None);
+
+/**
+ * Times the credentials of a callback are asked for a single request
+ */
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+const MAX_ATTEMPTS: u32 = 3;
+
+/**
+ * Server asking for credentials
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct CredentialsRequest<'a> {
+    /**
+     * Host name of the server
+     */
+    pub host: &'a str,
+
+    /**
+     * Resource of the request (ex: /admin/)
+     */
+    pub resource: &'a str,
+
+    /**
+     * 1 on the first request, greater when the previous credentials were rejected
+     */
+    pub attempt: u32,
+}
+
+/**
+ * Function returning the user name and the password for a request
+ */
+pub type CredentialsCallback =
+    dyn Fn(&CredentialsRequest) -> Option<(String, String)> + Send + Sync;
+
+/**
+ * Provider of the user name and password sent when a server requires authentication.
+ * The ipp-client backend only answers the Basic scheme and, having no TLS, only to a local server
+ */
+#[derive(Clone)]
+pub enum Credentials {
+    /**
+     * Fixed user name and password
+     */
+    Static { user_name: String, password: String },
+    /**
+     * Asked on demand, None cancels the authentication
+     */
+    Callback(Arc<CredentialsCallback>),
+    /**
+     * Read from the CUPS_USER (or the user of the process) and CUPS_PASSWORD variables
+     */
+    Environment,
+}
+
+impl Credentials {
+    /**
+     * Fixed user name and password
+     */
+    pub fn new(user_name: &str, password: &str) -> Self {
+        Credentials::Static {
+            user_name: user_name.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    /**
+     * Credentials asked to a function, returning the user name and the password
+     */
+    pub fn callback(
+        f: impl Fn(&CredentialsRequest) -> Option<(String, String)> + Send + Sync + 'static,
+    ) -> Self {
+        Credentials::Callback(Arc::new(f))
+    }
+
+    /**
+     * Return the user name and the password for a request. Fixed credentials are only sent once,
+     * the server would reject them again
+     */
+    #[cfg(any(target_family = "unix", feature = "ipp-client"))]
+    pub(crate) fn resolve(&self, request: &CredentialsRequest) -> Option<(String, String)> {
+        match self {
+            Credentials::Static {
+                user_name,
+                password,
+            } if request.attempt == 1 => Some((user_name.clone(), password.clone())),
+            Credentials::Callback(f) if request.attempt <= MAX_ATTEMPTS => f(request),
+            Credentials::Environment if request.attempt == 1 => {
+                let password = std::env::var("CUPS_PASSWORD").ok()?;
+                let user_name = ["CUPS_USER", "USER", "LOGNAME", "USERNAME"]
+                    .iter()
+                    .find_map(|v| std::env::var(v).ok().filter(|v| !v.is_empty()))?;
+                Some((user_name, password))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Debug for Credentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Credentials::Static { user_name, .. } => f
+                .debug_struct("Static")
+                .field("user_name", user_name)
+                .finish_non_exhaustive(),
+            Credentials::Callback(_) => f.write_str("Callback"),
+            Credentials::Environment => f.write_str("Environment"),
+        }
+    }
+}
+
+impl PartialEq for Credentials {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Credentials::Static {
+                    user_name,
+                    password,
+                },
+                Credentials::Static {
+                    user_name: other_user_name,
+                    password: other_password,
+                },
+            ) => user_name == other_user_name && password == other_password,
+            (Credentials::Callback(f), Credentials::Callback(other)) => Arc::ptr_eq(f, other),
+            (Credentials::Environment, Credentials::Environment) => true,
+            _ => false,
+        }
+    }
+}
+
+/**
+ * When the connection to the server is encrypted (HTTP_ENCRYPTION_* of libcups)
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encryption {
+    /**
+     * Upgrade to TLS when the server asks for it
+     */
+    IfRequested,
+    /**
+     * Never use TLS
+     */
+    Never,
+    /**
+     * Upgrade to TLS before sending requests
+     */
+    Required,
+    /**
+     * Use TLS from the start of the connection (ipps)
+     */
+    Always,
+}
+
+impl Encryption {
+    #[cfg(all(target_family = "unix", not(feature = "ipp-client")))]
+    pub(crate) fn value(&self) -> i32 {
+        match self {
+            Encryption::IfRequested => 0,
+            Encryption::Never => 1,
+            Encryption::Required => 2,
+            Encryption::Always => 3,
+        }
+    }
+}
+
+/**
+ * PrintServer is a connection to a CUPS or IPP server other than the default one,
+ * so one process can manage the queues of several servers
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrintServer {
+    /**
+     * Host name or address of the server, or the path of a domain socket
+     */
+    pub host: String,
+
+    /**
+     * Port of the server
+     */
+    pub port: u16,
+
+    /**
+     * Encryption policy of the connection
+     */
+    pub encryption: Encryption,
+
+    /**
+     * Name sent as requesting-user-name, None for the user of the process
+     */
+    pub user_name: Option<String>,
+
+    /**
+     * Timeout of the connection and of each request
+     */
+    pub timeout: Duration,
+
+    /**
+     * Credentials sent when the server requires authentication (ex: admin operations),
+     * the ones of the default server are never sent to other servers
+     */
+    pub credentials: Option<Credentials>,
+}
+
+impl PrintServer {
+    /**
+     * Server on the IPP port 631, encrypted if requested, with a 30 seconds timeout and no credentials
+     */
+    pub fn new(host: &str) -> Self {
+        PrintServer {
+            host: host.to_string(),
+            port: 631,
+            encryption: Encryption::IfRequested,
+            user_name: None,
+            timeout: Duration::from_secs(30),
+            credentials: None,
+        }
+    }
+
+    /**
+     * Return all available printers of the server
+     */
+    pub fn get_printers(&self) -> Vec<Printer> {
+        self.with(crate::get_printers)
+    }
+
+    /**
+     * Return a printer of the server by the name, the instance or the system name
+     */
+    pub fn get_printer_by_name(&self, name: &str) -> Option<Printer> {
+        self.with(|| crate::get_printer_by_name(name))
+    }
+
+    /**
+     * Return the default printer of the server, if exists
+     */
+    pub fn get_default_printer(&self) -> Option<Printer> {
+        self.with(crate::get_default_printer)
+    }
+
+    /**
+     * Return a job of any printer of the server by id
+     */
+    pub fn get_job(&self, job_id: u64) -> Option<PrinterJob> {
+        self.with(|| crate::get_job(job_id))
+    }
+
+    /**
+     * Make a printer of the server the default of the scope
+     */
+    pub fn set_default_printer(
+        &self,
+        printer_name: &str,
+        scope: PrinterScope,
+    ) -> Result<(), PrintersError> {
+        self.with(|| crate::set_default_printer(printer_name, scope))
+    }
+
+    /**
+     * Run any function of the lib against this server (ex: the admin module).
+     * Printers and jobs returned inside keep using this server
+     */
+    pub fn with<T>(&self, f: impl FnOnce() -> T) -> T {
+        with_server(Some(self), f)
+    }
+}
+
+/**
+ * Restores the previous server of the thread, even if the function panics
+ */
+struct ServerGuard(Option<PrintServer>);
+
+impl Drop for ServerGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        CURRENT.with_borrow_mut(|current| *current = previous);
+    }
+}
+
+/**
+ * Run a function with the requests of the thread sent to the server, None keeps the current one
+ */
+pub(crate) fn with_server<T>(server: Option<&PrintServer>, f: impl FnOnce() -> T) -> T {
+    let Some(server) = server else {
+        return f();
+    };
+
+    let previous = CURRENT.with_borrow_mut(|current| current.replace(server.clone()));
+    let _guard = ServerGuard(previous);
+    f()
+}
+
+/**
+ * The server the requests of the thread are sent to, None for the default server
+ */
+pub(crate) fn current() -> Option<PrintServer> {
+    CURRENT.with_borrow(|current| current.clone())
+}
+
+/**
+ * Set the credentials sent when the default server requires authentication, None to send none
+ */
+pub fn set_default_credentials(credentials: Option<Credentials>) {
+    *DEFAULT_CREDENTIALS
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = credentials;
+}
+
+/**
+ * Credentials of the server the requests of the thread are sent to
+ */
+#[cfg(any(target_family = "unix", feature = "ipp-client"))]
+pub(crate) fn credentials() -> Option<Credentials> {
+    match current() {
+        Some(server) => server.credentials,
+        None => DEFAULT_CREDENTIALS
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone(),
+    }
+}
@@ -1,15 +1,27 @@
 use cups::dests::get_dests;
-use std::str;
+use std::{io::Read, str};
 
-use crate::common::{
-    base::{
-        errors::PrintersError,
-        job::{PrinterJob, PrinterJobOptions, PrinterJobState},
-        printer::{Printer, PrinterState},
+use crate::{
+    admin::PrinterConfig,
+    common::{
+        base::{
+            capabilities::{CAPABILITIES_ATTRIBUTES, PrinterCapabilities},
+            errors::PrintersError,
+            job::{
+                CancelJobsFilter, JobChanges, JobDocument, PrinterJob, PrinterJobOptions,
+                PrinterJobState,
+            },
+            printer::{
+                Printer, PrinterScope, PrinterState, PrinterStateReason, PrinterStateReasonKind,
+            },
+            supplies::{SUPPLIES_ATTRIBUTES, Supply},
+        },
+        converters::Converter,
+        traits::platform::{PlatformActions, PlatformPrinterGetters},
+        utils::file,
     },
-    converters::Converter,
-    traits::platform::{PlatformActions, PlatformPrinterGetters},
-    utils::file,
+    ipp::requests::printer_attributes_request,
+    lpoptions,
 };
 
 mod cups;
@@ -36,15 +48,7 @@ impl PlatformActions for crate::Platform {
         options: PrinterJobOptions,
     ) -> Result<u64, PrintersError> {
         let buffer = options.converter.convert(buffer)?;
-        let buffer = &buffer.as_slice();
-        let file_path = file::save_tmp_file(buffer)?;
-
-        cups::jobs::print_file(
-            printer_system_name,
-            file_path.to_str().unwrap_or_default(),
-            options.name,
-            options.raw_properties,
-        )
+        cups::jobs::print_stream(printer_system_name, &mut buffer.as_slice(), &options)
     }
 
     fn print_file(
@@ -57,20 +61,67 @@ impl PlatformActions for crate::Platform {
             return Self::print(printer_system_name, buffer.as_slice(), options);
         }
 
-        cups::jobs::print_file(
+        cups::jobs::print_file(printer_system_name, file_path, &options)
+    }
+
+    fn print_stream(
+        printer_system_name: &str,
+        reader: &mut dyn Read,
+        options: PrinterJobOptions,
+    ) -> Result<u64, PrintersError> {
+        // Converters work on the whole document
+        if options.converter != Converter::None {
+            let mut buffer = Vec::new();
+            reader
+                .read_to_end(&mut buffer)
+                .map_err(PrintersError::from)?;
+            return Self::print(printer_system_name, buffer.as_slice(), options);
+        }
+
+        cups::jobs::print_stream(printer_system_name, reader, &options)
+    }
+
+    fn create_job(
+        printer_system_name: &str,
+        options: &PrinterJobOptions,
+    ) -> Result<u64, PrintersError> {
+        cups::jobs::create_job(printer_system_name, options)
+    }
+
+    fn send_document(
+        printer_system_name: &str,
+        job_id: u64,
+        document_name: &str,
+        reader: &mut dyn Read,
+        document_format: Option<&str>,
+        last_document: bool,
+    ) -> Result<(), PrintersError> {
+        cups::jobs::send_document(
             printer_system_name,
-            file_path,
-            options.name,
-            options.raw_properties,
+            job_id,
+            document_name,
+            reader,
+            document_format,
+            last_document,
         )
     }
 
-    fn get_printer_jobs(printer_name: &str, active_only: bool) -> Vec<PrinterJob> {
-        cups::jobs::get_printer_jobs(printer_name, active_only)
-            .unwrap_or_default()
+    fn get_printer_jobs(
+        printer_name: &str,
+        active_only: bool,
+    ) -> Result<Vec<PrinterJob>, PrintersError> {
+        Ok(cups::jobs::get_printer_jobs(printer_name, active_only)?
             .iter()
             .map(|j| PrinterJob::from_platform_printer_job_getters(j))
-            .collect()
+            .collect())
+    }
+
+    fn get_printer_job(
+        printer_name: Option<&str>,
+        job_id: u64,
+    ) -> Result<Option<PrinterJob>, PrintersError> {
+        cups::jobs::get_job(printer_name, job_id)
+            .map(|j| j.map(|j| PrinterJob::from_platform_printer_job_getters(&j)))
     }
 
     fn get_default_printer() -> Option<Printer> {
@@ -88,15 +139,91 @@ impl PlatformActions for crate::Platform {
         let dests = get_dests()?;
         let dest = dests
             .iter()
-            .find(|d| d.get_name() == printer_name || d.get_system_name() == printer_name)
+            .find(|d| match d.get_instance() {
+                Some(instance) => format!("{}/{}", d.get_system_name(), instance) == printer_name,
+                None => d.get_name() == printer_name || d.get_system_name() == printer_name,
+            })
             .map(|d| Printer::from_platform_printer_getters(d));
 
         cups::dests::free(dests);
         dest
     }
 
-    fn parse_printer_state(platform_state: u64, state_reasons: &str) -> PrinterState {
-        if state_reasons.contains("offline-report") {
+    fn get_printer_capabilities(printer_name: &str) -> Result<PrinterCapabilities, PrintersError> {
+        let request = printer_attributes_request(
+            printer_name,
+            &cups::ipp::user_name(),
+            CAPABILITIES_ATTRIBUTES,
+        );
+        let response = cups::ipp::do_request(&request, "/")?;
+        PrinterCapabilities::from_ipp_response(&response)
+    }
+
+    fn get_printer_supplies(printer_name: &str) -> Result<Vec<Supply>, PrintersError> {
+        let request =
+            printer_attributes_request(printer_name, &cups::ipp::user_name(), SUPPLIES_ATTRIBUTES);
+        let response = cups::ipp::do_request(&request, "/")?;
+        Supply::from_ipp_response(&response)
+    }
+
+    fn set_printer_state(printer_name: &str, state: PrinterState) -> Result<(), PrintersError> {
+        match state {
+            PrinterState::PAUSED => cups::printers::pause_printer(printer_name),
+            PrinterState::READY => cups::printers::resume_printer(printer_name),
+            state => Err(PrintersError::error(format!(
+                "printer state cannot be set to {state:?}"
+            ))),
+        }
+    }
+
+    fn set_printer_accepting_jobs(
+        printer_name: &str,
+        accepting: bool,
+        reason: Option<&str>,
+    ) -> Result<(), PrintersError> {
+        if accepting {
+            cups::printers::accept_jobs(printer_name)
+        } else {
+            cups::printers::reject_jobs(printer_name, reason)
+        }
+    }
+
+    fn set_printer_state_message(printer_name: &str, message: &str) -> Result<(), PrintersError> {
+        cups::printers::set_state_message(printer_name, message)
+    }
+
+    fn set_default_printer(
+        printer_name: &str,
+        instance: Option<&str>,
+        scope: PrinterScope,
+    ) -> Result<(), PrintersError> {
+        match (scope, instance) {
+            (PrinterScope::User, instance) => {
+                lpoptions::set_default(PrinterScope::User, printer_name, instance)
+            }
+            (PrinterScope::System, None) => cups::printers::set_default(printer_name),
+            (PrinterScope::System, Some(_)) => Err(PrintersError::error(
+                "an instance can only be the default printer of the user scope",
+            )),
+        }
+    }
+
+    fn add_modify_printer(printer_name: &str, config: &PrinterConfig) -> Result<(), PrintersError> {
+        cups::printers::add_modify_printer(printer_name, config)
+    }
+
+    fn delete_printer(printer_name: &str) -> Result<(), PrintersError> {
+        cups::printers::delete_printer(printer_name)
+    }
+
+    fn parse_printer_state(
+        platform_state: u64,
+        state_reasons: &[PrinterStateReason],
+    ) -> PrinterState {
+        if state_reasons
+            .iter()
+            .any(|r| r.kind == PrinterStateReasonKind::Offline)
+        {
             return PrinterState::OFFLINE;
         }
 
@@ -111,9 +238,11 @@ impl PlatformActions for crate::Platform {
     fn parse_printer_job_state(platform_state: u64) -> PrinterJobState {
         match platform_state {
             3 => PrinterJobState::PENDING,
-            4 | 6 => PrinterJobState::PAUSED,
+            4 => PrinterJobState::PAUSED,
             5 => PrinterJobState::PROCESSING,
-            7 | 8 => PrinterJobState::CANCELLED,
+            6 => PrinterJobState::STOPPED,
+            7 => PrinterJobState::CANCELLED,
+            8 => PrinterJobState::ABORTED,
             9 => PrinterJobState::COMPLETED,
             _ => PrinterJobState::UNKNOWN,
         }
@@ -124,18 +253,50 @@ impl PlatformActions for crate::Platform {
         job_id: u64,
         state: PrinterJobState,
     ) -> Result<(), PrintersError> {
-        let result = match state {
+        match state {
             PrinterJobState::PENDING => cups::jobs::restart_job(printer_name, job_id as i32),
             PrinterJobState::PROCESSING => cups::jobs::release_job(printer_name, job_id as i32),
             PrinterJobState::PAUSED => cups::jobs::hold_job(printer_name, job_id as i32),
             PrinterJobState::CANCELLED => cups::jobs::cancel_job(printer_name, job_id as i32),
-            _ => false,
-        };
+            state => Err(PrintersError::job_error(format!(
+                "cannot change a job to {state:?}"
+            ))),
+        }
+    }
 
-        if result {
-            Ok(())
-        } else {
-            Err(PrintersError::print_error("cups method failed"))
+    fn get_job_document(
+        printer_name: &str,
+        job_id: u64,
+        document_number: u32,
+    ) -> Result<JobDocument, PrintersError> {
+        cups::jobs::get_job_document(printer_name, job_id as i32, document_number)
+    }
+
+    fn set_job_attributes(
+        printer_name: &str,
+        job_id: u64,
+        changes: &JobChanges,
+    ) -> Result<(), PrintersError> {
+        cups::jobs::set_job_attributes(printer_name, job_id as i32, changes)
+    }
+
+    fn cancel_jobs(
+        printer_name: &str,
+        filter: &CancelJobsFilter,
+        purge: bool,
+    ) -> Result<Vec<u64>, PrintersError> {
+        let job_ids = filter.matching_jobs(printer_name, &cups::ipp::user_name(), purge)?;
+        if !job_ids.is_empty() {
+            cups::jobs::cancel_jobs(printer_name, filter, purge, &job_ids)?;
         }
+        Ok(job_ids)
+    }
+
+    fn move_jobs(
+        printer_name: &str,
+        job_id: Option<u64>,
+        target_name: &str,
+    ) -> Result<(), PrintersError> {
+        cups::jobs::move_job(printer_name, job_id.map(|id| id as i32), target_name)
     }
 }
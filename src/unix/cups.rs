@@ -1,2 +1,5 @@
 pub mod dests;
+pub mod http;
+pub mod ipp;
 pub mod jobs;
+pub mod printers;
@@ -1,12 +1,13 @@
 use crate::{
-    common::traits::platform::PlatformPrinterGetters, unix::utils::strings::c_char_to_string,
+    common::traits::platform::PlatformPrinterGetters,
+    unix::{cups::http, utils::strings::c_char_to_string},
 };
-use libc::{c_char, c_int};
+use libc::{c_char, c_int, c_void};
 use std::{ffi::CString, ptr, slice};
 
 #[link(name = "cups")]
 unsafe extern "C" {
-    fn cupsGetDests(dests: *mut *mut CupsDestT) -> c_int;
+    fn cupsGetDests2(http: *mut c_void, dests: *mut *mut CupsDestT) -> c_int;
     fn cupsFreeDests(num_dests: c_int, dests: *const CupsDestT);
     fn cupsGetOption(
         name: *const c_char,
@@ -123,16 +124,24 @@ impl PlatformPrinterGetters for CupsDestT {
     fn get_data_type(&self) -> String {
         self.get_option("media")
     }
+
+    fn get_instance(&self) -> Option<String> {
+        if self.instance.is_null() {
+            None
+        } else {
+            Some(c_char_to_string(self.instance))
+        }
+    }
 }
 
 /**
  * Returns a vector of CupsDestT (cups_dest_s) struct with all available destinations
- * Using cupsGetDests
+ * Using cupsGetDests2 on the current server
  */
 pub fn get_dests() -> Option<&'static [CupsDestT]> {
     unsafe {
         let mut dests_ptr: *mut CupsDestT = ptr::null_mut();
-        let dests_count: i32 = cupsGetDests(&mut dests_ptr);
+        let dests_count: i32 = cupsGetDests2(http::connection().ok()?, &mut dests_ptr);
         if dests_count > 0 {
             Some(slice::from_raw_parts(dests_ptr, dests_count as usize))
         } else {
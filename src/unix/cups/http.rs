@@ -0,0 +1,290 @@
+use libc::{c_char, c_int};
+use std::{
+    cell::{Cell, RefCell},
+    ffi::CString,
+    io::{self, ErrorKind},
+    os::raw::c_void,
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use crate::{
+    common::base::errors::PrintersError,
+    ipp::IppStatusCode,
+    server::{self, CredentialsRequest, PrintServer},
+    unix::utils::strings::{c_char_to_string, str_to_cstring},
+};
+
+const AF_UNSPEC: c_int = 0;
+/**
+ * Connect timeout of the submissions to the default server, as libcups uses
+ */
+const DEFAULT_TIMEOUT_MS: c_int = 30_000;
+
+type PasswordCb = unsafe extern "C" fn(
+    *const c_char,
+    *mut c_void,
+    *const c_char,
+    *const c_char,
+    *mut c_void,
+) -> *const c_char;
+
+#[link(name = "cups")]
+unsafe extern "C" {
+    unsafe fn httpConnect2(
+        host: *const c_char,
+        port: c_int,
+        addrlist: *mut c_void,
+        family: c_int,
+        encryption: c_int,
+        blocking: c_int,
+        msec: c_int,
+        cancel: *mut c_int,
+    ) -> *mut c_void;
+    unsafe fn httpClose(http: *mut c_void);
+    unsafe fn httpReconnect2(http: *mut c_void, msec: c_int, cancel: *mut c_int) -> c_int;
+    unsafe fn cupsServer() -> *const c_char;
+    unsafe fn ippPort() -> c_int;
+    unsafe fn cupsEncryption() -> c_int;
+    unsafe fn httpSetTimeout(
+        http: *mut c_void,
+        timeout: f64,
+        cb: *const c_void,
+        user_data: *mut c_void,
+    );
+    unsafe fn cupsSetUser(user: *const c_char);
+    unsafe fn cupsSetPasswordCB2(cb: Option<PasswordCb>, user_data: *mut c_void);
+    unsafe fn httpGetHostname(http: *mut c_void, s: *mut c_char, slen: c_int) -> *const c_char;
+}
+
+/**
+ * Connection of a thread to a server other than the default one
+ */
+struct Connection {
+    server: PrintServer,
+    http: *mut c_void,
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        unsafe { httpClose(self.http) };
+    }
+}
+
+/**
+ * Connection to the default server, host is the cupsServer it was opened to
+ */
+struct DefaultConnection {
+    host: String,
+    http: *mut c_void,
+}
+
+impl Drop for DefaultConnection {
+    fn drop(&mut self) {
+        unsafe { httpClose(self.http) };
+    }
+}
+
+thread_local! {
+    /**
+     * libcups connections are not shared between threads, as its default connection
+     */
+    static CONNECTION: RefCell<Option<Connection>> = const { RefCell::new(None) };
+
+    /**
+     * Connection of a thread to the default server used by the submissions,
+     * as the one of libcups cannot be checked before a request
+     */
+    static DEFAULT_CONNECTION: RefCell<Option<DefaultConnection>> = const { RefCell::new(None) };
+
+    /**
+     * The user of libcups was changed for a server
+     */
+    static USER_CHANGED: Cell<bool> = const { Cell::new(false) };
+
+    /**
+     * The password callback is installed, libcups keeps one per thread
+     */
+    static PASSWORD_CB: Cell<bool> = const { Cell::new(false) };
+
+    /**
+     * Times the credentials were asked since the last call of connection
+     */
+    static ATTEMPTS: Cell<u32> = const { Cell::new(0) };
+
+    /**
+     * Last password given to libcups, which keeps the pointer until the next callback
+     */
+    static PASSWORD: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/**
+ * Return the http_t of the current server, null (CUPS_HTTP_DEFAULT) for the default server.
+ * The requesting user name and the credentials of libcups follow the server too
+ */
+pub fn connection() -> Result<*mut c_void, PrintersError> {
+    if !PASSWORD_CB.replace(true) {
+        unsafe { cupsSetPasswordCB2(Some(password_cb), ptr::null_mut()) };
+    }
+    ATTEMPTS.set(0);
+
+    let Some(server) = server::current() else {
+        set_user(None);
+        return Ok(ptr::null_mut());
+    };
+    set_user(server.user_name.as_deref());
+
+    CONNECTION.with_borrow_mut(|connection| {
+        if let Some(connection) = connection
+            && connection.server == server
+        {
+            return Ok(connection.http);
+        }
+
+        *connection = None;
+        let host = str_to_cstring(&server.host);
+        let http = unsafe {
+            httpConnect2(
+                host.as_ptr(),
+                server.port as c_int,
+                ptr::null_mut(),
+                AF_UNSPEC,
+                server.encryption.value(),
+                1,
+                server.timeout.as_millis().min(c_int::MAX as u128) as c_int,
+                ptr::null_mut(),
+            )
+        };
+
+        if http.is_null() {
+            return Err(not_connected(&server.host, server.port as c_int));
+        }
+
+        unsafe {
+            httpSetTimeout(
+                http,
+                server.timeout.as_secs_f64(),
+                ptr::null(),
+                ptr::null_mut(),
+            )
+        };
+        *connection = Some(Connection { server, http });
+        Ok(http)
+    })
+}
+
+/**
+ * Return a connection to the current server for a job submission, opened again just before.
+ * A connection lost meanwhile (ex: cupsd restarted) fails here, before anything is sent,
+ * while libcups would only notice it once the job was possibly received
+ */
+pub fn submission_connection() -> Result<*mut c_void, PrintersError> {
+    let http = connection()?;
+    if let Some(server) = server::current() {
+        let timeout = server.timeout.as_millis().min(c_int::MAX as u128) as c_int;
+        if unsafe { httpReconnect2(http, timeout, ptr::null_mut()) } != 0 {
+            CONNECTION.set(None);
+            return Err(not_connected(&server.host, server.port as c_int));
+        }
+        return Ok(http);
+    }
+
+    let host = c_char_to_string(unsafe { cupsServer() });
+    let port = unsafe { ippPort() };
+
+    DEFAULT_CONNECTION.with_borrow_mut(|connection| {
+        if let Some(connection) = connection
+            && connection.host == host
+            && unsafe { httpReconnect2(connection.http, DEFAULT_TIMEOUT_MS, ptr::null_mut()) } == 0
+        {
+            return Ok(connection.http);
+        }
+
+        *connection = None;
+        let http = unsafe {
+            let host = str_to_cstring(&host);
+            httpConnect2(
+                host.as_ptr(),
+                port,
+                ptr::null_mut(),
+                AF_UNSPEC,
+                cupsEncryption(),
+                1,
+                DEFAULT_TIMEOUT_MS,
+                ptr::null_mut(),
+            )
+        };
+        if http.is_null() {
+            return Err(not_connected(&host, port));
+        }
+
+        *connection = Some(DefaultConnection { host, http });
+        Ok(http)
+    })
+}
+
+/**
+ * Error of a server that cannot be connected to, reported as server-error-service-unavailable
+ * as libcups does with a lost connection. The source tells that no request was sent
+ */
+fn not_connected(host: &str, port: c_int) -> PrintersError {
+    let message = format!("cannot connect to {host}:{port}");
+    PrintersError::error(&message)
+        .with_status(IppStatusCode::ServerErrorServiceUnavailable)
+        .with_source(io::Error::new(ErrorKind::NotConnected, message))
+}
+
+/**
+ * Set the user libcups sends requests as, None restores the user of the process
+ */
+fn set_user(user_name: Option<&str>) {
+    match user_name {
+        Some(user_name) => {
+            let user_name = str_to_cstring(user_name);
+            unsafe { cupsSetUser(user_name.as_ptr()) };
+            USER_CHANGED.set(true);
+        }
+        None if USER_CHANGED.get() => {
+            unsafe { cupsSetUser(ptr::null()) };
+            USER_CHANGED.set(false);
+        }
+        None => {}
+    }
+}
+
+/**
+ * Password callback of libcups, answering with the credentials of the current server.
+ * The default one of libcups prompts on the terminal
+ */
+unsafe extern "C" fn password_cb(
+    _prompt: *const c_char,
+    http: *mut c_void,
+    _method: *const c_char,
+    resource: *const c_char,
+    _user_data: *mut c_void,
+) -> *const c_char {
+    let Some(credentials) = server::credentials() else {
+        return ptr::null();
+    };
+
+    let attempt = ATTEMPTS.get() + 1;
+    ATTEMPTS.set(attempt);
+
+    let host = c_char_to_string(unsafe { httpGetHostname(http, ptr::null_mut(), 0) });
+    let resource = c_char_to_string(resource);
+    let request = CredentialsRequest {
+        host: &host,
+        resource: &resource,
+        attempt,
+    };
+
+    // A panic must not unwind into libcups
+    let resolved = panic::catch_unwind(AssertUnwindSafe(|| credentials.resolve(&request)));
+    let Ok(Some((user_name, password))) = resolved else {
+        return ptr::null();
+    };
+
+    // libcups sends the password with the name of cupsUser
+    set_user(Some(&user_name));
+    PASSWORD.with_borrow_mut(|current| current.insert(str_to_cstring(&password)).as_ptr())
+}
@@ -0,0 +1,236 @@
+use libc::{c_char, c_int, size_t, ssize_t};
+use std::{
+    os::{fd::AsRawFd, raw::c_void},
+    ptr,
+};
+
+use crate::{
+    common::base::errors::PrintersError,
+    ipp::{IppMessage, IppStatusCode},
+    server,
+    unix::{
+        cups::http,
+        utils::strings::{c_char_to_string, str_to_cstring},
+    },
+};
+
+const IPP_STATUS_ERROR_NOT_AUTHENTICATED: c_int = 0x0402;
+const IPP_STATUS_ERROR_CUPS_AUTHENTICATION_CANCELED: c_int = 0x1000;
+const CUPS_IPP_STATE_IDLE: c_int = 0;
+const CUPS_IPP_STATE_DATA: c_int = 3;
+
+type IppIoCb = unsafe extern "C" fn(*mut c_void, *mut u8, size_t) -> ssize_t;
+
+#[link(name = "cups")]
+unsafe extern "C" {
+    unsafe fn cupsDoRequest(
+        http: *mut c_void,
+        request: *mut c_void,
+        resource: *const c_char,
+    ) -> *mut c_void;
+
+    unsafe fn cupsDoIORequest(
+        http: *mut c_void,
+        request: *mut c_void,
+        resource: *const c_char,
+        infile: c_int,
+        outfile: c_int,
+    ) -> *mut c_void;
+
+    unsafe fn ippNew() -> *mut c_void;
+    unsafe fn ippDelete(ipp: *mut c_void);
+    unsafe fn ippSetState(ipp: *mut c_void, state: c_int) -> c_int;
+    unsafe fn ippReadIO(
+        src: *mut c_void,
+        cb: IppIoCb,
+        blocking: c_int,
+        parent: *mut c_void,
+        ipp: *mut c_void,
+    ) -> c_int;
+    unsafe fn ippWriteIO(
+        dst: *mut c_void,
+        cb: IppIoCb,
+        blocking: c_int,
+        parent: *mut c_void,
+        ipp: *mut c_void,
+    ) -> c_int;
+    unsafe fn cupsLastError() -> c_int;
+    unsafe fn cupsLastErrorString() -> *const c_char;
+    unsafe fn cupsUser() -> *const c_char;
+}
+
+/**
+ * Read callback feeding an encoded message to ippReadIO
+ */
+unsafe extern "C" fn read_cb(context: *mut c_void, buffer: *mut u8, bytes: size_t) -> ssize_t {
+    unsafe {
+        let source = &mut *(context as *mut &[u8]);
+        let count = bytes.min(source.len());
+        ptr::copy_nonoverlapping(source.as_ptr(), buffer, count);
+        *source = &source[count..];
+        count as ssize_t
+    }
+}
+
+/**
+ * Write callback collecting the message written by ippWriteIO
+ */
+unsafe extern "C" fn write_cb(context: *mut c_void, buffer: *mut u8, bytes: size_t) -> ssize_t {
+    unsafe {
+        let destination = &mut *(context as *mut Vec<u8>);
+        destination.extend_from_slice(std::slice::from_raw_parts(buffer, bytes));
+        bytes as ssize_t
+    }
+}
+
+/**
+ * Convert a message to a cups ipp_t, the caller owns the returned pointer
+ */
+fn to_ipp_t(message: &IppMessage) -> Result<*mut c_void, PrintersError> {
+    let mut buffer: Vec<u8> = Vec::new();
+    message.write_to(&mut buffer)?;
+
+    unsafe {
+        let ipp = ippNew();
+        if ipp.is_null() {
+            return Err(PrintersError::error("ippNew failed"));
+        }
+
+        let mut source = buffer.as_slice();
+        let state = ippReadIO(
+            &mut source as *mut &[u8] as *mut c_void,
+            read_cb,
+            1,
+            ptr::null_mut(),
+            ipp,
+        );
+
+        if state != CUPS_IPP_STATE_DATA {
+            ippDelete(ipp);
+            return Err(PrintersError::error("ippReadIO failed"));
+        }
+
+        Ok(ipp)
+    }
+}
+
+/**
+ * Convert a cups ipp_t to a message
+ */
+fn from_ipp_t(ipp: *mut c_void) -> Result<IppMessage, PrintersError> {
+    let mut buffer: Vec<u8> = Vec::new();
+
+    unsafe {
+        ippSetState(ipp, CUPS_IPP_STATE_IDLE);
+        let state = ippWriteIO(
+            &mut buffer as *mut Vec<u8> as *mut c_void,
+            write_cb,
+            1,
+            ptr::null_mut(),
+            ipp,
+        );
+
+        if state != CUPS_IPP_STATE_DATA {
+            return Err(PrintersError::error("ippWriteIO failed"));
+        }
+    }
+
+    IppMessage::decode(&buffer)
+}
+
+/**
+ * Error of the last failed libcups call with the status and the message of cupsLastError,
+ * AuthenticationRequired when the server asked for credentials that were missing or rejected
+ */
+pub fn last_error_as(function: &str, error: fn(String) -> PrintersError) -> PrintersError {
+    let (code, message) = unsafe { (cupsLastError(), c_char_to_string(cupsLastErrorString())) };
+    let status = IppStatusCode::from(code as u16);
+    let message = format!("{function} failed with {}: {message}", status.keyword());
+
+    let error = match code {
+        IPP_STATUS_ERROR_NOT_AUTHENTICATED | IPP_STATUS_ERROR_CUPS_AUTHENTICATION_CANCELED => {
+            PrintersError::authentication_error(message)
+        }
+        _ => error(message),
+    };
+    error
+        .with_code(code)
+        .with_status(status)
+        .with_operation(function)
+}
+
+/**
+ * Fail with AuthenticationRequired when the server answered client-error-not-authenticated
+ */
+fn check_authentication(
+    request: &IppMessage,
+    response: IppMessage,
+) -> Result<IppMessage, PrintersError> {
+    if response.status() == IppStatusCode::ClientErrorNotAuthenticated {
+        return Err(PrintersError::from_response(
+            request.operation(),
+            &response,
+            PrintersError::authentication_error,
+        ));
+    }
+    Ok(response)
+}
+
+/**
+ * The user name libcups sends requests as
+ */
+pub fn user_name() -> String {
+    server::current()
+        .and_then(|s| s.user_name)
+        .unwrap_or_else(|| c_char_to_string(unsafe { cupsUser() }))
+}
+
+/**
+ * Send a request to cups with cupsDoRequest and return the response
+ */
+pub fn do_request(request: &IppMessage, resource: &str) -> Result<IppMessage, PrintersError> {
+    let resource = str_to_cstring(resource);
+
+    let http = http::connection()?;
+
+    unsafe {
+        // cupsDoRequest frees the request
+        let ipp = to_ipp_t(request)?;
+        let response = cupsDoRequest(http, ipp, resource.as_ptr());
+
+        if response.is_null() {
+            return Err(last_error_as("cupsDoRequest", PrintersError::error));
+        }
+
+        let message = from_ipp_t(response);
+        ippDelete(response);
+        check_authentication(request, message?)
+    }
+}
+
+/**
+ * Send a request to cups with cupsDoIORequest, the data of the response is written to the output
+ */
+pub fn do_io_request(
+    request: &IppMessage,
+    resource: &str,
+    output: &impl AsRawFd,
+) -> Result<IppMessage, PrintersError> {
+    let resource = str_to_cstring(resource);
+
+    let http = http::connection()?;
+
+    unsafe {
+        // cupsDoIORequest frees the request
+        let ipp = to_ipp_t(request)?;
+        let response = cupsDoIORequest(http, ipp, resource.as_ptr(), -1, output.as_raw_fd());
+
+        if response.is_null() {
+            return Err(last_error_as("cupsDoIORequest", PrintersError::error));
+        }
+
+        let message = from_ipp_t(response);
+        ippDelete(response);
+        check_authentication(request, message?)
+    }
+}
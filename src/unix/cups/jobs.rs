@@ -1,30 +1,35 @@
-use libc::{c_char, c_int, time_t};
-use std::{os::raw::c_void, ptr, slice, time::SystemTime};
+use libc::{c_char, c_int, size_t};
+use std::{
+    env,
+    ffi::{CString, c_void},
+    fs::{self, OpenOptions},
+    io::{Read, Seek, SeekFrom},
+};
 
 use crate::{
-    common::{
-        base::{errors::PrintersError, options::OptionsCollection},
-        traits::platform::PlatformPrinterJobGetters,
+    common::base::{
+        errors::PrintersError,
+        job::{CancelJobsFilter, JobChanges, JobDocument, PrinterJobOptions},
+        options::OptionsCollection,
+    },
+    ipp::{
+        IppAttribute, IppOperation, IppStatusCode, IppValue,
+        job::{IppJob, from_response, get_job_request, get_jobs_request, jobs_from_response},
+        requests::{
+            cancel_jobs_request, get_document_request, job_request, move_job_request,
+            next_request_id, set_job_attributes_request,
+        },
     },
-    unix::utils::{
-        date::time_t_to_system_time,
-        strings::{c_char_to_string, str_to_cstring},
+    unix::{
+        cups::{http, ipp},
+        utils::strings::str_to_cstring,
     },
 };
 
-const CUPS_IPP_OK: c_int = 0x0000;
-const CUPS_IPP_TAG_URI: c_int = 0x45;
-const CUPS_IPP_TAG_INTEGER: c_int = 0x21;
-const CUPS_IPP_TAG_OPERATION: c_int = 0x01;
-
-const CUPS_IPP_OP_HOLD_JOB: c_int = 12;
-const CUPS_IPP_OP_CANCEL_JOB: c_int = 8;
-const CUPS_IPP_OP_RELEASE_JOB: c_int = 13;
-const CUPS_IPP_OP_RESTART_JOB: c_int = 14;
-
 #[link(name = "cups")]
 unsafe extern "C" {
-    unsafe fn cupsPrintFile(
+    unsafe fn cupsPrintFile2(
+        http: *mut c_void,
         printer_name: *const c_char,
         filename: *const c_char,
         title: *const c_char,
@@ -32,41 +37,37 @@ unsafe extern "C" {
         options: *const CupsOptionT,
     ) -> c_int;
 
-    unsafe fn cupsGetJobs(
-        jobs: *mut *mut CupsJobsS,
+    unsafe fn cupsCreateJob(
+        http: *mut c_void,
         name: *const c_char,
-        myjobs: c_int,
-        whichjobs: c_int,
+        title: *const c_char,
+        num_options: c_int,
+        options: *const CupsOptionT,
     ) -> c_int;
 
-    unsafe fn cupsDoRequest(
+    unsafe fn cupsStartDocument(
         http: *mut c_void,
-        request: *mut c_void,
-        resource: *const c_char,
-    ) -> *mut c_void;
-
-    unsafe fn ippAddString(
-        req: *mut c_void,
-        group: c_int,
-        value_tag: c_int,
-        name: *const c_char,
-        lang: *const c_char,
-        value: *const c_char,
-    );
-
-    unsafe fn ippAddInteger(
-        req: *mut c_void,
-        group: c_int,
-        value_tag: c_int,
         name: *const c_char,
-        value: c_int,
-    );
+        job_id: c_int,
+        docname: *const c_char,
+        format: *const c_char,
+        last_document: c_int,
+    ) -> c_int;
 
-    unsafe fn ippDelete(req: *mut c_void);
-    unsafe fn ippNewRequest(op: c_int) -> *mut c_void;
-    unsafe fn cupsLastError() -> c_int;
+    unsafe fn cupsWriteRequestData(
+        http: *mut c_void,
+        buffer: *const c_char,
+        length: size_t,
+    ) -> c_int;
+
+    unsafe fn cupsFinishDocument(http: *mut c_void, name: *const c_char) -> c_int;
 }
 
+const HTTP_STATUS_CONTINUE: c_int = 100;
+const IPP_STATUS_OK_EVENTS_COMPLETE: c_int = 0x0007;
+const CUPS_FORMAT_AUTO: &str = "application/octet-stream";
+const STREAM_BUFFER_SIZE: usize = 64 * 1024;
+
 #[derive(Debug)]
 #[repr(C)]
 struct CupsOptionT {
@@ -74,72 +75,25 @@ struct CupsOptionT {
     value: *const c_char,
 }
 
-#[derive(Debug)]
-#[repr(C)]
-pub struct CupsJobsS {
-    id: c_int,
-    dest: *const c_char,
-    title: *const c_char,
-    user: *const c_char,
-    format: *const c_char,
-    state: c_int,
-    size: c_int,
-    priority: c_int,
-    completed_time: time_t,
-    creation_time: time_t,
-    processing_time: time_t,
-}
-
-impl PlatformPrinterJobGetters for CupsJobsS {
-    fn get_id(&self) -> u64 {
-        self.id as u64
-    }
-
-    fn get_name(&self) -> String {
-        c_char_to_string(self.title)
-    }
-
-    fn get_state(&self) -> u64 {
-        self.state as u64
-    }
-
-    fn get_printer(&self) -> String {
-        c_char_to_string(self.dest)
-    }
-
-    fn get_media_type(&self) -> String {
-        c_char_to_string(self.format)
-    }
-
-    fn get_created_at(&self) -> SystemTime {
-        time_t_to_system_time(self.creation_time).unwrap_or(SystemTime::UNIX_EPOCH)
-    }
-
-    fn get_processed_at(&self) -> Option<SystemTime> {
-        time_t_to_system_time(self.processing_time)
-    }
-
-    fn get_completed_at(&self) -> Option<SystemTime> {
-        time_t_to_system_time(self.completed_time)
-    }
+/**
+ * Return the printer jobs using Get-Jobs
+ */
+pub fn get_printer_jobs(
+    printer_name: &str,
+    active_only: bool,
+) -> Result<Vec<IppJob>, PrintersError> {
+    let request = get_jobs_request(printer_name, active_only, &ipp::user_name());
+    let response = ipp::do_request(&request, "/")?;
+    jobs_from_response(&response)
 }
 
 /**
- * Return the printer jobs
+ * Return a job using Get-Job-Attributes
  */
-pub fn get_printer_jobs(printer_name: &str, active_only: bool) -> Option<&'static [CupsJobsS]> {
-    let mut jobs_ptr: *mut CupsJobsS = std::ptr::null_mut();
-    let whichjobs = if active_only { 0 } else { -1 };
-    let name = str_to_cstring(printer_name);
-
-    unsafe {
-        let jobs_count = cupsGetJobs(&mut jobs_ptr, name.as_ptr(), 0, whichjobs);
-        if jobs_count > 0 {
-            Some(slice::from_raw_parts(jobs_ptr, jobs_count as usize))
-        } else {
-            None
-        }
-    }
+pub fn get_job(printer_name: Option<&str>, job_id: u64) -> Result<Option<IppJob>, PrintersError> {
+    let request = get_job_request(printer_name, job_id, &ipp::user_name());
+    let response = ipp::do_request(&request, "/")?;
+    from_response(&response)
 }
 
 /**
@@ -148,34 +102,31 @@ pub fn get_printer_jobs(printer_name: &str, active_only: bool) -> Option<&'stati
 pub fn print_file(
     printer_name: &str,
     file_path: &str,
-    job_name: Option<&str>,
-    raw_options: &[(&str, &str)],
+    options: &PrinterJobOptions,
 ) -> Result<u64, PrintersError> {
+    let http = http::submission_connection()?;
+    let cups_options = cups_options(options);
+    let cups_options = to_options_collection(&cups_options);
+
     unsafe {
         let printer = &str_to_cstring(printer_name);
         let filename = str_to_cstring(file_path);
-        let title = str_to_cstring(job_name.unwrap_or(file_path));
-
-        let options = OptionsCollection::new(raw_options, |(key, value)| {
-            let key = str_to_cstring(key);
-            let value = str_to_cstring(value);
-            let option = CupsOptionT {
-                name: key.as_ptr(),
-                value: value.as_ptr(),
-            };
-            ((key, value), option)
-        });
+        let title = str_to_cstring(options.name.unwrap_or(file_path));
 
-        let result = cupsPrintFile(
+        let result = cupsPrintFile2(
+            http,
             printer.as_ptr(),
             filename.as_ptr(),
             title.as_ptr(),
-            options.size as c_int,
-            options.as_ptr(),
+            cups_options.size as c_int,
+            cups_options.as_ptr(),
         );
 
         if result == 0 {
-            Err(PrintersError::print_error("cupsPrintFile"))
+            Err(ipp::last_error_as(
+                "cupsPrintFile2",
+                PrintersError::print_error,
+            ))
         } else {
             Ok(result as u64)
         }
@@ -183,70 +134,335 @@ pub fn print_file(
 }
 
 /**
- * Send cancel job request to cups
+ * Stream a document to the printer with cupsCreateJob and cupsWriteRequestData,
+ * the data is sent as it is read and never written to disk
+ */
+pub fn print_stream(
+    printer_name: &str,
+    reader: &mut dyn Read,
+    options: &PrinterJobOptions,
+) -> Result<u64, PrintersError> {
+    let format = options
+        .raw_properties
+        .iter()
+        .find(|(key, _)| *key == "document-format")
+        .map(|(_, value)| *value);
+
+    let job_id = create_job(printer_name, options)?;
+    let title = options.name.unwrap_or("stream");
+
+    if let Err(error) = send_document(printer_name, job_id, title, reader, format, true) {
+        // Don't leave an incomplete job held in the queue
+        let _ = cancel_job(printer_name, job_id as i32);
+        return Err(error);
+    }
+
+    Ok(job_id)
+}
+
+/**
+ * Create a job without documents with cupsCreateJob
+ */
+pub fn create_job(printer_name: &str, options: &PrinterJobOptions) -> Result<u64, PrintersError> {
+    let http = http::submission_connection()?;
+    let cups_options = cups_options(options);
+    let cups_options = to_options_collection(&cups_options);
+
+    let printer = str_to_cstring(printer_name);
+    let title = str_to_cstring(options.name.unwrap_or("printers"));
+
+    let job_id = unsafe {
+        cupsCreateJob(
+            http,
+            printer.as_ptr(),
+            title.as_ptr(),
+            cups_options.size as c_int,
+            cups_options.as_ptr(),
+        )
+    };
+
+    if job_id == 0 {
+        return Err(ipp::last_error_as(
+            "cupsCreateJob",
+            PrintersError::print_error,
+        ));
+    }
+
+    Ok(job_id as u64)
+}
+
+/**
+ * Send a document of a created job with cupsStartDocument and cupsWriteRequestData
+ */
+pub fn send_document(
+    printer_name: &str,
+    job_id: u64,
+    document_name: &str,
+    reader: &mut dyn Read,
+    format: Option<&str>,
+    last_document: bool,
+) -> Result<(), PrintersError> {
+    let http = http::connection()?;
+    let printer = str_to_cstring(printer_name);
+    let document_name = str_to_cstring(document_name);
+    let format = str_to_cstring(format.unwrap_or(CUPS_FORMAT_AUTO));
+
+    unsafe {
+        let status = cupsStartDocument(
+            http,
+            printer.as_ptr(),
+            job_id as c_int,
+            document_name.as_ptr(),
+            format.as_ptr(),
+            last_document as c_int,
+        );
+        if status != HTTP_STATUS_CONTINUE {
+            return Err(ipp::last_error_as(
+                "cupsStartDocument",
+                PrintersError::print_error,
+            ));
+        }
+
+        let mut buffer = vec![0; STREAM_BUFFER_SIZE];
+        let mut write_error = None;
+        loop {
+            let count = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(count) => count,
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    write_error = Some(PrintersError::file_error(error));
+                    break;
+                }
+            };
+
+            let status =
+                cupsWriteRequestData(http, buffer.as_ptr() as *const c_char, count as size_t);
+            if status != HTTP_STATUS_CONTINUE {
+                write_error = Some(ipp::last_error_as(
+                    "cupsWriteRequestData",
+                    PrintersError::print_error,
+                ));
+                break;
+            }
+        }
+
+        // The request must always be finished to release the connection
+        let status = cupsFinishDocument(http, printer.as_ptr());
+        if let Some(error) = write_error {
+            return Err(error);
+        }
+
+        if status > IPP_STATUS_OK_EVENTS_COMPLETE {
+            return Err(ipp::last_error_as(
+                "cupsFinishDocument",
+                PrintersError::print_error,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * Return the typed options as cups options followed by the raw properties
+ */
+fn cups_options(options: &PrinterJobOptions) -> Vec<(String, String)> {
+    options
+        .operation_attributes()
+        .iter()
+        .chain(options.job_attributes().iter())
+        .map(to_option)
+        .chain(
+            options
+                .raw_properties
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string())),
+        )
+        .collect()
+}
+
+fn to_options_collection(options: &[(String, String)]) -> OptionsCollection<CString, CupsOptionT> {
+    let options: Vec<(&str, &str)> = options
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect();
+
+    OptionsCollection::new(&options, |(key, value)| {
+        let key = str_to_cstring(key);
+        let value = str_to_cstring(value);
+        let option = CupsOptionT {
+            name: key.as_ptr(),
+            value: value.as_ptr(),
+        };
+        ((key, value), option)
+    })
+}
+
+/**
+ * Convert an attribute to a cups option, cupsEncodeOptions restores the value syntax
  */
-pub fn hold_job(printer_name: &str, job_id: i32) -> bool {
-    do_request(printer_name, job_id, CUPS_IPP_OP_HOLD_JOB)
+fn to_option(attribute: &IppAttribute) -> (String, String) {
+    let value = attribute
+        .values
+        .iter()
+        .map(|value| match value {
+            IppValue::Integer(v) | IppValue::Enum(v) => v.to_string(),
+            IppValue::Boolean(v) => v.to_string(),
+            IppValue::RangeOfInteger { lower, upper } => format!("{lower}-{upper}"),
+            IppValue::OctetString(v) => String::from_utf8_lossy(v).to_string(),
+            v => v.as_str().unwrap_or_default().to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join(",");
+
+    (attribute.name.clone(), value)
+}
+
+/**
+ * Send hold job request to cups
+ */
+pub fn hold_job(printer_name: &str, job_id: i32) -> Result<(), PrintersError> {
+    do_request(printer_name, job_id, IppOperation::HoldJob)
 }
 
 /**
  * Send release job request to cups
  */
-pub fn release_job(printer_name: &str, job_id: i32) -> bool {
-    do_request(printer_name, job_id, CUPS_IPP_OP_RELEASE_JOB)
+pub fn release_job(printer_name: &str, job_id: i32) -> Result<(), PrintersError> {
+    do_request(printer_name, job_id, IppOperation::ReleaseJob)
 }
 
 /**
  * Send restart job request to cups
  */
-pub fn restart_job(printer_name: &str, job_id: i32) -> bool {
-    do_request(printer_name, job_id, CUPS_IPP_OP_RESTART_JOB)
+pub fn restart_job(printer_name: &str, job_id: i32) -> Result<(), PrintersError> {
+    do_request(printer_name, job_id, IppOperation::RestartJob)
 }
 
 /**
  * Send cancel job request to cups
  */
-pub fn cancel_job(printer_name: &str, job_id: i32) -> bool {
-    do_request(printer_name, job_id, CUPS_IPP_OP_CANCEL_JOB)
+pub fn cancel_job(printer_name: &str, job_id: i32) -> Result<(), PrintersError> {
+    do_request(printer_name, job_id, IppOperation::CancelJob)
 }
 
 /**
- * Send request op to cups
+ * Return a document of a job (CUPS-Get-Document), the server must keep the job files
  */
-fn do_request(printer_name: &str, job_id: i32, op: i32) -> bool {
-    unsafe {
-        let req = ippNewRequest(op);
-        if req.is_null() {
-            return false;
-        }
+pub fn get_job_document(
+    printer_name: &str,
+    job_id: i32,
+    document_number: u32,
+) -> Result<JobDocument, PrintersError> {
+    let request = get_document_request(printer_name, job_id, document_number, &ipp::user_name());
+
+    // The data is spooled to an unlinked file so large documents stay out of memory
+    let path = env::temp_dir().join(format!(
+        "printers-document-{}-{job_id}-{}",
+        std::process::id(),
+        next_request_id()
+    ));
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .map_err(PrintersError::from)?;
+    let _ = fs::remove_file(&path);
+
+    let response = ipp::do_io_request(&request, "/", &file)?;
+    file.seek(SeekFrom::Start(0)).map_err(PrintersError::from)?;
+    JobDocument::from_ipp_response(&response, Box::new(file))
+}
 
-        let uri_param = &str_to_cstring("printer-uri");
-        let printer_uri =
-            str_to_cstring(format!("ipp://localhost/printers/{printer_name}").as_str());
-
-        ippAddString(
-            req,
-            CUPS_IPP_TAG_OPERATION,
-            CUPS_IPP_TAG_URI,
-            uri_param.as_ptr(),
-            ptr::null(),
-            printer_uri.as_ptr(),
-        );
+/**
+ * Change the attributes of a job that has not started (Set-Job-Attributes)
+ */
+pub fn set_job_attributes(
+    printer_name: &str,
+    job_id: i32,
+    changes: &JobChanges,
+) -> Result<(), PrintersError> {
+    let request = set_job_attributes_request(printer_name, job_id, &ipp::user_name(), changes);
+    let response = ipp::do_request(&request, "/jobs/")?;
+    if response.is_successful() {
+        Ok(())
+    } else {
+        Err(PrintersError::from_response(
+            IppOperation::SetJobAttributes,
+            &response,
+            PrintersError::job_error,
+        ))
+    }
+}
 
-        let job_id_param = &str_to_cstring("job-id");
-        ippAddInteger(
-            req,
-            CUPS_IPP_TAG_OPERATION,
-            CUPS_IPP_TAG_INTEGER,
-            job_id_param.as_ptr(),
-            job_id,
-        );
+/**
+ * Cancel the listed jobs of a queue matching the filter, purging their history too if requested
+ */
+pub fn cancel_jobs(
+    printer_name: &str,
+    filter: &CancelJobsFilter,
+    purge: bool,
+    job_ids: &[u64],
+) -> Result<(), PrintersError> {
+    let request = cancel_jobs_request(printer_name, filter, purge, job_ids, &ipp::user_name());
+    let resource = match request.operation() {
+        IppOperation::CancelMyJobs => "/jobs/",
+        _ => "/admin/",
+    };
+
+    let response = ipp::do_request(&request, resource)?;
+    if response.is_successful() {
+        Ok(())
+    } else {
+        Err(PrintersError::from_response(
+            request.operation(),
+            &response,
+            PrintersError::job_error,
+        ))
+    }
+}
 
-        let resource = &str_to_cstring("/");
-        let response = cupsDoRequest(ptr::null_mut(), req, resource.as_ptr());
-        let status = cupsLastError();
-        ippDelete(response);
+/**
+ * Move a job, or every job of the queue when job_id is None, to another queue (CUPS-Move-Job)
+ */
+pub fn move_job(
+    printer_name: &str,
+    job_id: Option<i32>,
+    target_name: &str,
+) -> Result<(), PrintersError> {
+    let request = move_job_request(printer_name, job_id, target_name, &ipp::user_name());
+    let response = ipp::do_request(&request, "/jobs/")?;
+
+    match response.status() {
+        _ if response.is_successful() => Ok(()),
+        IppStatusCode::ServerErrorNotAcceptingJobs => Err(PrintersError::from_response(
+            IppOperation::CupsMoveJob,
+            &response,
+            PrintersError::not_accepting_jobs_error,
+        )),
+        _ => Err(PrintersError::from_response(
+            IppOperation::CupsMoveJob,
+            &response,
+            PrintersError::job_error,
+        )),
+    }
+}
 
-        status == CUPS_IPP_OK
+/**
+ * Send request op to cups
+ */
+fn do_request(printer_name: &str, job_id: i32, op: IppOperation) -> Result<(), PrintersError> {
+    let request = job_request(op, printer_name, job_id, &ipp::user_name());
+    let response = ipp::do_request(&request, "/")?;
+    if response.is_successful() {
+        Ok(())
+    } else {
+        Err(PrintersError::from_response(
+            op,
+            &response,
+            PrintersError::job_error,
+        ))
     }
 }
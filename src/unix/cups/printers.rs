@@ -0,0 +1,96 @@
+use crate::{
+    admin::PrinterConfig,
+    common::base::errors::PrintersError,
+    ipp::{
+        IppMessage, IppOperation,
+        requests::{
+            add_modify_printer_request, printer_admin_request, printer_state_message_request,
+        },
+    },
+    unix::cups::ipp,
+};
+
+/**
+ * Queue administration requests are sent to the admin resource, as cupsdisable and cupsreject do
+ */
+const ADMIN_RESOURCE: &str = "/admin/";
+
+/**
+ * Stop processing the jobs of the queue (Pause-Printer)
+ */
+pub fn pause_printer(printer_name: &str) -> Result<(), PrintersError> {
+    do_admin_request(IppOperation::PausePrinter, printer_name, None)
+}
+
+/**
+ * Start processing the jobs of the queue again (Resume-Printer)
+ */
+pub fn resume_printer(printer_name: &str) -> Result<(), PrintersError> {
+    do_admin_request(IppOperation::ResumePrinter, printer_name, None)
+}
+
+/**
+ * Accept new jobs on the queue (CUPS-Accept-Jobs)
+ */
+pub fn accept_jobs(printer_name: &str) -> Result<(), PrintersError> {
+    do_admin_request(IppOperation::CupsAcceptJobs, printer_name, None)
+}
+
+/**
+ * Reject new jobs on the queue (CUPS-Reject-Jobs)
+ */
+pub fn reject_jobs(printer_name: &str, reason: Option<&str>) -> Result<(), PrintersError> {
+    do_admin_request(IppOperation::CupsRejectJobs, printer_name, reason)
+}
+
+/**
+ * Change the printer-state-message of the queue (CUPS-Add-Modify-Printer)
+ */
+pub fn set_state_message(printer_name: &str, message: &str) -> Result<(), PrintersError> {
+    let request = printer_state_message_request(printer_name, &ipp::user_name(), message);
+    send(&request)
+}
+
+/**
+ * Make the queue the server default (CUPS-Set-Default)
+ */
+pub fn set_default(printer_name: &str) -> Result<(), PrintersError> {
+    do_admin_request(IppOperation::CupsSetDefault, printer_name, None)
+}
+
+/**
+ * Create or change a queue (CUPS-Add-Modify-Printer)
+ */
+pub fn add_modify_printer(printer_name: &str, config: &PrinterConfig) -> Result<(), PrintersError> {
+    let request = add_modify_printer_request(printer_name, &ipp::user_name(), config);
+    send(&request)
+}
+
+/**
+ * Delete a queue (CUPS-Delete-Printer)
+ */
+pub fn delete_printer(printer_name: &str) -> Result<(), PrintersError> {
+    do_admin_request(IppOperation::CupsDeletePrinter, printer_name, None)
+}
+
+fn do_admin_request(
+    operation: IppOperation,
+    printer_name: &str,
+    message: Option<&str>,
+) -> Result<(), PrintersError> {
+    let request = printer_admin_request(operation, printer_name, &ipp::user_name(), message);
+    send(&request)
+}
+
+fn send(request: &IppMessage) -> Result<(), PrintersError> {
+    let response = ipp::do_request(request, ADMIN_RESOURCE)?;
+    if response.is_successful() {
+        Ok(())
+    } else {
+        Err(PrintersError::from_response(
+            request.operation(),
+            &response,
+            PrintersError::error,
+        ))
+    }
+}
@@ -1,2 +1 @@
-pub mod date;
 pub mod strings;
@@ -1,11 +1,23 @@
-use crate::common::{
-    base::{
-        errors::PrintersError,
-        job::{PrinterJob, PrinterJobOptions, PrinterJobState},
-        printer::{Printer, PrinterState},
+use std::io::Read;
+
+use crate::{
+    admin::PrinterConfig,
+    common::{
+        base::{
+            capabilities::PrinterCapabilities,
+            errors::PrintersError,
+            job::{
+                CancelJobsFilter, JobChanges, JobDocument, PrinterJob, PrinterJobOptions,
+                PrinterJobState,
+            },
+            printer::{
+                Printer, PrinterScope, PrinterState, PrinterStateReason, PrinterStateReasonKind,
+            },
+            supplies::Supply,
+        },
+        traits::platform::{PlatformActions, PlatformPrinterGetters, PlatformPrinterJobGetters},
+        utils::file,
     },
-    traits::platform::{PlatformActions, PlatformPrinterGetters},
-    utils::file,
 };
 
 mod utils;
@@ -33,12 +45,7 @@ impl PlatformActions for crate::Platform {
         let buffer = options.converter.convert(buffer)?;
         let buffer = &buffer.as_slice();
 
-        winspool::jobs::print_buffer(
-            printer_system_name,
-            options.name,
-            buffer,
-            options.raw_properties,
-        )
+        winspool::jobs::print_buffer(printer_system_name, buffer, &options)
     }
 
     fn print_file(
@@ -50,9 +57,46 @@ impl PlatformActions for crate::Platform {
         Self::print(printer_system_name, &buffer, options)
     }
 
-    fn get_printer_jobs(printer_name: &str, active_only: bool) -> Vec<PrinterJob> {
-        winspool::jobs::enum_printer_jobs(printer_name)
-            .unwrap_or_default()
+    fn print_stream(
+        printer_system_name: &str,
+        reader: &mut dyn Read,
+        options: PrinterJobOptions,
+    ) -> Result<u64, PrintersError> {
+        let mut buffer = Vec::new();
+        reader
+            .read_to_end(&mut buffer)
+            .map_err(PrintersError::from)?;
+        Self::print(printer_system_name, buffer.as_slice(), options)
+    }
+
+    fn create_job(
+        _printer_system_name: &str,
+        _options: &PrinterJobOptions,
+    ) -> Result<u64, PrintersError> {
+        // NOTE: A spooler job holds a single document (StartDocPrinter / EndDocPrinter)
+        Err(PrintersError::print_error(
+            "multi-document jobs are not supported on windows",
+        ))
+    }
+
+    fn send_document(
+        _printer_system_name: &str,
+        _job_id: u64,
+        _document_name: &str,
+        _reader: &mut dyn Read,
+        _document_format: Option<&str>,
+        _last_document: bool,
+    ) -> Result<(), PrintersError> {
+        Err(PrintersError::print_error(
+            "multi-document jobs are not supported on windows",
+        ))
+    }
+
+    fn get_printer_jobs(
+        printer_name: &str,
+        active_only: bool,
+    ) -> Result<Vec<PrinterJob>, PrintersError> {
+        Ok(winspool::jobs::enum_printer_jobs(printer_name)?
             .iter()
             .map(|j| PrinterJob::from_platform_printer_job_getters(j))
             .filter(|j| {
@@ -60,11 +104,34 @@ impl PlatformActions for crate::Platform {
                     j.state == PrinterJobState::PENDING
                         || j.state == PrinterJobState::PROCESSING
                         || j.state == PrinterJobState::PAUSED
+                        || j.state == PrinterJobState::STOPPED
                 } else {
                     true
                 }
             })
-            .collect()
+            .collect())
+    }
+
+    fn get_printer_job(
+        printer_name: Option<&str>,
+        job_id: u64,
+    ) -> Result<Option<PrinterJob>, PrintersError> {
+        let printer_names: Vec<String> = match printer_name {
+            Some(printer_name) => vec![printer_name.to_string()],
+            None => Self::get_printers()
+                .into_iter()
+                .map(|p| p.system_name)
+                .collect(),
+        };
+
+        // Winspool only lists queued jobs, a finished job is not found
+        for printer_name in &printer_names {
+            let jobs = winspool::jobs::enum_printer_jobs(printer_name)?;
+            if let Some(job) = jobs.iter().find(|j| j.get_id() == job_id) {
+                return Ok(Some(PrinterJob::from_platform_printer_job_getters(job)));
+            }
+        }
+        Ok(None)
     }
 
     fn get_default_printer() -> Option<Printer> {
@@ -78,8 +145,80 @@ impl PlatformActions for crate::Platform {
             .map(|p| Printer::from_platform_printer_getters(p))
     }
 
-    fn parse_printer_state(platform_state: u64, state_reasons: &str) -> PrinterState {
-        if state_reasons.contains("offline") || state_reasons.contains("pending_deletion") {
+    fn get_printer_capabilities(printer_name: &str) -> Result<PrinterCapabilities, PrintersError> {
+        winspool::info::get_capabilities(printer_name)
+    }
+
+    fn get_printer_supplies(_printer_name: &str) -> Result<Vec<Supply>, PrintersError> {
+        // NOTE: The spooler does not expose marker levels, they are only available through the vendor driver
+        Err(PrintersError::error(
+            "printer supplies are not supported on windows",
+        ))
+    }
+
+    fn set_printer_state(printer_name: &str, state: PrinterState) -> Result<(), PrintersError> {
+        match state {
+            PrinterState::PAUSED => winspool::jobs::set_printer_state(printer_name, 1),
+            PrinterState::READY => winspool::jobs::set_printer_state(printer_name, 2),
+            state => Err(PrintersError::error(format!(
+                "printer state cannot be set to {state:?}"
+            ))),
+        }
+    }
+
+    fn set_printer_accepting_jobs(
+        _printer_name: &str,
+        _accepting: bool,
+        _reason: Option<&str>,
+    ) -> Result<(), PrintersError> {
+        // NOTE: The spooler always accepts jobs, a paused queue only holds them
+        Err(PrintersError::error(
+            "accepting or rejecting jobs is not supported on windows",
+        ))
+    }
+
+    fn set_printer_state_message(_printer_name: &str, _message: &str) -> Result<(), PrintersError> {
+        Err(PrintersError::error(
+            "printer state messages are not supported on windows",
+        ))
+    }
+
+    fn set_default_printer(
+        printer_name: &str,
+        _instance: Option<&str>,
+        scope: PrinterScope,
+    ) -> Result<(), PrintersError> {
+        match scope {
+            PrinterScope::User => winspool::info::set_default_printer(printer_name),
+            PrinterScope::System => Err(PrintersError::error(
+                "the default printer is set per user on windows",
+            )),
+        }
+    }
+
+    fn add_modify_printer(
+        _printer_name: &str,
+        _config: &PrinterConfig,
+    ) -> Result<(), PrintersError> {
+        Err(PrintersError::error(
+            "printer administration is not supported on windows",
+        ))
+    }
+
+    fn delete_printer(_printer_name: &str) -> Result<(), PrintersError> {
+        Err(PrintersError::error(
+            "printer administration is not supported on windows",
+        ))
+    }
+
+    fn parse_printer_state(
+        platform_state: u64,
+        state_reasons: &[PrinterStateReason],
+    ) -> PrinterState {
+        if state_reasons.iter().any(|r| {
+            r.kind == PrinterStateReasonKind::Offline
+                || r.kind == PrinterStateReasonKind::Deactivated
+        }) {
             return PrinterState::OFFLINE;
         }
 
@@ -101,7 +240,8 @@ impl PlatformActions for crate::Platform {
             1 | 8 => PrinterJobState::PAUSED,
             4 | 256 => PrinterJobState::CANCELLED,
             16 | 2048 | 8192 => PrinterJobState::PROCESSING,
-            32 | 64 | 512 | 1024 => PrinterJobState::PENDING,
+            2 | 32 | 64 | 1024 => PrinterJobState::STOPPED,
+            512 => PrinterJobState::PENDING,
             128 | 496 => PrinterJobState::COMPLETED,
             _ => PrinterJobState::UNKNOWN,
         }
@@ -120,4 +260,50 @@ impl PlatformActions for crate::Platform {
             _ => Err(PrintersError::job_error("Operation canot be defined")),
         };
     }
+
+    fn get_job_document(
+        _printer_name: &str,
+        _job_id: u64,
+        _document_number: u32,
+    ) -> Result<JobDocument, PrintersError> {
+        Err(PrintersError::job_error(
+            "job documents are not supported on windows",
+        ))
+    }
+
+    fn set_job_attributes(
+        _printer_name: &str,
+        _job_id: u64,
+        _changes: &JobChanges,
+    ) -> Result<(), PrintersError> {
+        Err(PrintersError::job_error(
+            "changing job attributes is not supported on windows",
+        ))
+    }
+
+    fn cancel_jobs(
+        printer_name: &str,
+        filter: &CancelJobsFilter,
+        purge: bool,
+    ) -> Result<Vec<u64>, PrintersError> {
+        let user_name = std::env::var("USERNAME").unwrap_or_default();
+        let job_ids = filter.matching_jobs(printer_name, &user_name, purge)?;
+
+        // NOTE: The spooler keeps no job history, only the listed jobs are deleted so that
+        // jobs submitted meanwhile are kept (PRINTER_CONTROL_PURGE would delete them too)
+        for job_id in &job_ids {
+            winspool::jobs::set_job_state(printer_name, 5, *job_id)?;
+        }
+        Ok(job_ids)
+    }
+
+    fn move_jobs(
+        _printer_name: &str,
+        _job_id: Option<u64>,
+        _target_name: &str,
+    ) -> Result<(), PrintersError> {
+        Err(PrintersError::job_error(
+            "moving jobs is not supported on windows",
+        ))
+    }
 }
@@ -1,2 +1,17 @@
+use std::io;
+
+use crate::common::base::errors::PrintersError;
+
 pub mod info;
 pub mod jobs;
+
+/**
+ * Error of the last failed winspool call with the code and the message of GetLastError
+ */
+pub fn last_error(function: &str, error: fn(String) -> PrintersError) -> PrintersError {
+    let os_error = io::Error::last_os_error();
+    let mut printers_error =
+        error(format!("{function} failed: {os_error}")).with_operation(function);
+    printers_error.code = os_error.raw_os_error();
+    printers_error.with_source(os_error)
+}
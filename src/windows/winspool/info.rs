@@ -1,14 +1,24 @@
 #![allow(non_snake_case)]
 #![allow(non_camel_case_types)]
 
-use libc::{c_int, c_uint, c_ulong, c_void, wchar_t};
+use libc::{c_int, c_long, c_short, c_uint, c_ulong, c_ushort, c_void, wchar_t};
 use std::{ptr, slice};
 
 use crate::{
-    common::traits::platform::PlatformPrinterGetters,
-    windows::utils::{
-        memory::{alloc_s, dealloc_s},
-        strings::{str_to_wide_string, wchar_t_to_string},
+    common::{
+        base::{
+            capabilities::PrinterCapabilities,
+            errors::PrintersError,
+            options::{PrintColorMode, PrintFinishing, PrintResolution, PrintSides},
+        },
+        traits::platform::PlatformPrinterGetters,
+    },
+    windows::{
+        utils::{
+            memory::{alloc_s, dealloc_s},
+            strings::{str_to_wide_string, wchar_t_to_string},
+        },
+        winspool::last_error,
     },
 };
 
@@ -27,6 +37,16 @@ unsafe extern "system" {
 
     fn GetDefaultPrinterW(pszBuffer: *mut wchar_t, pcchBuffer: *mut c_ulong) -> c_int;
 
+    fn SetDefaultPrinterW(pszPrinter: *const wchar_t) -> c_int;
+
+    fn DeviceCapabilitiesW(
+        pDevice: *const wchar_t,
+        pPort: *const wchar_t,
+        fwCapability: c_ushort,
+        pOutput: *mut c_void,
+        pDevMode: *const c_void,
+    ) -> c_int;
+
 }
 
 /**
@@ -102,35 +122,33 @@ impl PlatformPrinterGetters for PRINTER_INFO_2W {
     fn get_data_type(&self) -> String {
         wchar_t_to_string(self.pDatatype)
     }
+
+    fn get_instance(&self) -> Option<String> {
+        None
+    }
     fn get_state_reasons(&self) -> Vec<String> {
-        // NOTE: These reasons are virtual descriptions based on printer status
+        // NOTE: These reasons are the printer-state-reasons closest to the status bits,
+        // bits already represented by the printer state (printing, busy...) are omitted
         return [
-            (0x00000000, "ready"),
-            (0x00000001, "paused"),
-            (0x00000002, "error"),
-            (0x00000004, "pending_deletion"),
-            (0x00000008, "paper_jam"),
-            (0x00000010, "paper_out"),
-            (0x00000020, "manual_feed"),
-            (0x00000040, "paper_problem"),
-            (0x00000080, "offline"),
-            (0x00000100, "io_active"),
-            (0x00000200, "busy"),
-            (0x00000400, "printing"),
-            (0x00000800, "output_bin_full"),
-            (0x00001000, "not_available"),
-            (0x00002000, "waiting"),
-            (0x00004000, "processing"),
-            (0x00008000, "initializing"),
-            (0x00010000, "warming_up"),
-            (0x00020000, "toner_low"),
-            (0x00040000, "no_toner"),
-            (0x00080000, "page_punt"),
-            (0x00100000, "user_intervention"),
-            (0x00200000, "out_of_memory"),
-            (0x00400000, "door_open"),
-            (0x00800000, "server_unknown"),
-            (0x01000000, "power_save"),
+            (0x00000001, "paused-report"),
+            (0x00000002, "other-error"),
+            (0x00000004, "deactivated-report"),
+            (0x00000008, "media-jam-error"),
+            (0x00000010, "media-empty-error"),
+            (0x00000020, "media-needed-report"),
+            (0x00000040, "paper-problem-error"),
+            (0x00000080, "offline-report"),
+            (0x00000800, "output-area-full-error"),
+            (0x00001000, "shutdown-report"),
+            (0x00010000, "warming-up-report"),
+            (0x00020000, "toner-low-warning"),
+            (0x00040000, "toner-empty-error"),
+            (0x00080000, "page-punt-warning"),
+            (0x00100000, "user-intervention-required-error"),
+            (0x00200000, "interpreter-resource-unavailable-error"),
+            (0x00400000, "door-open-error"),
+            (0x00800000, "server-unknown-error"),
+            (0x01000000, "power-save-report"),
         ]
         .iter()
         .filter(|v| self.Status & v.0 != 0)
@@ -192,3 +210,178 @@ pub fn free(printers: &'static [PRINTER_INFO_2W]) {
         dealloc_s::<PRINTER_INFO_2W>(printers.as_ptr());
     }
 }
+
+const DC_PAPERS: c_ushort = 2;
+const DC_DUPLEX: c_ushort = 7;
+const DC_ENUMRESOLUTIONS: c_ushort = 13;
+const DC_PAPERNAMES: c_ushort = 16;
+const DC_COPIES: c_ushort = 18;
+const DC_STAPLE: c_ushort = 30;
+const DC_COLORDEVICE: c_ushort = 32;
+
+/**
+ * Returns a device capability value using DeviceCapabilitiesW
+ */
+fn device_capability(printer_name: &[u16], port_name: &[u16], capability: c_ushort) -> c_int {
+    unsafe {
+        DeviceCapabilitiesW(
+            printer_name.as_ptr() as *const wchar_t,
+            port_name.as_ptr() as *const wchar_t,
+            capability,
+            ptr::null_mut(),
+            ptr::null(),
+        )
+    }
+}
+
+/**
+ * Returns the entries of a device capability using DeviceCapabilitiesW, each entry with a fixed size
+ */
+fn device_capability_entries<T: Clone + Default>(
+    printer_name: &[u16],
+    port_name: &[u16],
+    capability: c_ushort,
+    entry_size: usize,
+) -> Vec<Vec<T>> {
+    let count = device_capability(printer_name, port_name, capability);
+    if count <= 0 {
+        return Vec::new();
+    }
+
+    let mut buffer: Vec<T> = vec![T::default(); count as usize * entry_size];
+    let count = unsafe {
+        DeviceCapabilitiesW(
+            printer_name.as_ptr() as *const wchar_t,
+            port_name.as_ptr() as *const wchar_t,
+            capability,
+            buffer.as_mut_ptr() as *mut c_void,
+            ptr::null(),
+        )
+    };
+
+    buffer
+        .chunks(entry_size)
+        .take(count.max(0) as usize)
+        .map(|v| v.to_vec())
+        .collect()
+}
+
+/**
+ * IPP media keywords and their DMPAPER values
+ */
+const MEDIA_PAPER_SIZES: &[(&str, c_short)] = &[
+    ("na_letter_8.5x11in", 1),
+    ("na_ledger_11x17in", 3),
+    ("na_legal_8.5x14in", 5),
+    ("na_invoice_5.5x8.5in", 6),
+    ("na_executive_7.25x10.5in", 7),
+    ("iso_a3_297x420mm", 8),
+    ("iso_a4_210x297mm", 9),
+    ("iso_a5_148x210mm", 11),
+    ("jis_b4_257x364mm", 12),
+    ("jis_b5_182x257mm", 13),
+    ("na_number-10_4.125x9.5in", 20),
+    ("iso_dl_110x220mm", 27),
+];
+
+/**
+ * Returns the port of a printer, required by DeviceCapabilitiesW
+ */
+fn port_name(printer_name: &str) -> Result<Vec<u16>, PrintersError> {
+    let printers = enum_printers(None);
+    let port_name = printers
+        .iter()
+        .find(|p| p.get_system_name() == printer_name)
+        .map(|p| p.get_port_name());
+    free(printers);
+
+    port_name
+        .map(|v| str_to_wide_string(&v))
+        .ok_or_else(|| PrintersError::error("printer not found"))
+}
+
+/**
+ * Returns the DMPAPER value of a media, given as an IPP keyword or as a paper name of the driver
+ */
+pub fn paper_size(printer_name: &str, media: &str) -> Result<Option<c_short>, PrintersError> {
+    if let Some((_, size)) = MEDIA_PAPER_SIZES.iter().find(|(name, _)| *name == media) {
+        return Ok(Some(*size));
+    }
+
+    let port_name = port_name(printer_name)?;
+    let printer_name = str_to_wide_string(printer_name);
+
+    // DC_PAPERS lists the paper values in the order of DC_PAPERNAMES
+    let names = device_capability_entries::<wchar_t>(&printer_name, &port_name, DC_PAPERNAMES, 64);
+    let papers = device_capability_entries::<c_ushort>(&printer_name, &port_name, DC_PAPERS, 1);
+
+    Ok(names
+        .iter()
+        .position(|v| wchar_t_to_string(v.as_ptr()) == media)
+        .and_then(|index| papers.get(index))
+        .map(|v| v[0] as c_short))
+}
+
+/**
+ * Returns the printer capabilities using DeviceCapabilitiesW
+ */
+pub fn get_capabilities(printer_name: &str) -> Result<PrinterCapabilities, PrintersError> {
+    let port_name = port_name(printer_name)?;
+    let printer_name = str_to_wide_string(printer_name);
+
+    let media = device_capability_entries::<wchar_t>(&printer_name, &port_name, DC_PAPERNAMES, 64)
+        .iter()
+        .map(|v| wchar_t_to_string(v.as_ptr()))
+        .collect();
+
+    let sides = if device_capability(&printer_name, &port_name, DC_DUPLEX) == 1 {
+        vec![
+            PrintSides::OneSided,
+            PrintSides::TwoSidedLongEdge,
+            PrintSides::TwoSidedShortEdge,
+        ]
+    } else {
+        vec![PrintSides::OneSided]
+    };
+
+    let color_modes = if device_capability(&printer_name, &port_name, DC_COLORDEVICE) == 1 {
+        vec![PrintColorMode::Monochrome, PrintColorMode::Color]
+    } else {
+        vec![PrintColorMode::Monochrome]
+    };
+
+    let resolutions =
+        device_capability_entries::<c_long>(&printer_name, &port_name, DC_ENUMRESOLUTIONS, 2)
+            .iter()
+            .map(|v| PrintResolution::new(v[0].max(0) as u32, v[1].max(0) as u32))
+            .collect();
+
+    let finishings = if device_capability(&printer_name, &port_name, DC_STAPLE) == 1 {
+        vec![PrintFinishing::Staple]
+    } else {
+        Vec::new()
+    };
+
+    Ok(PrinterCapabilities {
+        media,
+        sides,
+        color_modes,
+        resolutions,
+        document_formats: Vec::new(),
+        copies: 1..=device_capability(&printer_name, &port_name, DC_COPIES).max(1) as u32,
+        finishings,
+    })
+}
+
+/**
+ * Set the default printer of the current user
+ */
+pub fn set_default_printer(printer_name: &str) -> Result<(), PrintersError> {
+    let printer_name = str_to_wide_string(printer_name);
+
+    if unsafe { SetDefaultPrinterW(printer_name.as_ptr() as *const wchar_t) } == 0 {
+        Err(last_error("SetDefaultPrinterW", PrintersError::error))
+    } else {
+        Ok(())
+    }
+}
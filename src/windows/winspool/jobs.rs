@@ -1,15 +1,25 @@
 #![allow(non_snake_case)]
 #![allow(non_camel_case_types)]
 
-use libc::{c_int, c_ulong, c_ushort, c_void, wchar_t};
+use libc::{c_int, c_long, c_short, c_ulong, c_ushort, c_void, wchar_t};
 use std::{ffi::c_char, ptr, slice};
 
 use crate::{
-    common::{base::errors::PrintersError, traits::platform::PlatformPrinterJobGetters},
-    windows::utils::{
-        date::{calculate_system_time, get_current_epoch},
-        memory::alloc_s,
-        strings::{str_to_wide_string, wchar_t_to_string},
+    common::{
+        base::{
+            errors::PrintersError,
+            job::PrinterJobOptions,
+            options::{JobHoldUntil, PrintColorMode, PrintOrientation, PrintQuality, PrintSides},
+        },
+        traits::platform::PlatformPrinterJobGetters,
+    },
+    windows::{
+        utils::{
+            date::{calculate_system_time, get_current_epoch},
+            memory::alloc_s,
+            strings::{str_to_wide_string, wchar_t_to_string},
+        },
+        winspool::{info, last_error},
     },
 };
 
@@ -45,6 +55,12 @@ unsafe extern "system" {
         pcbNeeded: *mut c_ulong,
         pcReturned: *mut c_ulong,
     ) -> c_int;
+    fn SetPrinterW(
+        hPrinter: *mut c_void,
+        Level: c_ulong,
+        pPrinter: *mut c_char,
+        Command: c_ulong,
+    ) -> c_int;
     fn SetJobW(
         hPrinter: *mut c_void,
         JobId: c_ulong,
@@ -52,6 +68,65 @@ unsafe extern "system" {
         pJob: *mut c_char,
         Command: c_ulong,
     ) -> c_int;
+    fn GetJobW(
+        hPrinter: *mut c_void,
+        JobId: c_ulong,
+        Level: c_ulong,
+        pJob: *mut c_void,
+        cbBuf: c_ulong,
+        pcbNeeded: *mut c_ulong,
+    ) -> c_int;
+    fn DocumentPropertiesW(
+        hWnd: *mut c_void,
+        hPrinter: *mut c_void,
+        pDeviceName: *const wchar_t,
+        pDevModeOutput: *mut c_void,
+        pDevModeInput: *const c_void,
+        fMode: c_ulong,
+    ) -> c_long;
+}
+
+const PRINTER_ACCESS_ADMINISTER: c_ulong = 0x00000004;
+const PRINTER_ACCESS_USE: c_ulong = 0x00000008;
+
+const DM_OUT_BUFFER: c_ulong = 2;
+const DM_IN_BUFFER: c_ulong = 8;
+
+const DM_ORIENTATION: c_ulong = 0x00000001;
+const DM_PAPERSIZE: c_ulong = 0x00000002;
+const DM_PRINTQUALITY: c_ulong = 0x00000400;
+const DM_COLOR: c_ulong = 0x00000800;
+const DM_DUPLEX: c_ulong = 0x00001000;
+const DM_COLLATE: c_ulong = 0x00008000;
+
+const JOB_CONTROL_PAUSE: c_ulong = 1;
+const JOB_CONTROL_DELETE: c_ulong = 5;
+
+/**
+ * The leading fields of the DEVMODEW structure, the driver data follows them.
+ * https://learn.microsoft.com/en-us/windows/win32/api/wingdi/ns-wingdi-devmodew
+ */
+#[repr(C)]
+struct DEVMODEW_PREFIX {
+    dmDeviceName: [wchar_t; 32],
+    dmSpecVersion: c_ushort,
+    dmDriverVersion: c_ushort,
+    dmSize: c_ushort,
+    dmDriverExtra: c_ushort,
+    dmFields: c_ulong,
+    dmOrientation: c_short,
+    dmPaperSize: c_short,
+    dmPaperLength: c_short,
+    dmPaperWidth: c_short,
+    dmScale: c_short,
+    dmCopies: c_short,
+    dmDefaultSource: c_short,
+    dmPrintQuality: c_short,
+    dmColor: c_short,
+    dmDuplex: c_short,
+    dmYResolution: c_short,
+    dmTTOption: c_short,
+    dmCollate: c_short,
 }
 
 #[repr(C)]
@@ -147,6 +222,55 @@ impl PlatformPrinterJobGetters for JOB_INFO_1W {
     fn get_completed_at(&self) -> Option<std::time::SystemTime> {
         Some(self.get_created_at())
     }
+
+    fn get_owner(&self) -> String {
+        wchar_t_to_string(self.pUserName)
+    }
+
+    fn get_size(&self) -> u64 {
+        0
+    }
+
+    fn get_priority(&self) -> u32 {
+        self.Priority
+    }
+
+    fn get_state_reasons(&self) -> Vec<String> {
+        // NOTE: These reasons are the job-state-reasons closest to the job status bits
+        [
+            (0x00000002, "errors-detected"),
+            (0x00000008, "job-spooling"),
+            (0x00000010, "job-printing"),
+            (0x00000020, "service-off-line"),
+            (0x00000040, "printer-stopped"),
+            (0x00000080, "job-completed-successfully"),
+            (0x00000100, "job-canceled-by-user"),
+        ]
+        .iter()
+        .filter(|v| self.Status & v.0 != 0)
+        .map(|v| v.1.to_string())
+        .collect()
+    }
+
+    fn get_printer_state_message(&self) -> String {
+        wchar_t_to_string(self.pStatus)
+    }
+
+    fn get_impressions_completed(&self) -> u32 {
+        0
+    }
+
+    fn get_pages_completed(&self) -> u32 {
+        self.PagesPrinted
+    }
+
+    fn get_document_count(&self) -> u32 {
+        1
+    }
+
+    fn get_hold_until(&self) -> Option<String> {
+        None
+    }
 }
 
 /**
@@ -164,28 +288,298 @@ fn open_printer(printer_name: &str) -> Result<*mut c_void, PrintersError> {
         )
     } == 0
     {
-        Err(PrintersError::job_error("OpenPrinterW failed"))
+        Err(last_error("OpenPrinterW", PrintersError::job_error))
     } else {
         Ok(printer_handle)
     };
 }
 
 /**
- * Print a buffer as RAW datatype with winspool WritePrinterx
+ * Settings of a job that have no DEVMODE field
+ */
+struct JobSettings {
+    priority: Option<c_ulong>,
+    hold: bool,
+}
+
+/**
+ * Fail for the typed options the spooler cannot apply, instead of printing without them
+ */
+fn job_settings(options: &PrinterJobOptions) -> Result<JobSettings, PrintersError> {
+    let unsupported = |name: &str| {
+        Err(PrintersError::print_error(format!(
+            "{name} is not supported on windows"
+        )))
+    };
+
+    if !options.page_ranges.is_empty() {
+        return unsupported("page-ranges");
+    }
+    if options.number_up.is_some_and(|n| n > 1) {
+        return unsupported("number-up");
+    }
+    if options.job_password.is_some() {
+        return unsupported("job-password");
+    }
+
+    let hold = match options.hold_until {
+        None | Some(JobHoldUntil::NoHold) => false,
+        Some(JobHoldUntil::Indefinite) => true,
+        Some(hold_until) => {
+            return unsupported(&format!("job-hold-until {}", hold_until.keyword()));
+        }
+    };
+
+    Ok(JobSettings {
+        priority: options.priority.map(|p| p.clamp(1, 99) as c_ulong),
+        hold,
+    })
+}
+
+/**
+ * Build the DEVMODE of a job from the defaults of the printer and the typed options,
+ * None when no option needs it
+ */
+fn job_devmode(
+    printer_handle: *mut c_void,
+    printer_name: &str,
+    options: &PrinterJobOptions,
+) -> Result<Option<Vec<u64>>, PrintersError> {
+    let unsupported = |name: &str| {
+        Err(PrintersError::print_error(format!(
+            "{name} is not supported on windows"
+        )))
+    };
+
+    let mut fields: Vec<(c_ulong, c_short)> = Vec::new();
+
+    if let Some(sides) = options.sides {
+        let duplex = match sides {
+            PrintSides::OneSided => 1,
+            PrintSides::TwoSidedLongEdge => 2,
+            PrintSides::TwoSidedShortEdge => 3,
+        };
+        fields.push((DM_DUPLEX, duplex));
+    }
+    if let Some(media) = options.media {
+        match info::paper_size(printer_name, media)? {
+            Some(paper_size) => fields.push((DM_PAPERSIZE, paper_size)),
+            None => {
+                return Err(PrintersError::print_error(format!(
+                    "media {media} is not supported by {printer_name}"
+                )));
+            }
+        }
+    }
+    if let Some(orientation) = options.orientation {
+        let orientation = match orientation {
+            PrintOrientation::Portrait => 1,
+            PrintOrientation::Landscape => 2,
+            _ => return unsupported(&format!("orientation {orientation:?}")),
+        };
+        fields.push((DM_ORIENTATION, orientation));
+    }
+    if let Some(quality) = options.quality {
+        // DMRES_DRAFT, DMRES_MEDIUM and DMRES_HIGH
+        let quality = match quality {
+            PrintQuality::Draft => -1,
+            PrintQuality::Normal => -3,
+            PrintQuality::High => -4,
+        };
+        fields.push((DM_PRINTQUALITY, quality));
+    }
+    match options.color_mode {
+        None | Some(PrintColorMode::Auto) => {}
+        Some(PrintColorMode::Color) => fields.push((DM_COLOR, 2)),
+        Some(PrintColorMode::Highlight) => return unsupported("print-color-mode highlight"),
+        Some(_) => fields.push((DM_COLOR, 1)),
+    }
+    if let Some(collate) = options.collate {
+        fields.push((DM_COLLATE, collate as c_short));
+    }
+
+    if fields.is_empty() {
+        return Ok(None);
+    }
+
+    let device_name = str_to_wide_string(printer_name);
+    unsafe {
+        let size = DocumentPropertiesW(
+            ptr::null_mut(),
+            printer_handle,
+            device_name.as_ptr() as *const wchar_t,
+            ptr::null_mut(),
+            ptr::null(),
+            0,
+        );
+        if size <= 0 {
+            return Err(last_error(
+                "DocumentPropertiesW",
+                PrintersError::print_error,
+            ));
+        }
+
+        // Stored as u64 to keep the buffer aligned for DEVMODEW_PREFIX
+        let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+        let devmode = buffer.as_mut_ptr() as *mut c_void;
+        if DocumentPropertiesW(
+            ptr::null_mut(),
+            printer_handle,
+            device_name.as_ptr() as *const wchar_t,
+            devmode,
+            ptr::null(),
+            DM_OUT_BUFFER,
+        ) < 0
+        {
+            return Err(last_error(
+                "DocumentPropertiesW",
+                PrintersError::print_error,
+            ));
+        }
+
+        let fields_devmode = &mut *(devmode as *mut DEVMODEW_PREFIX);
+        for (field, value) in fields {
+            match field {
+                DM_DUPLEX => fields_devmode.dmDuplex = value,
+                DM_PAPERSIZE => fields_devmode.dmPaperSize = value,
+                DM_ORIENTATION => fields_devmode.dmOrientation = value,
+                DM_PRINTQUALITY => fields_devmode.dmPrintQuality = value,
+                DM_COLOR => fields_devmode.dmColor = value,
+                _ => fields_devmode.dmCollate = value,
+            }
+            fields_devmode.dmFields |= field;
+        }
+
+        // The driver merges the changes and validates them against the device
+        if DocumentPropertiesW(
+            ptr::null_mut(),
+            printer_handle,
+            device_name.as_ptr() as *const wchar_t,
+            devmode,
+            devmode,
+            DM_IN_BUFFER | DM_OUT_BUFFER,
+        ) < 0
+        {
+            return Err(last_error(
+                "DocumentPropertiesW",
+                PrintersError::print_error,
+            ));
+        }
+
+        Ok(Some(buffer))
+    }
+}
+
+/**
+ * Open a printer with the DEVMODE used by the jobs started on the handle
+ */
+fn open_printer_with_devmode(
+    printer_name: &str,
+    devmode: &mut [u64],
+) -> Result<*mut c_void, PrintersError> {
+    let printer_name = str_to_wide_string(printer_name);
+    let mut printer_handle: *mut c_void = ptr::null_mut();
+    let mut defaults = PrinterDefaultW {
+        pDatatype: ptr::null_mut(),
+        pDevMode: devmode.as_mut_ptr() as *mut c_void,
+        DesiredAccess: PRINTER_ACCESS_USE,
+    };
+
+    if unsafe {
+        OpenPrinterW(
+            printer_name.as_ptr() as *const wchar_t,
+            &mut printer_handle,
+            &mut defaults,
+        )
+    } == 0
+    {
+        Err(last_error("OpenPrinterW", PrintersError::job_error))
+    } else {
+        Ok(printer_handle)
+    }
+}
+
+/**
+ * Change the priority of a started job with a JOB_INFO_1 (1 to 99)
+ */
+fn set_job_priority(
+    printer_handle: *mut c_void,
+    job_id: c_ulong,
+    priority: c_ulong,
+) -> Result<(), PrintersError> {
+    unsafe {
+        let mut bytes_needed: c_ulong = 0;
+        GetJobW(
+            printer_handle,
+            job_id,
+            1,
+            ptr::null_mut(),
+            0,
+            &mut bytes_needed,
+        );
+
+        let mut buffer = vec![0u64; (bytes_needed as usize).div_ceil(8).max(1)];
+        if GetJobW(
+            printer_handle,
+            job_id,
+            1,
+            buffer.as_mut_ptr() as *mut c_void,
+            bytes_needed,
+            &mut bytes_needed,
+        ) == 0
+        {
+            return Err(last_error("GetJobW", PrintersError::print_error));
+        }
+
+        let job = &mut *(buffer.as_mut_ptr() as *mut JOB_INFO_1W);
+        job.Priority = priority;
+        // JOB_POSITION_UNSPECIFIED keeps the job at its place in the queue
+        job.Position = 0;
+
+        if SetJobW(
+            printer_handle,
+            job_id,
+            1,
+            buffer.as_mut_ptr() as *mut c_char,
+            0,
+        ) == 0
+        {
+            return Err(last_error("SetJobW", PrintersError::print_error));
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Print a buffer with winspool WritePrinter, RAW by default.
+ * The typed options are sent in the DEVMODE of the job, used by the driver for the
+ * non RAW datatypes, and the ones the spooler cannot apply fail the print
  */
 pub fn print_buffer(
     printer_name: &str,
-    job_name: Option<&str>,
     buffer: &[u8],
-    options: &[(&str, &str)],
+    options: &PrinterJobOptions,
 ) -> Result<u64, PrintersError> {
+    let settings = job_settings(options)?;
+
     unsafe {
         let printer_handle = open_printer(printer_name)?;
+        let printer_handle = match job_devmode(printer_handle, printer_name, options) {
+            Ok(None) => printer_handle,
+            Ok(Some(mut devmode)) => {
+                ClosePrinter(printer_handle);
+                open_printer_with_devmode(printer_name, &mut devmode)?
+            }
+            Err(error) => {
+                ClosePrinter(printer_handle);
+                return Err(error);
+            }
+        };
 
-        let mut copies = 1;
+        let mut copies = options.copies.unwrap_or(1);
         let mut data_type = "RAW";
 
-        for option in options {
+        for option in options.raw_properties {
             match option.0 {
                 "copies" => copies = option.1.parse().unwrap_or(copies),
                 "document-format" => data_type = option.1,
@@ -194,8 +588,11 @@ pub fn print_buffer(
         }
 
         let mut p_data_type = str_to_wide_string(data_type);
-        let mut p_doc_name =
-            str_to_wide_string(job_name.unwrap_or(get_current_epoch().to_string().as_str()));
+        let mut p_doc_name = str_to_wide_string(
+            options
+                .name
+                .unwrap_or(get_current_epoch().to_string().as_str()),
+        );
 
         let doc_info = DocInfo1 {
             pDocName: p_doc_name.as_mut_ptr() as *mut wchar_t,
@@ -205,8 +602,40 @@ pub fn print_buffer(
 
         let job_id = StartDocPrinterW(printer_handle, 1, &doc_info);
         if job_id == 0 {
+            let error = last_error("StartDocPrinterW", PrintersError::job_error);
             ClosePrinter(printer_handle);
-            return Err(PrintersError::job_error("StartDocPrinterW failed"));
+            return Err(error);
+        }
+
+        let mut result = Ok(());
+        if let Some(priority) = settings.priority {
+            result = set_job_priority(printer_handle, job_id, priority);
+        }
+        if result.is_ok()
+            && settings.hold
+            && SetJobW(
+                printer_handle,
+                job_id,
+                0,
+                ptr::null_mut(),
+                JOB_CONTROL_PAUSE,
+            ) == 0
+        {
+            result = Err(last_error("SetJobW", PrintersError::print_error));
+        }
+
+        // A job missing its settings is deleted rather than printed
+        if let Err(error) = result {
+            SetJobW(
+                printer_handle,
+                job_id,
+                0,
+                ptr::null_mut(),
+                JOB_CONTROL_DELETE,
+            );
+            EndDocPrinter(printer_handle);
+            ClosePrinter(printer_handle);
+            return Err(error);
         }
 
         for _ in 0..copies {
@@ -261,10 +690,11 @@ pub fn enum_printer_jobs(printer_name: &str) -> Result<&'static [JOB_INFO_1W], P
         buffer_ptr = alloc_s::<JOB_INFO_1W>(bytes_needed);
     }
 
+    let error = (enum_result == 0).then(|| last_error("EnumJobsW", PrintersError::job_error));
     unsafe { ClosePrinter(printer_handle) };
 
-    if enum_result == 0 {
-        return Err(PrintersError::job_error("EnumJobsW failed"));
+    if let Some(error) = error {
+        return Err(error);
     }
 
     Ok(if jobs_count > 0 {
@@ -289,12 +719,46 @@ pub fn set_job_state(printer_name: &str, command: u64, job_id: u64) -> Result<()
             command as c_ulong,
         );
 
+        let error = (result == 0).then(|| last_error("SetJobW", PrintersError::job_error));
+        ClosePrinter(printer_handle);
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+/**
+ * Change printer state (PRINTER_CONTROL_PAUSE = 1, PRINTER_CONTROL_RESUME = 2, PRINTER_CONTROL_PURGE = 3)
+ */
+pub fn set_printer_state(printer_name: &str, command: u64) -> Result<(), PrintersError> {
+    let wide_name = str_to_wide_string(printer_name);
+    let mut printer_handle: *mut c_void = ptr::null_mut();
+    let mut defaults = PrinterDefaultW {
+        pDatatype: ptr::null_mut(),
+        pDevMode: ptr::null_mut(),
+        DesiredAccess: PRINTER_ACCESS_ADMINISTER,
+    };
+
+    unsafe {
+        if OpenPrinterW(
+            wide_name.as_ptr() as *const wchar_t,
+            &mut printer_handle,
+            &mut defaults,
+        ) == 0
+        {
+            return Err(last_error("OpenPrinterW", PrintersError::error));
+        }
+
+        let result = SetPrinterW(printer_handle, 0, ptr::null_mut(), command as c_ulong);
+
+        let error = (result == 0).then(|| last_error("SetPrinterW", PrintersError::error));
         ClosePrinter(printer_handle);
 
-        if result == 0 {
-            Err(PrintersError::job_error("SetJobW failed"))
-        } else {
-            Ok(())
+        match error {
+            Some(error) => Err(error),
+            None => Ok(()),
         }
     }
 }
@@ -0,0 +1,256 @@
+#![cfg(all(feature = "async", any(target_family = "unix", feature = "ipp-client")))]
+
+mod common;
+
+mod asynchronous {
+    use crate::common::{Responder, printer_response, response};
+    use printers::{
+        asynchronous,
+        common::{
+            base::job::{PrinterJobOptions, PrinterJobState},
+            converters::Converter,
+        },
+        ipp::{IppAttribute, IppGroupTag, IppMessage, IppOperation, IppStatusCode, IppValue},
+    };
+    use std::{
+        env,
+        future::Future,
+        pin::{Pin, pin},
+        sync::{Arc, OnceLock},
+        task::{Context, Poll, Wake, Waker},
+        thread::{self, Thread},
+        time::{Duration, Instant},
+    };
+
+    static RESPONDER: OnceLock<Responder> = OnceLock::new();
+
+    /**
+     * Start a local IPP responder and point the backend to it
+     */
+    fn responder() -> &'static Responder {
+        RESPONDER.get_or_init(|| {
+            let responder = Responder::start(handle);
+            // SAFETY: set once before any request of this test binary reads it
+            unsafe { env::set_var("CUPS_SERVER", &responder.address) };
+            responder
+        })
+    }
+
+    fn handle(request: &IppMessage) -> IppMessage {
+        if let Some(response) = printer_response(request, "MockPrinter") {
+            return response;
+        }
+
+        let mut response = response(request, IppStatusCode::SuccessfulOk);
+        let job_id = request
+            .group(IppGroupTag::Operation)
+            .and_then(|g| g.integer("job-id"));
+        let job_name = request
+            .group(IppGroupTag::Operation)
+            .map(|g| g.string("job-name"))
+            .unwrap_or_default();
+
+        match request.operation() {
+            IppOperation::PrintJob | IppOperation::CreateJob => {
+                // An endless job is processed forever
+                let id = if job_name == "endless job" { 12 } else { 7 };
+                response.add(
+                    IppGroupTag::Job,
+                    IppAttribute::new("job-id", IppValue::Integer(id)),
+                );
+            }
+            IppOperation::SendDocument | IppOperation::GetPrinterAttributes => {}
+            IppOperation::GetJobAttributes if job_id == Some(7) => {
+                for attribute in [
+                    IppAttribute::new("job-id", IppValue::Integer(7)),
+                    IppAttribute::new("job-state", IppValue::Enum(9)),
+                ] {
+                    response.add(IppGroupTag::Job, attribute);
+                }
+            }
+            IppOperation::GetJobAttributes if job_id == Some(12) => {
+                for attribute in [
+                    IppAttribute::new("job-id", IppValue::Integer(12)),
+                    IppAttribute::new("job-state", IppValue::Enum(5)),
+                ] {
+                    response.add(IppGroupTag::Job, attribute);
+                }
+            }
+            IppOperation::CancelJob | IppOperation::HoldJob | IppOperation::ReleaseJob
+                if job_id == Some(7) => {}
+            _ => response.code = IppStatusCode::ClientErrorNotFound.into(),
+        }
+
+        response
+    }
+
+    /**
+     * Wakes the thread blocked on a future
+     */
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /**
+     * Minimal executor, the tasks of the lib do not depend on any runtime
+     */
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut context = Context::from_waker(&waker);
+
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    #[test]
+    fn test_printers() {
+        responder();
+
+        let printers = block_on(asynchronous::get_printers());
+        assert!(printers.iter().any(|p| p.system_name == "MockPrinter"));
+
+        let printer = block_on(asynchronous::get_default_printer()).unwrap();
+        assert_eq!(printer.system_name, "MockPrinter");
+        assert!(block_on(asynchronous::get_printer_by_name("MissingPrinter")).is_none());
+    }
+
+    #[test]
+    fn test_print() {
+        let responder = responder();
+        let printer = block_on(asynchronous::get_printer_by_name("MockPrinter")).unwrap();
+
+        let mut job = block_on(printer.print_async(b"async", PrinterJobOptions::none())).unwrap();
+        assert_eq!(job.id, 7);
+
+        block_on(job.pause_async()).unwrap();
+        block_on(printer.release_job_async(7)).unwrap();
+        assert_eq!(responder.received(IppOperation::HoldJob).len(), 1);
+
+        let state = block_on(job.wait_until_done_async(Duration::from_secs(5))).unwrap();
+        assert_eq!(state, PrinterJobState::COMPLETED);
+        assert_eq!(job.status(), PrinterJobState::COMPLETED);
+
+        let error = block_on(printer.cancel_job_async(8)).unwrap_err();
+        assert!(!error.message.is_empty());
+    }
+
+    #[test]
+    fn test_print_borrowed_options() {
+        let responder = responder();
+        let printer = block_on(asynchronous::get_printer_by_name("MockPrinter")).unwrap();
+
+        // The options borrow values dropped before the task completes
+        let task = {
+            let name = format!("async {}", 42);
+            let raw_properties = [("job-sheets", "none")];
+            printer.print_async(
+                b"async",
+                PrinterJobOptions {
+                    name: Some(&name),
+                    raw_properties: &raw_properties,
+                    ..PrinterJobOptions::none()
+                },
+            )
+        };
+        assert_eq!(block_on(task).unwrap().id, 7);
+
+        let requests = responder.requests.lock().unwrap().clone();
+        assert!(requests.iter().any(|r| {
+            r.group(IppGroupTag::Operation)
+                .is_some_and(|g| g.string("job-name") == "async 42")
+        }));
+    }
+
+    #[test]
+    fn test_dropped_task() {
+        let responder = responder();
+        let printer = block_on(asynchronous::get_printer_by_name("MockPrinter")).unwrap();
+
+        // Tasks start when first polled
+        drop(printer.cancel_job_async(7));
+        thread::sleep(Duration::from_millis(200));
+        assert!(responder.received(IppOperation::CancelJob).is_empty());
+
+        // A started task completes even if dropped
+        let mut task = printer.cancel_job_async(7);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let poll = Pin::new(&mut task).poll(&mut Context::from_waker(&waker));
+        assert!(poll.is_pending());
+        drop(task);
+
+        for _ in 0..50 {
+            if !responder.received(IppOperation::CancelJob).is_empty() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        panic!("the started task was not completed");
+    }
+
+    #[test]
+    fn test_dropped_wait() {
+        let responder = responder();
+        let printer = block_on(asynchronous::get_printer_by_name("MockPrinter")).unwrap();
+        let mut job = block_on(printer.print_async(
+            b"endless",
+            PrinterJobOptions {
+                name: Some("endless job"),
+                ..PrinterJobOptions::none()
+            },
+        ))
+        .unwrap();
+        assert_eq!(job.id, 12);
+
+        let polls = || {
+            responder
+                .received(IppOperation::GetJobAttributes)
+                .iter()
+                .filter(|r| {
+                    r.group(IppGroupTag::Operation)
+                        .and_then(|g| g.integer("job-id"))
+                        == Some(12)
+                })
+                .count()
+        };
+
+        // A wait past its timeout fails with the last state
+        let error = block_on(job.wait_until_done_async(Duration::from_millis(600))).unwrap_err();
+        assert!(!error.message.is_empty());
+        assert_eq!(job.status(), PrinterJobState::PROCESSING);
+
+        // A dropped wait stops polling the server
+        {
+            let mut wait = pin!(job.wait_until_done_async(Duration::from_secs(60)));
+            let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+            let deadline = Instant::now() + Duration::from_millis(800);
+            while Instant::now() < deadline {
+                assert!(
+                    wait.as_mut()
+                        .poll(&mut Context::from_waker(&waker))
+                        .is_pending()
+                );
+                thread::park_timeout(Duration::from_millis(50));
+            }
+        }
+
+        thread::sleep(Duration::from_millis(300));
+        let count = polls();
+        thread::sleep(Duration::from_millis(1500));
+        assert_eq!(polls(), count);
+    }
+
+    #[test]
+    fn test_convert() {
+        let converted = block_on(Converter::None.convert_async(b"plain")).unwrap();
+        assert_eq!(converted, b"plain");
+    }
+}
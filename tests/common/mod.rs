@@ -0,0 +1,300 @@
+#![allow(dead_code)]
+
+use printers::ipp::{IppAttribute, IppGroupTag, IppMessage, IppOperation, IppStatusCode, IppValue};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+};
+
+pub type Handler = fn(&IppMessage) -> IppMessage;
+
+/**
+ * Resource prefix requiring an Authorization header and the expected header value
+ */
+type Protection = Option<(&'static str, &'static str)>;
+
+/**
+ * A local IPP responder serving canned responses over HTTP, used as a stand-in for a CUPS server
+ */
+pub struct Responder {
+    pub address: String,
+    pub requests: Arc<Mutex<Vec<IppMessage>>>,
+    handler: Handler,
+    protection: Protection,
+    listener: Mutex<Option<Listener>>,
+    connections: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+/**
+ * Thread accepting the connections of a running responder
+ */
+struct Listener {
+    stopped: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl Responder {
+    pub fn start(handler: Handler) -> Self {
+        Self::start_with(handler, None)
+    }
+
+    /**
+     * Start a responder answering 401 to the requests of a resource without the authorization
+     */
+    pub fn start_protected(
+        handler: Handler,
+        resource: &'static str,
+        authorization: &'static str,
+    ) -> Self {
+        Self::start_with(handler, Some((resource, authorization)))
+    }
+
+    /**
+     * Start a protected responder listening on an address, ex: not on the loopback
+     */
+    pub fn start_protected_on(
+        address: &str,
+        handler: Handler,
+        resource: &'static str,
+        authorization: &'static str,
+    ) -> Self {
+        Self::bind(address, handler, Some((resource, authorization)))
+    }
+
+    fn start_with(handler: Handler, protection: Protection) -> Self {
+        Self::bind("127.0.0.1:0", handler, protection)
+    }
+
+    fn bind(address: &str, handler: Handler, protection: Protection) -> Self {
+        let listener = TcpListener::bind(address).unwrap();
+        let responder = Responder {
+            address: listener.local_addr().unwrap().to_string(),
+            requests: Arc::new(Mutex::new(Vec::new())),
+            handler,
+            protection,
+            listener: Mutex::new(None),
+            connections: Arc::new(Mutex::new(Vec::new())),
+        };
+        responder.listen(listener);
+        responder
+    }
+
+    fn listen(&self, listener: TcpListener) {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (handler, protection) = (self.handler, self.protection);
+        let (received, connections) = (self.requests.clone(), self.connections.clone());
+
+        let stop = stopped.clone();
+        let thread = thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                connections
+                    .lock()
+                    .unwrap()
+                    .push(stream.try_clone().unwrap());
+                let received = received.clone();
+                thread::spawn(move || serve(stream, handler, protection, received));
+            }
+        });
+
+        *self.listener.lock().unwrap() = Some(Listener { stopped, thread });
+    }
+
+    /**
+     * Stop the responder as a restarting server does, connections are refused and the open ones closed
+     */
+    pub fn stop(&self) {
+        if let Some(listener) = self.listener.lock().unwrap().take() {
+            listener.stopped.store(true, Ordering::SeqCst);
+            // Wake the accept loop, which drops the listener
+            let _ = TcpStream::connect(&self.address);
+            listener.thread.join().unwrap();
+        }
+        for connection in self.connections.lock().unwrap().drain(..) {
+            let _ = connection.shutdown(Shutdown::Both);
+        }
+    }
+
+    /**
+     * Start a stopped responder again on the same address
+     */
+    pub fn restart(&self) {
+        self.listen(TcpListener::bind(&self.address).unwrap());
+    }
+
+    /**
+     * Return the received requests of an operation
+     */
+    pub fn received(&self, operation: IppOperation) -> Vec<IppMessage> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.operation() == operation)
+            .cloned()
+            .collect()
+    }
+}
+
+/**
+ * Create an empty response for a request
+ */
+pub fn response(request: &IppMessage, status: IppStatusCode) -> IppMessage {
+    IppMessage::response(status, request.request_id)
+}
+
+/**
+ * Answer CUPS-Get-Default and CUPS-Get-Printers with a single idle queue
+ */
+pub fn printer_response(request: &IppMessage, printer_name: &str) -> Option<IppMessage> {
+    let mut response = response(request, IppStatusCode::SuccessfulOk);
+    let attributes = match request.operation() {
+        IppOperation::CupsGetDefault => vec![IppAttribute::new(
+            "printer-name",
+            IppValue::Name(printer_name.into()),
+        )],
+        IppOperation::CupsGetPrinters => vec![
+            IppAttribute::new("printer-name", IppValue::Name(printer_name.into())),
+            IppAttribute::new(
+                "printer-uri-supported",
+                IppValue::Uri(format!("ipp://localhost/printers/{printer_name}")),
+            ),
+            IppAttribute::new("printer-state", IppValue::Enum(3)),
+            IppAttribute::new("printer-state-reasons", IppValue::Keyword("none".into())),
+        ],
+        _ => return None,
+    };
+
+    for attribute in attributes {
+        response.add(IppGroupTag::Printer, attribute);
+    }
+    Some(response)
+}
+
+fn serve(
+    stream: TcpStream,
+    handler: Handler,
+    protection: Protection,
+    requests: Arc<Mutex<Vec<IppMessage>>>,
+) {
+    let mut writer = stream.try_clone().unwrap();
+    let mut reader = BufReader::new(stream);
+
+    // Keep the connection alive as long as the client sends requests
+    while let Some((method, resource, headers)) = read_head(&mut reader) {
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.clone())
+        };
+
+        let unauthorized = protection.is_some_and(|(protected, authorization)| {
+            resource.starts_with(protected)
+                && header("authorization").is_none_or(|v| v != authorization)
+        });
+        let unauthorized_head = "HTTP/1.1 401 Unauthorized\r\n\
+            WWW-Authenticate: Basic realm=\"CUPS\"\r\n\
+            Content-Length: 0\r\n\r\n";
+
+        // A request expecting 100-continue is refused before its body is sent
+        if header("expect").is_some_and(|v| v.eq_ignore_ascii_case("100-continue")) {
+            if unauthorized {
+                let _ = writer.write_all(unauthorized_head.as_bytes());
+                return;
+            }
+            writer.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").unwrap();
+        }
+
+        let body = if header("transfer-encoding").is_some_and(|v| v.contains("chunked")) {
+            read_chunked(&mut reader)
+        } else {
+            let length = header("content-length")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0);
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            body
+        };
+
+        if unauthorized {
+            if writer.write_all(unauthorized_head.as_bytes()).is_err() {
+                return;
+            }
+            continue;
+        }
+
+        let body = if method == "POST" {
+            let request = IppMessage::decode(&body).unwrap();
+            let response = handler(&request);
+            requests.lock().unwrap().push(request);
+            response.encode()
+        } else {
+            Vec::new()
+        };
+
+        let mut message = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/ipp\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        message.extend_from_slice(&body);
+
+        if writer.write_all(&message).is_err() {
+            return;
+        }
+    }
+}
+
+/**
+ * Method, resource and headers of a request
+ */
+type Head = (String, String, Vec<(String, String)>);
+
+fn read_head(reader: &mut dyn BufRead) -> Option<Head> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).ok()? == 0 {
+        return None;
+    }
+
+    let mut request_line = line.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let resource = request_line.next()?.to_string();
+    let mut headers = Vec::new();
+
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            return Some((method, resource, headers));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+}
+
+fn read_chunked(reader: &mut dyn BufRead) -> Vec<u8> {
+    let mut body = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let size = usize::from_str_radix(line.trim(), 16).unwrap();
+        if size == 0 {
+            reader.read_line(&mut line).unwrap();
+            return body;
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..]).unwrap();
+        reader.read_line(&mut line).unwrap();
+    }
+}
@@ -0,0 +1,256 @@
+mod ipp {
+    use printers::ipp::{
+        IppAttribute, IppDateTime, IppGroupTag, IppMessage, IppOperation, IppResolutionUnits,
+        IppStatusCode, IppValue, IppVersion,
+    };
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_encode_request() {
+        let mut request = IppMessage::request(IppOperation::GetJobAttributes, 7);
+        request.add(
+            IppGroupTag::Operation,
+            IppAttribute::new("job-id", IppValue::Integer(3)),
+        );
+
+        let bytes = request.encode();
+
+        assert_eq!(&bytes[..8], &[2, 0, 0x00, 0x09, 0, 0, 0, 7]);
+        assert_eq!(bytes[8], 0x01);
+        assert_eq!(
+            &bytes[bytes.len() - 16..],
+            &[
+                0x21, 0, 6, b'j', b'o', b'b', b'-', b'i', b'd', 0, 4, 0, 0, 0, 3, 0x03
+            ][..]
+        );
+    }
+
+    #[test]
+    fn test_round_trip_values() {
+        let date = IppDateTime {
+            year: 2024,
+            month: 2,
+            day: 29,
+            hour: 13,
+            minutes: 45,
+            seconds: 10,
+            deci_seconds: 5,
+            utc_offset: -180,
+        };
+
+        let values = vec![
+            IppAttribute::new("integer", IppValue::Integer(-12)),
+            IppAttribute::new("boolean", IppValue::Boolean(true)),
+            IppAttribute::new("enum", IppValue::Enum(4)),
+            IppAttribute::new("octets", IppValue::OctetString(vec![0, 1, 255])),
+            IppAttribute::new("date", IppValue::DateTime(date)),
+            IppAttribute::new(
+                "resolution",
+                IppValue::Resolution {
+                    cross_feed: 600,
+                    feed: 300,
+                    units: IppResolutionUnits::DotsPerInch,
+                },
+            ),
+            IppAttribute::new(
+                "range",
+                IppValue::RangeOfInteger {
+                    lower: 1,
+                    upper: 999,
+                },
+            ),
+            IppAttribute::new(
+                "text-lang",
+                IppValue::TextWithLanguage {
+                    language: "pt-br".into(),
+                    text: "Olá".into(),
+                },
+            ),
+            IppAttribute::new(
+                "name-lang",
+                IppValue::NameWithLanguage {
+                    language: "en".into(),
+                    name: "office".into(),
+                },
+            ),
+            IppAttribute::with_values(
+                "strings",
+                vec![
+                    IppValue::Text("text".into()),
+                    IppValue::Name("name".into()),
+                    IppValue::Keyword("keyword".into()),
+                    IppValue::Uri("ipp://localhost/".into()),
+                    IppValue::UriScheme("ipps".into()),
+                    IppValue::Charset("utf-8".into()),
+                    IppValue::NaturalLanguage("en".into()),
+                    IppValue::MimeMediaType("application/pdf".into()),
+                ],
+            ),
+            IppAttribute::with_values(
+                "out-of-band",
+                vec![
+                    IppValue::Unsupported,
+                    IppValue::Unknown,
+                    IppValue::NoValue,
+                    IppValue::NotSettable,
+                    IppValue::DeleteAttribute,
+                    IppValue::AdminDefine,
+                ],
+            ),
+            IppAttribute::new(
+                "other",
+                IppValue::Other {
+                    tag: 0x4F,
+                    data: vec![1, 2, 3],
+                },
+            ),
+        ];
+
+        let mut message = IppMessage::request(IppOperation::PrintJob, 1);
+        for value in values.iter().cloned() {
+            message.add(IppGroupTag::Job, value);
+        }
+        message.data = b"%PDF-1.7".to_vec();
+
+        let decoded = IppMessage::decode(&message.encode()).unwrap();
+
+        assert_eq!(decoded, message);
+        assert_eq!(decoded.group(IppGroupTag::Job).unwrap().attributes, values);
+    }
+
+    #[test]
+    fn test_round_trip_collection() {
+        let media_size = IppValue::Collection(vec![
+            IppAttribute::new("x-dimension", IppValue::Integer(21000)),
+            IppAttribute::new("y-dimension", IppValue::Integer(29700)),
+        ]);
+
+        let media_col = IppAttribute::with_values(
+            "media-col",
+            vec![
+                IppValue::Collection(vec![
+                    IppAttribute::new("media-size", media_size.clone()),
+                    IppAttribute::with_values(
+                        "media-type",
+                        vec![
+                            IppValue::Keyword("stationery".into()),
+                            IppValue::Keyword("labels".into()),
+                        ],
+                    ),
+                ]),
+                IppValue::Collection(vec![IppAttribute::new("media-size", media_size)]),
+            ],
+        );
+
+        let mut message = IppMessage::response(IppStatusCode::SuccessfulOk, 9);
+        message.add(IppGroupTag::Printer, media_col.clone());
+        message.add(
+            IppGroupTag::Printer,
+            IppAttribute::new("after", IppValue::Boolean(false)),
+        );
+
+        let decoded = IppMessage::decode(&message.encode()).unwrap();
+        let printer = decoded.group(IppGroupTag::Printer).unwrap();
+
+        assert_eq!(printer.get("media-col"), Some(&media_col));
+        assert_eq!(printer.value("after"), Some(&IppValue::Boolean(false)));
+
+        let members = printer.value("media-col").unwrap().as_collection().unwrap();
+        assert_eq!(members[0].name, "media-size");
+    }
+
+    #[test]
+    fn test_decode_groups() {
+        let mut message = IppMessage::response(IppStatusCode::ClientErrorNotFound, 3);
+        message.version = IppVersion::V1_1;
+        message.add(
+            IppGroupTag::Job,
+            IppAttribute::new("job-id", IppValue::Integer(1)),
+        );
+        message
+            .groups
+            .push(printers::ipp::IppAttributeGroup::new(IppGroupTag::Job));
+        message.add(
+            IppGroupTag::Job,
+            IppAttribute::new("job-id", IppValue::Integer(2)),
+        );
+
+        let decoded = IppMessage::decode(&message.encode()).unwrap();
+
+        assert_eq!(decoded.version, IppVersion::V1_1);
+        assert_eq!(decoded.request_id, 3);
+        assert_eq!(decoded.status(), IppStatusCode::ClientErrorNotFound);
+        assert_eq!(decoded.status().keyword(), "client-error-not-found");
+        assert!(!decoded.is_successful());
+
+        let ids: Vec<_> = decoded
+            .groups(IppGroupTag::Job)
+            .filter_map(|g| g.integer("job-id"))
+            .collect();
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert!(IppMessage::decode(&[2, 0, 0]).is_err());
+        assert!(IppMessage::decode(&[2, 0, 0, 0, 0, 0, 0, 1]).is_err());
+        assert!(IppMessage::decode(&[2, 0, 0, 0, 0, 0, 0, 1, 0x21, 0, 1, b'a']).is_err());
+    }
+
+    #[test]
+    fn test_decode_nested_collections() {
+        // A printer attribute made of depth collections, each one the member of the previous
+        let nested = |depth: usize| {
+            let mut bytes = vec![2, 0, 0, 0, 0, 0, 0, 1, 0x04];
+            bytes.extend([0x34, 0, 1, b'n', 0, 0]);
+            for _ in 1..depth {
+                bytes.extend([0x4a, 0, 0, 0, 1, b'm', 0x34, 0, 0, 0, 0]);
+            }
+            bytes.extend([0x4a, 0, 0, 0, 1, b'm', 0x21, 0, 0, 0, 4, 0, 0, 0, 1]);
+            for _ in 0..depth {
+                bytes.extend([0x37, 0, 0, 0, 0]);
+            }
+            bytes.push(0x03);
+            bytes
+        };
+
+        assert!(IppMessage::decode(&nested(32)).is_ok());
+        let error = IppMessage::decode(&nested(33)).unwrap_err();
+        assert!(error.message.contains("nested"));
+
+        // A hostile message does not exhaust the stack
+        assert!(IppMessage::decode(&nested(100_000)).is_err());
+    }
+
+    #[test]
+    fn test_date_time() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_214_310);
+        let date = IppDateTime::from_system_time(time);
+
+        assert_eq!(
+            (date.year, date.month, date.day, date.hour, date.minutes),
+            (2024, 2, 29, 13, 45)
+        );
+        assert_eq!(date.to_system_time(), time);
+
+        let shifted = IppDateTime {
+            hour: 10,
+            utc_offset: -180,
+            ..date
+        };
+        assert_eq!(shifted.to_system_time(), time);
+    }
+
+    #[test]
+    fn test_codes() {
+        assert_eq!(u16::from(IppOperation::CupsGetDocument), 0x4027);
+        assert_eq!(
+            IppOperation::from(0x000B),
+            IppOperation::GetPrinterAttributes
+        );
+        assert_eq!(IppOperation::from(0x7777), IppOperation::Other(0x7777));
+        assert_eq!(IppStatusCode::from(0x0507), IppStatusCode::ServerErrorBusy);
+        assert_eq!(IppOperation::CupsGetDocument.keyword(), "CUPS-Get-Document");
+        assert_eq!(IppOperation::Other(0x7777).keyword(), "0x7777");
+    }
+}
@@ -0,0 +1,342 @@
+#![cfg(feature = "ipp-client")]
+
+mod common;
+
+mod ipp_client {
+    use crate::common::{Responder, response};
+    use printers::{
+        common::base::{
+            errors::PrintersFailure,
+            job::{PrinterJobOptions, PrinterJobState},
+            printer::{
+                PrinterState, PrinterStateReason, PrinterStateReasonKind,
+                PrinterStateReasonSeverity,
+            },
+        },
+        get_default_printer, get_printer_by_name, get_printers,
+        ipp::{
+            IppAttribute, IppGroupTag, IppMessage, IppOperation, IppStatusCode, IppValue,
+            IppVersion,
+        },
+        server::{Credentials, PrintServer},
+    };
+    use std::{
+        env,
+        net::{IpAddr, UdpSocket},
+        sync::OnceLock,
+    };
+
+    static RESPONDER: OnceLock<Responder> = OnceLock::new();
+
+    /**
+     * Start a local IPP responder and point the client to it
+     */
+    fn responder() -> &'static Responder {
+        RESPONDER.get_or_init(|| {
+            let responder = Responder::start(handle);
+            // SAFETY: set once before any request of this test binary reads it
+            unsafe { env::set_var("CUPS_SERVER", &responder.address) };
+            responder
+        })
+    }
+
+    fn handle(request: &IppMessage) -> IppMessage {
+        // The jobs named legacy go to a printer only speaking IPP/1.1
+        let legacy = request
+            .group(IppGroupTag::Operation)
+            .is_some_and(|g| g.string("job-name").starts_with("legacy"));
+        if legacy && request.version != IppVersion::V1_1 {
+            return response(request, IppStatusCode::ServerErrorVersionNotSupported);
+        }
+
+        let mut response = response(request, IppStatusCode::SuccessfulOk);
+        let job_id = request
+            .group(IppGroupTag::Operation)
+            .and_then(|g| g.integer("job-id"));
+
+        match request.operation() {
+            IppOperation::CupsGetDefault => {
+                response.add(
+                    IppGroupTag::Printer,
+                    IppAttribute::new("printer-name", IppValue::Name("MockPrinter".into())),
+                );
+            }
+            IppOperation::CupsGetPrinters => {
+                for attribute in [
+                    IppAttribute::new("printer-name", IppValue::Name("MockPrinter".into())),
+                    IppAttribute::new("printer-info", IppValue::Text("Mock Printer".into())),
+                    IppAttribute::new("printer-location", IppValue::Text("Lab".into())),
+                    IppAttribute::new("printer-state", IppValue::Enum(3)),
+                    IppAttribute::with_values(
+                        "printer-state-reasons",
+                        vec![
+                            IppValue::Keyword("toner-low-warning".into()),
+                            IppValue::Keyword("media-jam".into()),
+                            IppValue::Keyword("com.vendor-thing-report".into()),
+                        ],
+                    ),
+                    IppAttribute::new("printer-is-shared", IppValue::Boolean(true)),
+                ] {
+                    response.add(IppGroupTag::Printer, attribute);
+                }
+            }
+            IppOperation::PrintJob => {
+                response.add(
+                    IppGroupTag::Job,
+                    IppAttribute::new("job-id", IppValue::Integer(42)),
+                );
+            }
+            IppOperation::GetJobs => {
+                for attribute in [
+                    IppAttribute::new("job-id", IppValue::Integer(42)),
+                    IppAttribute::new("job-name", IppValue::Name("mock job".into())),
+                    IppAttribute::new("job-state", IppValue::Enum(4)),
+                    IppAttribute::new(
+                        "job-printer-uri",
+                        IppValue::Uri("ipp://localhost/printers/MockPrinter".into()),
+                    ),
+                    IppAttribute::new("time-at-creation", IppValue::Integer(1_700_000_000)),
+                ] {
+                    response.add(IppGroupTag::Job, attribute);
+                }
+            }
+            IppOperation::CancelJob
+            | IppOperation::HoldJob
+            | IppOperation::ReleaseJob
+            | IppOperation::RestartJob => {
+                if job_id != Some(42) {
+                    response.code = IppStatusCode::ClientErrorNotFound.into();
+                }
+            }
+            _ => response.code = IppStatusCode::ServerErrorOperationNotSupported.into(),
+        }
+
+        response
+    }
+
+    #[test]
+    fn test_get_printers() {
+        responder();
+
+        let printers = get_printers();
+        assert_eq!(printers.len(), 1);
+
+        let printer = &printers[0];
+        assert_eq!(printer.name, "Mock Printer");
+        assert_eq!(printer.system_name, "MockPrinter");
+        assert_eq!(printer.location, "Lab");
+        assert_eq!(printer.state, PrinterState::READY);
+        assert!(printer.is_default);
+        assert!(printer.is_shared);
+
+        assert_eq!(
+            printer.state_reasons,
+            vec![
+                PrinterStateReason {
+                    kind: PrinterStateReasonKind::TonerLow,
+                    severity: PrinterStateReasonSeverity::Warning,
+                },
+                PrinterStateReason {
+                    kind: PrinterStateReasonKind::MediaJam,
+                    severity: PrinterStateReasonSeverity::Error,
+                },
+                PrinterStateReason {
+                    kind: PrinterStateReasonKind::Other("com.vendor-thing".into()),
+                    severity: PrinterStateReasonSeverity::Report,
+                },
+            ]
+        );
+        assert_eq!(printer.state_reasons[1].keyword(), "media-jam-error");
+    }
+
+    #[test]
+    fn test_offline_state_reason() {
+        let reason = PrinterStateReason::from_keyword("offline-report");
+
+        assert_eq!(reason.kind, PrinterStateReasonKind::Offline);
+        assert_eq!(reason.severity, PrinterStateReasonSeverity::Report);
+        assert_eq!(reason.kind.keyword(), "offline");
+    }
+
+    #[test]
+    fn test_get_default_printer() {
+        responder();
+
+        let printer = get_default_printer();
+        assert!(printer.is_some_and(|p| p.system_name == "MockPrinter"));
+        assert!(get_printer_by_name("Mock Printer").is_some());
+        assert!(get_printer_by_name("Missing").is_none());
+    }
+
+    #[test]
+    fn test_print() {
+        let responder = responder();
+
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+        let result = printer.print(
+            b"hello from printers",
+            PrinterJobOptions {
+                name: Some("ipp client test"),
+                raw_properties: &[("document-format", "text/plain"), ("copies", "2")],
+                ..PrinterJobOptions::none()
+            },
+        );
+
+        assert_eq!(result.unwrap().id, 42);
+
+        let requests = responder.received(IppOperation::PrintJob);
+        let request = requests
+            .iter()
+            .find(|r| {
+                r.group(IppGroupTag::Operation).unwrap().string("job-name") == "ipp client test"
+            })
+            .unwrap();
+
+        let operation = request.group(IppGroupTag::Operation).unwrap();
+        let job = request.group(IppGroupTag::Job).unwrap();
+
+        assert_eq!(request.data, b"hello from printers");
+        assert_eq!(operation.string("document-format"), "text/plain");
+        assert_eq!(job.value("copies"), Some(&IppValue::Integer(2)));
+    }
+
+    #[test]
+    fn test_print_stream_downgrade() {
+        let responder = responder();
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+        let options = |name| PrinterJobOptions {
+            name: Some(name),
+            ..PrinterJobOptions::none()
+        };
+
+        // A short document is kept to be sent again as IPP/1.1
+        let job = printer
+            .print_stream(&b"short document"[..], options("legacy short"))
+            .unwrap();
+        assert_eq!(job.id, 42);
+        let requests: Vec<_> = responder
+            .received(IppOperation::PrintJob)
+            .into_iter()
+            .filter(|r| {
+                r.group(IppGroupTag::Operation).unwrap().string("job-name") == "legacy short"
+            })
+            .collect();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].version, IppVersion::V1_1);
+        assert_eq!(requests[1].data, b"short document");
+
+        // A long document was streamed and cannot be sent again
+        let data = vec![b'x'; 200_000];
+        let error = printer
+            .print_stream(data.as_slice(), options("legacy long"))
+            .unwrap_err();
+        assert_eq!(
+            error.status,
+            Some(IppStatusCode::ServerErrorVersionNotSupported)
+        );
+    }
+
+    #[test]
+    fn test_print_stream_authentication() {
+        responder();
+        // admin:secret
+        let protected = Responder::start_protected(handle, "/printers/", "Basic YWRtaW46c2VjcmV0");
+        let (host, port) = protected.address.rsplit_once(':').unwrap();
+        let printer = |credentials| {
+            PrintServer {
+                port: port.parse().unwrap(),
+                credentials,
+                ..PrintServer::new(host)
+            }
+            .get_printer_by_name("MockPrinter")
+            .unwrap()
+        };
+        let data = b"protected".repeat(20_000);
+
+        // The server refuses the request before the document is read
+        let mut reader = data.as_slice();
+        let error = printer(None)
+            .print_stream(&mut reader, PrinterJobOptions::none())
+            .unwrap_err();
+        assert_eq!(error.failure, PrintersFailure::AuthenticationRequired);
+        assert_eq!(reader.len(), data.len());
+
+        let job = printer(Some(Credentials::new("admin", "secret")))
+            .print_stream(data.as_slice(), PrinterJobOptions::none())
+            .unwrap();
+        assert_eq!(job.id, 42);
+        let requests = protected.received(IppOperation::PrintJob);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].data, data);
+    }
+
+    /**
+     * An address of this machine outside of the loopback, found from the route to a test network
+     */
+    fn local_address() -> Option<IpAddr> {
+        let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+        socket.connect("203.0.113.1:9").ok()?;
+        let ip = socket.local_addr().ok()?.ip();
+        (!ip.is_loopback() && !ip.is_unspecified()).then_some(ip)
+    }
+
+    #[test]
+    fn test_remote_authentication() {
+        responder();
+        // Without a network interface there is no remote address to try
+        let Some(ip) = local_address() else {
+            return;
+        };
+
+        // admin:secret
+        let protected = Responder::start_protected_on(
+            &format!("{ip}:0"),
+            handle,
+            "/printers/",
+            "Basic YWRtaW46c2VjcmV0",
+        );
+        let (_, port) = protected.address.rsplit_once(':').unwrap();
+        let printer = PrintServer {
+            port: port.parse().unwrap(),
+            credentials: Some(Credentials::new("admin", "secret")),
+            ..PrintServer::new(&ip.to_string())
+        }
+        .get_printer_by_name("MockPrinter")
+        .unwrap();
+
+        // The password is not sent in clear text to another machine
+        let error = printer
+            .print(b"remote", PrinterJobOptions::none())
+            .unwrap_err();
+        assert_eq!(error.failure, PrintersFailure::AuthenticationRequired);
+        assert!(error.message.contains("clear text"));
+        assert!(protected.received(IppOperation::PrintJob).is_empty());
+    }
+
+    #[test]
+    fn test_get_active_jobs() {
+        responder();
+
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+        let jobs = printer.get_active_jobs();
+
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].id, 42);
+        assert_eq!(jobs[0].name, "mock job");
+        assert_eq!(jobs[0].state, PrinterJobState::PAUSED);
+        assert_eq!(jobs[0].printer_name, "MockPrinter");
+    }
+
+    #[test]
+    fn test_manage_job() {
+        responder();
+
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+
+        assert!(printer.pause_job(42).is_ok());
+        assert!(printer.resume_job(42).is_ok());
+        assert!(printer.restart_job(42).is_ok());
+        assert!(printer.cancel_job(42).is_ok());
+        assert!(printer.cancel_job(7).is_err());
+    }
+}
@@ -23,6 +23,18 @@ pub mod job {
         }
     }
 
+    #[test]
+    fn test_get_job() {
+        for printer in get_printers() {
+            for job in printer.get_active_jobs() {
+                if let Some(found) = printer.get_job(job.id) {
+                    assert_eq!(found.id, job.id);
+                    assert_eq!(found.printer_name, printer.system_name);
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_manage_job() {
         let printer = if let Some(printer) = get_default_printer() {
@@ -31,8 +43,8 @@ pub mod job {
             panic!("Default printer must be available")
         };
 
-        let job_id = if let Ok(job_id) = printer.print(b"test", PrinterJobOptions::none()) {
-            job_id
+        let job_id = if let Ok(job) = printer.print(b"test", PrinterJobOptions::none()) {
+            job.id
         } else {
             panic!("Cannot create test job")
         };
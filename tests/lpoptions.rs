@@ -0,0 +1,17 @@
+#![cfg(target_family = "unix")]
+
+mod lpoptions {
+    use printers::{common::base::printer::PrinterScope, lpoptions};
+    use std::env;
+
+    #[test]
+    fn test_file_path_without_home() {
+        // SAFETY: the only test of this binary
+        unsafe { env::remove_var("HOME") };
+
+        // The home directory comes from the password database, never from the working directory
+        let path = lpoptions::file_path(PrinterScope::User).unwrap();
+        assert!(path.is_absolute());
+        assert!(path.ends_with(".cups/lpoptions"));
+    }
+}
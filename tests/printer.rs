@@ -27,6 +27,16 @@ mod printer {
         }
     }
 
+    #[test]
+    fn test_capabilities() {
+        for printer in get_printers() {
+            if let Ok(capabilities) = printer.capabilities() {
+                assert!(*capabilities.copies.start() >= 1);
+                assert!(!capabilities.color_modes.is_empty());
+            }
+        }
+    }
+
     #[test]
     fn test_print() {
         for printer in get_printers() {
@@ -36,11 +46,13 @@ mod printer {
                     converter: common::converters::Converter::None,
                     name: None,
                     raw_properties: &[("copies", "1")],
+                    ..common::base::job::PrinterJobOptions::none()
                 },
             );
 
-            if let Ok(job_id) = result {
-                assert!(job_id > 0);
+            if let Ok(job) = result {
+                assert!(job.id > 0);
+                assert_eq!(job.printer_name, printer.system_name);
             }
         }
     }
@@ -0,0 +1,98 @@
+#![cfg(any(target_family = "unix", feature = "ipp-client"))]
+
+mod common;
+
+mod retry {
+    use crate::common::{Responder, printer_response, response};
+    use printers::{
+        common::base::{job::PrinterJobOptions, printer::Printer, retry::RetryPolicy},
+        get_printer_by_name,
+        ipp::{IppAttribute, IppGroupTag, IppMessage, IppOperation, IppStatusCode, IppValue},
+        server::PrintServer,
+    };
+    use std::{env, sync::OnceLock, thread, time::Duration};
+
+    static RESPONDER: OnceLock<Responder> = OnceLock::new();
+
+    /**
+     * Start a local IPP responder and point the backend to it
+     */
+    fn responder() -> &'static Responder {
+        RESPONDER.get_or_init(|| {
+            let responder = Responder::start(handle);
+            // SAFETY: set once before any request of this test binary reads it
+            unsafe { env::set_var("CUPS_SERVER", &responder.address) };
+            responder
+        })
+    }
+
+    fn handle(request: &IppMessage) -> IppMessage {
+        if let Some(response) = printer_response(request, "MockPrinter") {
+            return response;
+        }
+
+        let mut response = response(request, IppStatusCode::SuccessfulOk);
+        if matches!(
+            request.operation(),
+            IppOperation::PrintJob | IppOperation::CreateJob
+        ) {
+            response.add(
+                IppGroupTag::Job,
+                IppAttribute::new("job-id", IppValue::Integer(7)),
+            );
+        }
+        response
+    }
+
+    fn submissions(responder: &Responder) -> usize {
+        responder.received(IppOperation::PrintJob).len()
+            + responder.received(IppOperation::CreateJob).len()
+    }
+
+    /**
+     * Print while the server restarts, the submission is sent again once it is back
+     */
+    fn print_during_restart(printer: &Printer) {
+        let responder = responder();
+        let options = || PrinterJobOptions {
+            retry: Some(RetryPolicy {
+                initial_backoff: Duration::from_millis(200),
+                max_backoff: Duration::from_millis(200),
+                ..RetryPolicy::new(10)
+            }),
+            ..PrinterJobOptions::none()
+        };
+
+        // The connections are opened before the restart
+        printer.print(b"before", options()).unwrap();
+        let before = submissions(responder);
+
+        responder.stop();
+        let restart = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(500));
+            responder.restart();
+        });
+
+        let job = printer.print(b"during", options()).unwrap();
+        restart.join().unwrap();
+        assert_eq!(job.id, 7);
+        // Attempts made while down never reached the server, the job is submitted once
+        assert_eq!(submissions(responder), before + 1);
+    }
+
+    #[test]
+    fn test_server_restart() {
+        let responder = responder();
+
+        // The default server, libcups keeps its own connection
+        print_during_restart(&get_printer_by_name("MockPrinter").unwrap());
+
+        // A server of the application, its connection is cached
+        let (host, port) = responder.address.rsplit_once(':').unwrap();
+        let server = PrintServer {
+            port: port.parse().unwrap(),
+            ..PrintServer::new(host)
+        };
+        print_during_restart(&server.get_printer_by_name("MockPrinter").unwrap());
+    }
+}
@@ -0,0 +1,1708 @@
+#![cfg(any(target_family = "unix", feature = "ipp-client"))]
+
+mod common;
+
+mod server {
+    use crate::common::{Responder, printer_response, response};
+    use printers::{
+        admin::{PrinterConfig, add_printer, delete_printer, modify_printer},
+        common::{
+            base::{
+                errors::{PrintersError, PrintersFailure},
+                job::{
+                    CancelJobsFilter, JobChanges, PrinterJobOptions, PrinterJobState,
+                    PrinterJobStateReason,
+                },
+                options::{
+                    JobHoldUntil, JobPassword, PrintColorMode, PrintFinishing, PrintOrientation,
+                    PrintQuality, PrintResolution, PrintSides,
+                },
+                printer::{Printer, PrinterScope},
+                retry::{RetryClass, RetryPolicy},
+                supplies::{Supply, SupplyLevel, SupplyType},
+            },
+            converters::{Converter, GhostscriptConverterOptions},
+        },
+        get_default_printer, get_job, get_printer_by_name,
+        ipp::{
+            IppAttribute, IppAttributeGroup, IppGroupTag, IppMessage, IppOperation,
+            IppResolutionUnits, IppStatusCode, IppValue,
+        },
+        lpoptions,
+        server::{Credentials, PrintServer},
+        set_default_printer,
+    };
+    use std::{
+        env,
+        error::Error,
+        fs,
+        io::{self, ErrorKind, Read},
+        net::TcpListener,
+        sync::{Arc, Mutex, OnceLock},
+        time::{Duration, Instant},
+    };
+
+    static RESPONDER: OnceLock<Responder> = OnceLock::new();
+
+    /**
+     * Start a local IPP responder and point the backend to it
+     */
+    fn responder() -> &'static Responder {
+        RESPONDER.get_or_init(|| {
+            let responder = Responder::start(handle);
+            // lpoptions files of the user and system scopes are kept out of the real ones
+            let root = env::temp_dir().join(format!("printers-server-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).unwrap();
+
+            // SAFETY: set once before any request of this test binary reads them
+            unsafe {
+                env::set_var("CUPS_SERVER", &responder.address);
+                env::set_var("HOME", &root);
+                env::set_var("CUPS_SERVERROOT", &root);
+            }
+            responder
+        })
+    }
+
+    /**
+     * Queues created through the admin module, as name and location
+     */
+    static QUEUES: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+    fn handle(request: &IppMessage) -> IppMessage {
+        if let Some(response) = busy_response(request) {
+            return response;
+        }
+        if let Some(mut response) = printer_response(request, "MockPrinter") {
+            if request.operation() == IppOperation::CupsGetPrinters {
+                add_queues(&mut response);
+            }
+            return response;
+        }
+
+        let mut response = response(request, IppStatusCode::SuccessfulOk);
+        let job_id = request
+            .group(IppGroupTag::Operation)
+            .and_then(|g| g.integer("job-id"));
+
+        match request.operation() {
+            IppOperation::GetPrinterAttributes => add_printer_attributes(&mut response),
+            IppOperation::PrintJob | IppOperation::CreateJob => {
+                let job_name = request
+                    .group(IppGroupTag::Operation)
+                    .map(|g| g.string("job-name"))
+                    .unwrap_or_default();
+                let id = match job_name.as_str() {
+                    "aborted job" => 9,
+                    "failing job" => 13,
+                    "vanished job" => 15,
+                    _ => 7,
+                };
+                response.add(
+                    IppGroupTag::Job,
+                    IppAttribute::new("job-id", IppValue::Integer(id)),
+                );
+            }
+            IppOperation::SendDocument => {}
+            IppOperation::GetJobs
+                if request
+                    .group(IppGroupTag::Operation)
+                    .is_some_and(|g| g.string("printer-uri").ends_with("/Locked")) =>
+            {
+                response.code = IppStatusCode::ClientErrorForbidden.into();
+            }
+            IppOperation::GetJobs
+                if request
+                    .group(IppGroupTag::Operation)
+                    .is_some_and(|g| g.string("printer-uri").ends_with("/Held")) =>
+            {
+                // Job 15 cannot be released, job 16 is held until the night
+                for (id, hold_until) in [(11, "indefinite"), (15, "indefinite"), (16, "night")] {
+                    add_job_attributes(&mut response, id, 4, &["job-hold-until-specified"]);
+                    response.add(
+                        IppGroupTag::Job,
+                        IppAttribute::new("job-hold-until", IppValue::Keyword(hold_until.into())),
+                    );
+                }
+            }
+            IppOperation::GetJobs if printer_uri(request).ends_with("/Team%20Printer%20%232") => {
+                for attribute in [
+                    IppAttribute::new("job-id", IppValue::Integer(21)),
+                    IppAttribute::new("job-state", IppValue::Enum(3)),
+                    IppAttribute::new(
+                        "job-printer-uri",
+                        IppValue::Uri("ipp://localhost/printers/Team%20Printer%20%232".into()),
+                    ),
+                ] {
+                    response.add(IppGroupTag::Job, attribute);
+                }
+            }
+            IppOperation::GetJobs => {
+                add_job_attributes(&mut response, 7, 9, &["job-completed-successfully"]);
+                add_job_attributes(&mut response, 11, 4, &["job-password-wait"]);
+                response.add(
+                    IppGroupTag::Job,
+                    IppAttribute::new("job-hold-until", IppValue::Keyword("indefinite".into())),
+                );
+            }
+            IppOperation::GetJobAttributes => {
+                let job_uri = request
+                    .group(IppGroupTag::Operation)
+                    .map(|g| g.string("job-uri"))
+                    .unwrap_or_default();
+
+                if job_id == Some(7) || job_uri.ends_with("/jobs/7") {
+                    add_job_attributes(
+                        &mut response,
+                        7,
+                        9,
+                        &["job-completed-successfully", "vendor-reason"],
+                    );
+                } else if job_id == Some(13) || job_uri.ends_with("/jobs/13") {
+                    response.code = IppStatusCode::ServerErrorInternalError.into();
+                } else if job_id == Some(9) || job_uri.ends_with("/jobs/9") {
+                    add_job_attributes(&mut response, 9, 8, &["aborted-by-system"]);
+                } else if job_id == Some(11) {
+                    add_job_attributes(&mut response, 11, 4, &["job-hold-until-specified"]);
+                    response.add(
+                        IppGroupTag::Job,
+                        IppAttribute::new("job-hold-until", IppValue::Keyword("night".into())),
+                    );
+                } else {
+                    response.code = IppStatusCode::ClientErrorNotFound.into();
+                }
+            }
+            IppOperation::CancelJob | IppOperation::HoldJob | IppOperation::ReleaseJob => {
+                if job_id != Some(7) && job_id != Some(11) {
+                    response.code = IppStatusCode::ClientErrorNotFound.into();
+                }
+            }
+            IppOperation::CupsGetDocument => {
+                let document_number = request
+                    .group(IppGroupTag::Operation)
+                    .and_then(|g| g.integer("document-number"));
+
+                if job_id == Some(7) && document_number == Some(1) {
+                    for attribute in [
+                        IppAttribute::new(
+                            "document-format",
+                            IppValue::MimeMediaType("text/plain".into()),
+                        ),
+                        IppAttribute::new("document-name", IppValue::Name("report.txt".into())),
+                    ] {
+                        response.add(IppGroupTag::Operation, attribute);
+                    }
+                    response.data = b"quarterly report".repeat(1024);
+                } else {
+                    response.code = IppStatusCode::ClientErrorNotFound.into();
+                }
+            }
+            IppOperation::SetJobAttributes => match job_id {
+                Some(7) => {}
+                Some(9) => {
+                    response.code = IppStatusCode::ClientErrorNotPossible.into();
+                    response.add(
+                        IppGroupTag::Operation,
+                        IppAttribute::new(
+                            "status-message",
+                            IppValue::Text("Job #9 is finished and cannot be altered.".into()),
+                        ),
+                    );
+                }
+                _ => response.code = IppStatusCode::ClientErrorNotFound.into(),
+            },
+            IppOperation::CupsMoveJob => {
+                let target = request
+                    .group(IppGroupTag::Job)
+                    .map(|g| g.string("job-printer-uri"))
+                    .unwrap_or_default();
+
+                if target.ends_with("/printers/RejectingPrinter") {
+                    response.code = IppStatusCode::ServerErrorNotAcceptingJobs.into();
+                } else if !target.ends_with("/printers/OtherPrinter")
+                    || job_id.is_some_and(|id| id != 7)
+                {
+                    response.code = IppStatusCode::ClientErrorNotFound.into();
+                }
+            }
+            IppOperation::CupsAddModifyPrinter | IppOperation::CupsDeletePrinter
+                if !printer_uri(request).ends_with("/printers/MockPrinter") =>
+            {
+                let name = printer_uri(request)
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+                let mut queues = QUEUES.lock().unwrap();
+                let index = queues.iter().position(|(n, _)| *n == name);
+
+                match (request.operation(), index) {
+                    (IppOperation::CupsDeletePrinter, Some(index)) => {
+                        queues.remove(index);
+                    }
+                    (IppOperation::CupsAddModifyPrinter, index) => {
+                        let printer = request.group(IppGroupTag::Printer).unwrap();
+                        let location = printer.string("printer-location");
+                        match index {
+                            Some(_) if location.is_empty() => {}
+                            Some(index) => queues[index].1 = location,
+                            None => queues.push((name, location)),
+                        }
+                    }
+                    _ => response.code = IppStatusCode::ClientErrorNotFound.into(),
+                }
+            }
+            IppOperation::PausePrinter
+            | IppOperation::ResumePrinter
+            | IppOperation::CancelJobs
+            | IppOperation::CancelMyJobs
+            | IppOperation::PurgeJobs
+            | IppOperation::CupsAcceptJobs
+            | IppOperation::CupsRejectJobs
+            | IppOperation::CupsSetDefault
+            | IppOperation::CupsAddModifyPrinter => {
+                let printer_uri = request
+                    .group(IppGroupTag::Operation)
+                    .map(|g| g.string("printer-uri"))
+                    .unwrap_or_default();
+
+                if !printer_uri.ends_with("/printers/MockPrinter") {
+                    response.code = IppStatusCode::ClientErrorNotFound.into();
+                }
+            }
+            _ => response.code = IppStatusCode::ServerErrorOperationNotSupported.into(),
+        }
+
+        response
+    }
+
+    /**
+     * Job requests of the busy user still answered with server-error-busy
+     */
+    static BUSY: Mutex<u32> = Mutex::new(0);
+
+    fn busy_response(request: &IppMessage) -> Option<IppMessage> {
+        let job_operation = matches!(
+            request.operation(),
+            IppOperation::PrintJob
+                | IppOperation::CreateJob
+                | IppOperation::HoldJob
+                | IppOperation::ReleaseJob
+                | IppOperation::CancelJob
+                | IppOperation::SetJobAttributes
+        );
+        let mut busy = BUSY.lock().unwrap();
+        if !job_operation || *busy == 0 || requesting_user_name(request) != "busy" {
+            return None;
+        }
+        *busy -= 1;
+        Some(response(request, IppStatusCode::ServerErrorBusy))
+    }
+
+    fn requesting_user_name(request: &IppMessage) -> String {
+        request
+            .group(IppGroupTag::Operation)
+            .map(|g| g.string("requesting-user-name"))
+            .unwrap_or_default()
+    }
+
+    fn printer_uri(request: &IppMessage) -> String {
+        request
+            .group(IppGroupTag::Operation)
+            .map(|g| g.string("printer-uri"))
+            .unwrap_or_default()
+    }
+
+    fn add_queues(response: &mut IppMessage) {
+        for (name, location) in QUEUES.lock().unwrap().iter() {
+            response
+                .groups
+                .push(IppAttributeGroup::new(IppGroupTag::Printer));
+            for attribute in [
+                IppAttribute::new("printer-name", IppValue::Name(name.clone())),
+                IppAttribute::new(
+                    "printer-uri-supported",
+                    IppValue::Uri(format!("ipp://localhost/printers/{name}")),
+                ),
+                IppAttribute::new("printer-location", IppValue::Text(location.clone())),
+                IppAttribute::new("printer-state", IppValue::Enum(3)),
+            ] {
+                response.add(IppGroupTag::Printer, attribute);
+            }
+        }
+    }
+
+    fn add_job_attributes(response: &mut IppMessage, id: i32, state: i32, reasons: &[&str]) {
+        response
+            .groups
+            .push(IppAttributeGroup::new(IppGroupTag::Job));
+        for attribute in [
+            IppAttribute::new("job-id", IppValue::Integer(id)),
+            IppAttribute::new("job-name", IppValue::Name("typed options".into())),
+            IppAttribute::new("job-state", IppValue::Enum(state)),
+            IppAttribute::with_values(
+                "job-state-reasons",
+                reasons
+                    .iter()
+                    .map(|v| IppValue::Keyword(v.to_string()))
+                    .collect(),
+            ),
+            IppAttribute::new(
+                "job-printer-uri",
+                IppValue::Uri("ipp://localhost/printers/MockPrinter".into()),
+            ),
+            IppAttribute::new(
+                "job-printer-state-message",
+                IppValue::Text("Printing page 2".into()),
+            ),
+            IppAttribute::new("job-originating-user-name", IppValue::Name("alice".into())),
+            IppAttribute::new("job-k-octets", IppValue::Integer(3)),
+            IppAttribute::new("job-priority", IppValue::Integer(50)),
+            IppAttribute::new("job-impressions-completed", IppValue::Integer(4)),
+            IppAttribute::new("job-media-sheets-completed", IppValue::Integer(2)),
+            IppAttribute::new("number-of-documents", IppValue::Integer(1)),
+        ] {
+            response.add(IppGroupTag::Job, attribute);
+        }
+    }
+
+    fn add_printer_attributes(response: &mut IppMessage) {
+        let keywords = |values: &[&str]| {
+            values
+                .iter()
+                .map(|v| IppValue::Keyword(v.to_string()))
+                .collect()
+        };
+
+        for attribute in [
+            IppAttribute::with_values(
+                "media-supported",
+                keywords(&["iso_a4_210x297mm", "na_letter_8.5x11in"]),
+            ),
+            IppAttribute::with_values(
+                "sides-supported",
+                keywords(&["one-sided", "two-sided-long-edge"]),
+            ),
+            IppAttribute::with_values(
+                "print-color-mode-supported",
+                keywords(&["monochrome", "color", "unknown-mode"]),
+            ),
+            IppAttribute::with_values(
+                "printer-resolution-supported",
+                vec![
+                    IppValue::Resolution {
+                        cross_feed: 600,
+                        feed: 600,
+                        units: IppResolutionUnits::DotsPerInch,
+                    },
+                    IppValue::Resolution {
+                        cross_feed: 118,
+                        feed: 118,
+                        units: IppResolutionUnits::DotsPerCentimeter,
+                    },
+                ],
+            ),
+            IppAttribute::with_values(
+                "document-format-supported",
+                vec![
+                    IppValue::MimeMediaType("application/pdf".into()),
+                    IppValue::MimeMediaType("image/jpeg".into()),
+                ],
+            ),
+            IppAttribute::new(
+                "copies-supported",
+                IppValue::RangeOfInteger {
+                    lower: 1,
+                    upper: 99,
+                },
+            ),
+            IppAttribute::with_values(
+                "finishings-supported",
+                vec![IppValue::Enum(3), IppValue::Enum(20), IppValue::Enum(90)],
+            ),
+            IppAttribute::with_values(
+                "marker-names",
+                vec![
+                    IppValue::Name("Black Toner".into()),
+                    IppValue::Name("Cyan Toner".into()),
+                    IppValue::Name("Waste Toner Box".into()),
+                ],
+            ),
+            IppAttribute::with_values(
+                "marker-colors",
+                vec![
+                    IppValue::Name("#000000".into()),
+                    IppValue::Name("#00FFFF".into()),
+                    IppValue::Name("none".into()),
+                ],
+            ),
+            IppAttribute::with_values(
+                "marker-levels",
+                vec![
+                    IppValue::Integer(80),
+                    IppValue::Integer(-3),
+                    IppValue::Integer(-2),
+                ],
+            ),
+            IppAttribute::with_values(
+                "marker-low-levels",
+                vec![
+                    IppValue::Integer(10),
+                    IppValue::Integer(10),
+                    IppValue::Integer(-1),
+                ],
+            ),
+            IppAttribute::with_values(
+                "marker-high-levels",
+                vec![
+                    IppValue::Integer(100),
+                    IppValue::Integer(100),
+                    IppValue::Integer(90),
+                ],
+            ),
+            IppAttribute::with_values("marker-types", keywords(&["toner", "toner", "waste-toner"])),
+        ] {
+            response.add(IppGroupTag::Printer, attribute);
+        }
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let responder = responder();
+
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+        let capabilities = printer.capabilities().unwrap();
+
+        assert_eq!(
+            capabilities.media,
+            vec!["iso_a4_210x297mm", "na_letter_8.5x11in"]
+        );
+        assert_eq!(
+            capabilities.sides,
+            vec![PrintSides::OneSided, PrintSides::TwoSidedLongEdge]
+        );
+        assert_eq!(
+            capabilities.color_modes,
+            vec![PrintColorMode::Monochrome, PrintColorMode::Color]
+        );
+        assert_eq!(
+            capabilities.resolutions,
+            vec![
+                PrintResolution::new(600, 600),
+                PrintResolution::new(300, 300)
+            ]
+        );
+        assert_eq!(
+            capabilities.document_formats,
+            vec!["application/pdf", "image/jpeg"]
+        );
+        assert_eq!(capabilities.copies, 1..=99);
+        assert_eq!(
+            capabilities.finishings,
+            vec![
+                PrintFinishing::None,
+                PrintFinishing::StapleTopLeft,
+                PrintFinishing::Other(90)
+            ]
+        );
+
+        let requests = responder.received(IppOperation::GetPrinterAttributes);
+        let operation = requests
+            .iter()
+            .map(|r| r.group(IppGroupTag::Operation).unwrap())
+            .find(|g| {
+                g.strings("requested-attributes")
+                    .contains(&"media-supported".to_string())
+            })
+            .unwrap();
+        assert_eq!(
+            operation.string("printer-uri"),
+            "ipp://localhost/printers/MockPrinter"
+        );
+    }
+
+    #[test]
+    fn test_supplies() {
+        let responder = responder();
+
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+        let supplies = printer.supplies().unwrap();
+
+        assert_eq!(
+            supplies,
+            vec![
+                Supply {
+                    name: "Black Toner".into(),
+                    color: "#000000".into(),
+                    level: SupplyLevel::Percent(80),
+                    low_level: Some(10),
+                    high_level: Some(100),
+                    supply_type: SupplyType::Toner,
+                },
+                Supply {
+                    name: "Cyan Toner".into(),
+                    color: "#00FFFF".into(),
+                    level: SupplyLevel::SomeRemaining,
+                    low_level: Some(10),
+                    high_level: Some(100),
+                    supply_type: SupplyType::Toner,
+                },
+                Supply {
+                    name: "Waste Toner Box".into(),
+                    color: "none".into(),
+                    level: SupplyLevel::Unknown,
+                    low_level: None,
+                    high_level: Some(90),
+                    supply_type: SupplyType::WasteToner,
+                },
+            ]
+        );
+
+        let requests = responder.received(IppOperation::GetPrinterAttributes);
+        assert!(requests.iter().any(|r| {
+            r.group(IppGroupTag::Operation)
+                .unwrap()
+                .strings("requested-attributes")
+                .contains(&"marker-levels".to_string())
+        }));
+    }
+
+    #[test]
+    fn test_print_typed_options() {
+        let responder = responder();
+
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+        let result = printer.print(
+            b"typed options",
+            PrinterJobOptions {
+                name: Some("typed options"),
+                raw_properties: &[
+                    ("document-format", "text/plain"),
+                    ("media", "na_letter_8.5x11in"),
+                ],
+                copies: Some(2),
+                sides: Some(PrintSides::TwoSidedShortEdge),
+                media: Some("iso_a4_210x297mm"),
+                orientation: Some(PrintOrientation::Landscape),
+                page_ranges: &[1..=3, 5..=5],
+                number_up: Some(2),
+                quality: Some(PrintQuality::High),
+                color_mode: Some(PrintColorMode::Monochrome),
+                collate: Some(true),
+                priority: Some(80),
+                ..PrinterJobOptions::none()
+            },
+        );
+
+        assert_eq!(result.unwrap().id, 7);
+
+        // libcups submits with Create-Job and Send-Document
+        let request = responder
+            .requests
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|r| {
+                [IppOperation::PrintJob, IppOperation::CreateJob].contains(&r.operation())
+                    && r.group(IppGroupTag::Operation).unwrap().string("job-name")
+                        == "typed options"
+            })
+            .cloned()
+            .unwrap();
+        let job = request.group(IppGroupTag::Job).unwrap();
+
+        assert_eq!(job.value("copies"), Some(&IppValue::Integer(2)));
+        assert_eq!(job.string("sides"), "two-sided-short-edge");
+        assert_eq!(job.strings("media"), vec!["na_letter_8.5x11in"]);
+        assert_eq!(job.value("orientation-requested"), Some(&IppValue::Enum(4)));
+        assert_eq!(
+            job.get("page-ranges").unwrap().values,
+            vec![
+                IppValue::RangeOfInteger { lower: 1, upper: 3 },
+                IppValue::RangeOfInteger { lower: 5, upper: 5 },
+            ]
+        );
+        assert_eq!(job.value("number-up"), Some(&IppValue::Integer(2)));
+        assert_eq!(job.value("print-quality"), Some(&IppValue::Enum(5)));
+        assert_eq!(job.string("print-color-mode"), "monochrome");
+        assert_eq!(
+            job.string("multiple-document-handling"),
+            "separate-documents-collated-copies"
+        );
+        assert_eq!(job.value("job-priority"), Some(&IppValue::Integer(80)));
+    }
+
+    #[test]
+    fn test_print_stream() {
+        let responder = responder();
+
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+        let reader = io::repeat(b'x').take(200_000).chain(&b"end of stream"[..]);
+        let job = printer
+            .print_stream(
+                reader,
+                PrinterJobOptions {
+                    name: Some("streamed"),
+                    raw_properties: &[("document-format", "text/plain")],
+                    ..PrinterJobOptions::none()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(job.id, 7);
+
+        // cups sends the data with Send-Document, the ipp client with Print-Job
+        let requests = responder.requests.lock().unwrap();
+        let request = requests
+            .iter()
+            .filter(|r| {
+                [IppOperation::PrintJob, IppOperation::SendDocument].contains(&r.operation())
+            })
+            .find(|r| r.data.ends_with(b"end of stream"))
+            .unwrap();
+
+        assert_eq!(request.data.len(), 200_013);
+        assert_eq!(
+            request
+                .group(IppGroupTag::Operation)
+                .unwrap()
+                .string("document-format"),
+            "text/plain"
+        );
+    }
+
+    #[test]
+    fn test_create_job() {
+        let responder = responder();
+
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+        let mut job = printer
+            .create_job(PrinterJobOptions {
+                name: Some("invoice batch"),
+                ..PrinterJobOptions::none()
+            })
+            .unwrap();
+
+        job.add_bytes(b"cover letter", Some("text/plain"))
+            .unwrap()
+            .add_reader(&b"%PDF-1.7 invoice"[..], Some("application/pdf"))
+            .unwrap();
+
+        assert_eq!(job.close().unwrap().id, 7);
+
+        let documents: Vec<(String, String, bool)> = responder
+            .received(IppOperation::SendDocument)
+            .iter()
+            .map(|r| {
+                let operation = r.group(IppGroupTag::Operation).unwrap();
+                (
+                    operation.string("document-name"),
+                    operation.string("document-format"),
+                    operation
+                        .value("last-document")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or_default(),
+                )
+            })
+            .filter(|(name, _, _)| name.starts_with("invoice batch"))
+            .collect();
+
+        assert_eq!(
+            documents,
+            vec![
+                ("invoice batch (1)".into(), "text/plain".into(), false),
+                ("invoice batch (2)".into(), "application/pdf".into(), false),
+                (
+                    "invoice batch".into(),
+                    "application/octet-stream".into(),
+                    true
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_create_job_dropped() {
+        let responder = responder();
+
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+        let job = printer
+            .create_job(PrinterJobOptions {
+                name: Some("abandoned"),
+                ..PrinterJobOptions::none()
+            })
+            .unwrap();
+        drop(job);
+
+        assert!(!responder.received(IppOperation::CancelJob).is_empty());
+    }
+
+    #[test]
+    fn test_queue_admin() {
+        let responder = responder();
+
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+        printer.pause().unwrap();
+        printer.resume().unwrap();
+        printer.reject_jobs(Some("Out for maintenance")).unwrap();
+        printer.accept_jobs().unwrap();
+        printer.set_state_message("Back in service").unwrap();
+
+        for operation in [
+            IppOperation::PausePrinter,
+            IppOperation::ResumePrinter,
+            IppOperation::CupsAcceptJobs,
+        ] {
+            assert_eq!(responder.received(operation).len(), 1);
+        }
+
+        let rejected = responder.received(IppOperation::CupsRejectJobs);
+        assert_eq!(
+            rejected[0]
+                .group(IppGroupTag::Operation)
+                .unwrap()
+                .string("printer-state-message"),
+            "Out for maintenance"
+        );
+
+        let modified = responder.received(IppOperation::CupsAddModifyPrinter);
+        assert!(modified.iter().any(|r| {
+            r.group(IppGroupTag::Printer)
+                .unwrap()
+                .string("printer-state-message")
+                == "Back in service"
+        }));
+
+        let mut missing = printer.clone();
+        missing.system_name = "Missing".into();
+        assert!(missing.pause().is_err());
+    }
+
+    #[test]
+    fn test_admin_printers() {
+        let responder = responder();
+
+        let printer = add_printer(
+            "Provisioned",
+            PrinterConfig {
+                device_uri: Some("ipp://10.0.0.5/ipp/print"),
+                model: Some("everywhere"),
+                location: Some("Floor 2"),
+                default_options: &[("media", "iso_a4_210x297mm")],
+                ..PrinterConfig::none()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(printer.system_name, "Provisioned");
+        assert_eq!(printer.location, "Floor 2");
+
+        let requests = responder.received(IppOperation::CupsAddModifyPrinter);
+        let attributes = requests
+            .iter()
+            .filter_map(|r| r.group(IppGroupTag::Printer))
+            .find(|g| g.string("ppd-name") == "everywhere")
+            .unwrap();
+        assert_eq!(attributes.string("device-uri"), "ipp://10.0.0.5/ipp/print");
+        assert_eq!(attributes.string("media-default"), "iso_a4_210x297mm");
+        assert_eq!(
+            attributes.value("printer-is-accepting-jobs"),
+            Some(&IppValue::Boolean(true))
+        );
+
+        assert!(
+            add_printer(
+                "Provisioned",
+                PrinterConfig {
+                    device_uri: Some("socket://10.0.0.6"),
+                    ..PrinterConfig::none()
+                }
+            )
+            .is_err()
+        );
+
+        let printer = modify_printer(
+            "Provisioned",
+            PrinterConfig {
+                location: Some("Floor 3"),
+                ..PrinterConfig::none()
+            },
+        )
+        .unwrap();
+        assert_eq!(printer.location, "Floor 3");
+
+        delete_printer("Provisioned").unwrap();
+        assert!(get_printer_by_name("Provisioned").is_none());
+        assert!(modify_printer("Provisioned", PrinterConfig::none()).is_err());
+    }
+
+    #[test]
+    fn test_instances() {
+        let responder = responder();
+
+        lpoptions::set_options(
+            PrinterScope::User,
+            "MockPrinter",
+            Some("duplex"),
+            &[
+                ("sides", "two-sided-long-edge"),
+                ("job-sheets", "none none"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            lpoptions::get_options(PrinterScope::User, "MockPrinter", Some("duplex")).unwrap(),
+            vec![
+                ("sides".to_string(), "two-sided-long-edge".to_string()),
+                ("job-sheets".to_string(), "none none".to_string()),
+            ]
+        );
+
+        let printer = get_printer_by_name("MockPrinter/duplex").unwrap();
+        assert_eq!(printer.system_name, "MockPrinter");
+        assert_eq!(printer.instance.as_deref(), Some("duplex"));
+        assert_eq!(printer.destination_name(), "MockPrinter/duplex");
+        assert!(
+            get_printer_by_name("MockPrinter")
+                .unwrap()
+                .instance
+                .is_none()
+        );
+
+        printer
+            .print(
+                b"instance",
+                PrinterJobOptions {
+                    name: Some("instance job"),
+                    ..PrinterJobOptions::none()
+                },
+            )
+            .unwrap();
+
+        let requests = responder.requests.lock().unwrap().clone();
+        let request = requests
+            .iter()
+            .find(|r| {
+                r.group(IppGroupTag::Operation)
+                    .is_some_and(|g| g.string("job-name") == "instance job")
+            })
+            .unwrap();
+        assert_eq!(
+            request.group(IppGroupTag::Job).unwrap().string("sides"),
+            "two-sided-long-edge"
+        );
+
+        set_default_printer("MockPrinter/duplex", PrinterScope::User).unwrap();
+        assert!(
+            lpoptions::read(PrinterScope::User)
+                .unwrap()
+                .iter()
+                .any(|d| d.is_default && d.instance.as_deref() == Some("duplex"))
+        );
+        assert_eq!(
+            get_default_printer().and_then(|p| p.instance),
+            Some("duplex".to_string())
+        );
+
+        set_default_printer("MockPrinter", PrinterScope::System).unwrap();
+        assert_eq!(responder.received(IppOperation::CupsSetDefault).len(), 1);
+        assert!(set_default_printer("MockPrinter/duplex", PrinterScope::System).is_err());
+        assert!(set_default_printer("Missing", PrinterScope::User).is_err());
+
+        lpoptions::remove(PrinterScope::User, "MockPrinter", Some("duplex")).unwrap();
+        assert!(get_printer_by_name("MockPrinter/duplex").is_none());
+    }
+
+    #[test]
+    fn test_move_jobs() {
+        let responder = responder();
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+
+        printer.move_job(7, "OtherPrinter").unwrap();
+        printer.move_all_jobs("OtherPrinter/duplex").unwrap();
+
+        let requests = responder.received(IppOperation::CupsMoveJob);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            requests[0]
+                .group(IppGroupTag::Operation)
+                .unwrap()
+                .integer("job-id"),
+            Some(7)
+        );
+        assert_eq!(
+            requests[1]
+                .group(IppGroupTag::Operation)
+                .unwrap()
+                .integer("job-id"),
+            None
+        );
+        assert_eq!(
+            requests[1]
+                .group(IppGroupTag::Job)
+                .unwrap()
+                .string("job-printer-uri"),
+            "ipp://localhost/printers/OtherPrinter"
+        );
+
+        let error = printer.move_all_jobs("RejectingPrinter").unwrap_err();
+        assert_eq!(error.failure, PrintersFailure::NotAcceptingJobs);
+        assert_eq!(
+            error.status,
+            Some(IppStatusCode::ServerErrorNotAcceptingJobs)
+        );
+        let error = printer.move_job(8, "OtherPrinter").unwrap_err();
+        assert_eq!(error.failure, PrintersFailure::JobFailure);
+        assert_eq!(error.status, Some(IppStatusCode::ClientErrorNotFound));
+        assert_eq!(error.code, Some(0x0406));
+    }
+
+    #[test]
+    fn test_job_document() {
+        responder();
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+
+        let mut document = printer.get_job_document(7, 1).unwrap();
+        assert_eq!(document.format, "text/plain");
+        assert_eq!(document.name.as_deref(), Some("report.txt"));
+
+        let mut data = Vec::new();
+        document.read_to_end(&mut data).unwrap();
+        assert_eq!(data, b"quarterly report".repeat(1024));
+
+        let error = printer.get_job_document(7, 2).unwrap_err();
+        assert_eq!(error.failure, PrintersFailure::JobFailure);
+    }
+
+    #[test]
+    fn test_update_job() {
+        let responder = responder();
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+
+        printer
+            .update_job(
+                7,
+                JobChanges {
+                    name: Some("batch report"),
+                    priority: Some(120),
+                    hold_until: Some(JobHoldUntil::Night),
+                    copies: Some(3),
+                    page_ranges: &[2..=4],
+                },
+            )
+            .unwrap();
+        printer
+            .update_job(
+                7,
+                JobChanges {
+                    hold_until: Some(JobHoldUntil::Time {
+                        hour: 22,
+                        minute: 5,
+                    }),
+                    ..JobChanges::none()
+                },
+            )
+            .unwrap();
+
+        let requests: Vec<_> = responder
+            .received(IppOperation::SetJobAttributes)
+            .into_iter()
+            .filter(|r| requesting_user_name(r) != "busy")
+            .collect();
+        let job = requests[0].group(IppGroupTag::Job).unwrap();
+        assert_eq!(job.string("job-name"), "batch report");
+        assert_eq!(job.integer("job-priority"), Some(100));
+        assert_eq!(job.integer("copies"), Some(3));
+        assert_eq!(
+            job.get("job-hold-until").unwrap().values,
+            vec![IppValue::Keyword("night".into())]
+        );
+        assert_eq!(
+            job.get("page-ranges").unwrap().values,
+            vec![IppValue::RangeOfInteger { lower: 2, upper: 4 }]
+        );
+
+        let job = requests[1].group(IppGroupTag::Job).unwrap();
+        assert_eq!(job.attributes.len(), 1);
+        assert_eq!(
+            job.get("job-hold-until").unwrap().values,
+            vec![IppValue::Name("22:05".into())]
+        );
+
+        let error = printer
+            .update_job(
+                9,
+                JobChanges {
+                    copies: Some(2),
+                    ..JobChanges::none()
+                },
+            )
+            .unwrap_err();
+        assert_eq!(error.failure, PrintersFailure::JobFailure);
+        assert!(error.message.contains("client-error-not-possible"));
+        assert_eq!(error.status, Some(IppStatusCode::ClientErrorNotPossible));
+        assert_eq!(error.operation.as_deref(), Some("Set-Job-Attributes"));
+        assert_eq!(
+            error.to_string(),
+            "Set-Job-Attributes failed with client-error-not-possible: \
+            Job #9 is finished and cannot be altered."
+        );
+
+        assert_eq!(
+            JobHoldUntil::from_keyword("weekend"),
+            Some(JobHoldUntil::Weekend)
+        );
+        assert_eq!(
+            JobHoldUntil::from_keyword("07:30:00"),
+            Some(JobHoldUntil::Time {
+                hour: 7,
+                minute: 30
+            })
+        );
+        assert_eq!(JobHoldUntil::from_keyword("25:00"), None);
+    }
+
+    #[test]
+    fn test_cancel_all_jobs() {
+        let responder = responder();
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+
+        // The completed job of the mock is only affected by a purge
+        assert_eq!(
+            printer.cancel_all_jobs(CancelJobsFilter::All).unwrap(),
+            vec![11]
+        );
+        assert_eq!(
+            printer
+                .purge_all_jobs(CancelJobsFilter::User("alice"))
+                .unwrap(),
+            vec![7, 11]
+        );
+        // Nothing matches, no request is sent
+        assert!(
+            printer
+                .purge_all_jobs(CancelJobsFilter::User("bob"))
+                .unwrap()
+                .is_empty()
+        );
+        // The jobs of the mock belong to alice
+        let (host, port) = responder.address.rsplit_once(':').unwrap();
+        let alice = PrintServer {
+            port: port.parse().unwrap(),
+            user_name: Some("alice".into()),
+            ..PrintServer::new(host)
+        };
+        assert_eq!(
+            alice
+                .get_printer_by_name("MockPrinter")
+                .unwrap()
+                .cancel_all_jobs(CancelJobsFilter::Mine)
+                .unwrap(),
+            vec![11]
+        );
+
+        let cancelled = responder.received(IppOperation::CancelJobs);
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(responder.received(IppOperation::CancelMyJobs).len(), 1);
+
+        // Only the reported jobs are cancelled, not the ones submitted after the listing
+        let job_ids = |request: &IppMessage| -> Vec<i32> {
+            request
+                .group(IppGroupTag::Operation)
+                .and_then(|g| g.get("job-ids"))
+                .map(|a| a.values.iter().filter_map(|v| v.as_i32()).collect())
+                .unwrap_or_default()
+        };
+        assert_eq!(job_ids(&cancelled[0]), vec![11]);
+
+        let purged = responder.received(IppOperation::PurgeJobs);
+        assert_eq!(purged.len(), 1);
+        assert_eq!(job_ids(&purged[0]), vec![7, 11]);
+        // The jobs of another user are cancelled as the current user, not by posing as the owner
+        let operation = purged[0].group(IppGroupTag::Operation).unwrap();
+        assert_ne!(operation.string("requesting-user-name"), "alice");
+        assert!(operation.get("my-jobs").is_none());
+
+        // Jobs that cannot be listed are not reported as nothing to cancel
+        let locked = Printer {
+            system_name: "Locked".into(),
+            ..printer.clone()
+        };
+        let error = locked.cancel_all_jobs(CancelJobsFilter::All).unwrap_err();
+        assert_eq!(error.status, Some(IppStatusCode::ClientErrorForbidden));
+        assert_eq!(responder.received(IppOperation::CancelJobs).len(), 1);
+
+        let error = get_printer_by_name("MockPrinter")
+            .map(|p| Printer {
+                system_name: "Missing".into(),
+                ..p
+            })
+            .unwrap()
+            .cancel_all_jobs(CancelJobsFilter::All)
+            .unwrap_err();
+        assert_eq!(error.failure, PrintersFailure::JobFailure);
+    }
+
+    #[test]
+    fn test_job_handle() {
+        let responder = responder();
+
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+        let mut job = printer.print(b"handle", PrinterJobOptions::none()).unwrap();
+
+        assert_eq!(job.id, 7);
+        assert_eq!(job.printer_name, "MockPrinter");
+        assert_eq!(job.status(), PrinterJobState::PENDING);
+
+        assert!(job.pause().is_ok());
+        assert!(job.resume().is_ok());
+        assert!(job.cancel().is_ok());
+        assert_eq!(responder.received(IppOperation::HoldJob).len(), 1);
+
+        let state = job.wait_until_done(Duration::from_secs(5)).unwrap();
+        assert_eq!(state, PrinterJobState::COMPLETED);
+        assert_eq!(job.status(), PrinterJobState::COMPLETED);
+    }
+
+    #[test]
+    fn test_held_job() {
+        let responder = responder();
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+
+        printer
+            .print(
+                b"held",
+                PrinterJobOptions {
+                    name: Some("held job"),
+                    hold_until: Some(JobHoldUntil::Night),
+                    ..PrinterJobOptions::none()
+                },
+            )
+            .unwrap();
+
+        let requests = responder.requests.lock().unwrap().clone();
+        let request = requests
+            .iter()
+            .find(|r| {
+                r.group(IppGroupTag::Operation)
+                    .is_some_and(|g| g.string("job-name") == "held job")
+            })
+            .unwrap();
+        assert_eq!(
+            request
+                .group(IppGroupTag::Job)
+                .unwrap()
+                .string("job-hold-until"),
+            "night"
+        );
+
+        let job = printer.get_job(11).unwrap();
+        assert_eq!(job.state, PrinterJobState::PAUSED);
+        assert_eq!(job.hold_until, Some(JobHoldUntil::Night));
+        assert_eq!(printer.get_job(7).unwrap().hold_until, None);
+
+        printer.release_job(11).unwrap();
+        assert!(
+            responder
+                .received(IppOperation::ReleaseJob)
+                .iter()
+                .any(|r| r.group(IppGroupTag::Operation).unwrap().integer("job-id") == Some(11))
+        );
+    }
+
+    #[test]
+    fn test_get_printer_jobs() {
+        let responder = responder();
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+
+        let jobs = printer.get_job_history();
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(
+            jobs[0].state_reasons,
+            vec![PrinterJobStateReason::JobCompletedSuccessfully]
+        );
+        assert_eq!(jobs[0].hold_until, None);
+
+        let job = &jobs[1];
+        assert_eq!(job.id, 11);
+        assert_eq!(job.state, PrinterJobState::PAUSED);
+        assert_eq!(
+            job.state_reasons,
+            vec![PrinterJobStateReason::JobPasswordWait]
+        );
+        assert_eq!(job.printer_state_message, "Printing page 2");
+        assert_eq!(job.impressions_completed, 4);
+        assert_eq!(job.pages_completed, 2);
+        assert_eq!(job.document_count, 1);
+        assert_eq!(job.hold_until, Some(JobHoldUntil::Indefinite));
+
+        let request = responder.received(IppOperation::GetJobs).pop().unwrap();
+        let operation = request.group(IppGroupTag::Operation).unwrap();
+        assert!(
+            operation
+                .strings("requested-attributes")
+                .contains(&"job-hold-until".to_string())
+        );
+
+        // The queue name is percent-encoded in the printer-uri and decoded from the job-printer-uri
+        let team = Printer {
+            system_name: "Team Printer #2".into(),
+            ..printer
+        };
+        let jobs = team.get_active_jobs();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].id, 21);
+        assert_eq!(jobs[0].printer_name, "Team Printer #2");
+    }
+
+    #[test]
+    fn test_secure_release() {
+        let responder = responder();
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+
+        printer
+            .print(
+                b"payroll",
+                PrinterJobOptions {
+                    name: Some("payroll slip"),
+                    job_password: Some(JobPassword::new("1234")),
+                    ..PrinterJobOptions::none()
+                },
+            )
+            .unwrap();
+
+        let password = JobPassword::new("1234");
+        assert_eq!(
+            format!("{password:?}"),
+            "JobPassword { password: \"***\", encryption: None }"
+        );
+
+        let requests = responder.requests.lock().unwrap().clone();
+        let request = requests
+            .iter()
+            .find(|r| {
+                r.group(IppGroupTag::Operation)
+                    .is_some_and(|g| g.string("job-name") == "payroll slip")
+            })
+            .unwrap();
+        let operation = request.group(IppGroupTag::Operation).unwrap();
+        assert_eq!(
+            operation.get("job-password").unwrap().values,
+            vec![IppValue::OctetString(b"1234".to_vec())]
+        );
+        assert_eq!(operation.string("job-password-encryption"), "none");
+        assert_eq!(
+            request
+                .group(IppGroupTag::Job)
+                .unwrap()
+                .string("job-hold-until"),
+            "indefinite"
+        );
+
+        let held = printer.get_held_jobs("alice");
+        assert_eq!(held.len(), 1);
+        assert_eq!(held[0].id, 11);
+        assert!(printer.get_held_jobs("bob").is_empty());
+
+        let outcome = printer.release_held_jobs("alice").unwrap();
+        assert_eq!(outcome.released, vec![11]);
+        assert!(outcome.failed.is_empty());
+        assert!(
+            responder
+                .received(IppOperation::ReleaseJob)
+                .iter()
+                .any(|r| r.group(IppGroupTag::Operation).unwrap().integer("job-id") == Some(11))
+        );
+
+        // Jobs held until a time are left alone, a failed release does not stop the others
+        let held = Printer {
+            system_name: "Held".into(),
+            ..printer.clone()
+        };
+        let ids: Vec<u64> = held.get_held_jobs("alice").iter().map(|j| j.id).collect();
+        assert_eq!(ids, vec![11, 15]);
+        let outcome = held.release_held_jobs("alice").unwrap();
+        assert_eq!(outcome.released, vec![11]);
+        assert_eq!(outcome.failed.len(), 1);
+        assert_eq!(outcome.failed[0].0, 15);
+        assert_eq!(
+            outcome.failed[0].1.status,
+            Some(IppStatusCode::ClientErrorNotFound)
+        );
+
+        let locked = Printer {
+            system_name: "Locked".into(),
+            ..printer
+        };
+        let error = locked.release_held_jobs("alice").unwrap_err();
+        assert_eq!(error.status, Some(IppStatusCode::ClientErrorForbidden));
+    }
+
+    #[test]
+    fn test_get_job() {
+        responder();
+
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+        let job = printer.get_job(7).unwrap();
+
+        assert_eq!(job.id, 7);
+        assert_eq!(job.state, PrinterJobState::COMPLETED);
+        assert_eq!(job.printer_name, "MockPrinter");
+        assert_eq!(job.owner, "alice");
+        assert_eq!(job.size, 3072);
+        assert_eq!(job.priority, 50);
+        assert_eq!(
+            job.state_reasons,
+            vec![
+                PrinterJobStateReason::JobCompletedSuccessfully,
+                PrinterJobStateReason::Other("vendor-reason".into())
+            ]
+        );
+        assert_eq!(job.printer_state_message, "Printing page 2");
+        assert_eq!(job.impressions_completed, 4);
+        assert_eq!(job.pages_completed, 2);
+        assert_eq!(job.document_count, 1);
+
+        let aborted = printer.get_job(9).unwrap();
+        assert_eq!(aborted.state, PrinterJobState::ABORTED);
+        assert_eq!(
+            aborted.state_reasons,
+            vec![PrinterJobStateReason::AbortedBySystem]
+        );
+
+        assert!(printer.get_job(8).is_none());
+        assert!(get_job(7).is_some_and(|j| j.owner == "alice"));
+        assert!(get_job(8).is_none());
+    }
+
+    #[test]
+    fn test_print_server() {
+        let responder = responder();
+        let (host, port) = responder.address.rsplit_once(':').unwrap();
+        let server = PrintServer {
+            port: port.parse().unwrap(),
+            user_name: Some("operator".into()),
+            ..PrintServer::new(host)
+        };
+
+        let printer = server.get_printer_by_name("MockPrinter").unwrap();
+        assert_eq!(printer.server.as_ref(), Some(&server));
+        assert!(get_printer_by_name("MockPrinter").unwrap().server.is_none());
+
+        printer
+            .print(
+                b"remote",
+                PrinterJobOptions {
+                    name: Some("remote job"),
+                    ..PrinterJobOptions::none()
+                },
+            )
+            .unwrap();
+        printer.release_job(11).unwrap();
+
+        let requests = responder.requests.lock().unwrap().clone();
+        let operation = requests
+            .iter()
+            .filter_map(|r| r.group(IppGroupTag::Operation))
+            .find(|g| g.string("job-name") == "remote job")
+            .unwrap();
+        assert_eq!(operation.string("requesting-user-name"), "operator");
+
+        // Uris built by the lib address the server instead of localhost
+        let released = responder.received(IppOperation::ReleaseJob);
+        let operation = released
+            .iter()
+            .filter_map(|r| r.group(IppGroupTag::Operation))
+            .find(|g| g.string("requesting-user-name") == "operator")
+            .unwrap();
+        assert_eq!(
+            operation.string("printer-uri"),
+            format!("ipp://{}/printers/MockPrinter", responder.address)
+        );
+        assert!(server.get_job(7).is_some());
+
+        let unreachable = PrintServer {
+            port: 1,
+            timeout: Duration::from_secs(1),
+            ..PrintServer::new("127.0.0.1")
+        };
+        assert!(unreachable.get_printers().is_empty());
+        assert!(unreachable.get_printer_by_name("MockPrinter").is_none());
+    }
+
+    #[test]
+    fn test_authentication() {
+        responder();
+        // admin:secret
+        let protected = Responder::start_protected(handle, "/admin/", "Basic YWRtaW46c2VjcmV0");
+        let (host, port) = protected.address.rsplit_once(':').unwrap();
+        let printer = |credentials| {
+            PrintServer {
+                port: port.parse().unwrap(),
+                credentials,
+                ..PrintServer::new(host)
+            }
+            .get_printer_by_name("MockPrinter")
+            .unwrap()
+        };
+
+        let error = printer(None).pause().unwrap_err();
+        assert_eq!(error.failure, PrintersFailure::AuthenticationRequired);
+
+        let error = printer(Some(Credentials::new("admin", "wrong")))
+            .pause()
+            .unwrap_err();
+        assert_eq!(error.failure, PrintersFailure::AuthenticationRequired);
+        assert!(protected.received(IppOperation::PausePrinter).is_empty());
+
+        printer(Some(Credentials::new("admin", "secret")))
+            .pause()
+            .unwrap();
+        assert_eq!(protected.received(IppOperation::PausePrinter).len(), 1);
+
+        // A callback is asked again when its credentials are rejected
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let asked = requests.clone();
+        let credentials = Credentials::callback(move |request| {
+            asked
+                .lock()
+                .unwrap()
+                .push((request.resource.to_string(), request.attempt));
+            let password = if request.attempt == 1 {
+                "wrong"
+            } else {
+                "secret"
+            };
+            Some(("admin".into(), password.into()))
+        });
+        printer(Some(credentials)).resume().unwrap();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec![("/admin/".to_string(), 1), ("/admin/".to_string(), 2)]
+        );
+        assert_eq!(protected.received(IppOperation::ResumePrinter).len(), 1);
+    }
+
+    #[test]
+    fn test_error_details() {
+        responder();
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+
+        // Converters read the file before any request
+        let error = printer
+            .print_file(
+                "/missing/document.pdf",
+                PrinterJobOptions {
+                    converter: Converter::Ghostscript(GhostscriptConverterOptions::ps2write()),
+                    ..PrinterJobOptions::none()
+                },
+            )
+            .unwrap_err();
+        assert_eq!(error.failure, PrintersFailure::FileFailure);
+        assert_eq!(error.code, Some(2));
+        assert!(
+            error
+                .source()
+                .and_then(|e| e.downcast_ref::<io::Error>())
+                .is_some_and(|e| e.kind() == io::ErrorKind::NotFound)
+        );
+
+        // The error works with the error handling of the std
+        let error: Box<dyn Error> = Box::new(printer.get_job_document(8, 1).unwrap_err());
+        assert!(!error.to_string().is_empty());
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        let responder = responder();
+        let (host, port) = responder.address.rsplit_once(':').unwrap();
+        let server = PrintServer {
+            port: port.parse().unwrap(),
+            user_name: Some("busy".into()),
+            ..PrintServer::new(host)
+        };
+        let printer = server.get_printer_by_name("MockPrinter").unwrap();
+
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(10),
+            ..RetryPolicy::new(3)
+        };
+        let options = || PrinterJobOptions {
+            retry: Some(policy.clone()),
+            ..PrinterJobOptions::none()
+        };
+        let set_busy = |count| *BUSY.lock().unwrap() = count;
+
+        set_busy(2);
+        let job = printer.print(b"busy", options()).unwrap();
+        assert_eq!(*BUSY.lock().unwrap(), 0);
+
+        // The policy of the submission also covers the control of the job
+        set_busy(1);
+        job.pause().unwrap();
+        let held = responder.received(IppOperation::HoldJob);
+        assert_eq!(
+            held.iter()
+                .filter(|r| requesting_user_name(r) == "busy")
+                .count(),
+            2
+        );
+
+        // The policy of the printer covers the control of any of its jobs
+        let cancelled = |responder: &Responder| {
+            responder
+                .received(IppOperation::CancelJob)
+                .iter()
+                .filter(|r| requesting_user_name(r) == "busy")
+                .count()
+        };
+        set_busy(1);
+        let before = cancelled(responder);
+        assert!(printer.cancel_job(7).is_err());
+        assert_eq!(cancelled(responder), before + 1);
+
+        set_busy(1);
+        let retried = Printer {
+            retry: Some(policy.clone()),
+            ..printer.clone()
+        };
+        retried.cancel_job(7).unwrap();
+        assert_eq!(cancelled(responder), before + 3);
+
+        set_busy(1);
+        let changes = JobChanges {
+            priority: Some(60),
+            ..JobChanges::none()
+        };
+        retried.update_job(7, changes).unwrap();
+        assert_eq!(*BUSY.lock().unwrap(), 0);
+
+        set_busy(5);
+        let error = printer.print(b"busy", options()).unwrap_err();
+        assert_eq!(error.attempts, 3);
+        assert_eq!(error.status, Some(IppStatusCode::ServerErrorBusy));
+        assert!(error.message.ends_with("(after 3 attempts)"));
+
+        // Without a policy, or for failures it does not cover, the first error is returned
+        set_busy(1);
+        let error = printer
+            .print(b"busy", PrinterJobOptions::none())
+            .unwrap_err();
+        assert_eq!(error.attempts, 1);
+        assert!(!error.message.contains("attempts"));
+
+        set_busy(1);
+        let error = printer
+            .print(
+                b"busy",
+                PrinterJobOptions {
+                    retry: Some(RetryPolicy {
+                        retry_on: vec![RetryClass::NotAcceptingJobs],
+                        ..policy.clone()
+                    }),
+                    ..PrinterJobOptions::none()
+                },
+            )
+            .unwrap_err();
+        assert_eq!(error.attempts, 1);
+
+        // No attempt is started past the deadline
+        set_busy(5);
+        let error = printer
+            .print(
+                b"busy",
+                PrinterJobOptions {
+                    retry: Some(RetryPolicy {
+                        initial_backoff: Duration::from_secs(1),
+                        deadline: Some(Duration::from_millis(500)),
+                        ..policy.clone()
+                    }),
+                    ..PrinterJobOptions::none()
+                },
+            )
+            .unwrap_err();
+        assert_eq!(error.attempts, 1);
+
+        // A stream is not sent again once read
+        set_busy(1);
+        let data = b"stream".repeat(1024);
+        if let Err(error) = printer.print_stream(data.as_slice(), options()) {
+            assert_eq!(error.attempts, 1);
+        }
+        set_busy(0);
+
+        // A lost connection may come after the server received the job, only control is retried
+        let timed_out = PrintersError::from(io::Error::from(ErrorKind::TimedOut));
+        assert!(policy.is_retryable(&timed_out));
+        assert!(!policy.is_retryable_submission(&timed_out));
+
+        // A submission that never reached the server is sent again
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let unreachable = PrintServer {
+            port,
+            ..PrintServer::new("127.0.0.1")
+        };
+        let printer = Printer {
+            server: Some(unreachable),
+            ..printer
+        };
+        let error = printer.print(b"unreachable", options()).unwrap_err();
+        assert_eq!(error.attempts, 3);
+    }
+
+    #[test]
+    fn test_aborted_job() {
+        responder();
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+        let mut job = printer
+            .print(
+                b"aborted",
+                PrinterJobOptions {
+                    name: Some("aborted job"),
+                    ..PrinterJobOptions::none()
+                },
+            )
+            .unwrap();
+        assert_eq!(job.id, 9);
+
+        // An aborted job is done, it is not polled until the timeout
+        let start = Instant::now();
+        let state = job.wait_until_done(Duration::from_secs(10)).unwrap();
+        assert_eq!(state, PrinterJobState::ABORTED);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_refresh_errors() {
+        responder();
+        let printer = get_printer_by_name("MockPrinter").unwrap();
+        let print = |name| {
+            printer
+                .print(
+                    b"refresh",
+                    PrinterJobOptions {
+                        name: Some(name),
+                        ..PrinterJobOptions::none()
+                    },
+                )
+                .unwrap()
+        };
+
+        // A failed lookup is an error, not a completed job
+        let mut job = print("failing job");
+        let error = job.refresh().unwrap_err();
+        assert_eq!(error.status, Some(IppStatusCode::ServerErrorInternalError));
+        assert_eq!(job.status(), PrinterJobState::PENDING);
+        assert!(job.wait_until_done(Duration::from_secs(5)).is_err());
+
+        // A job the server does not know anymore is not reported as completed
+        let mut job = print("vanished job");
+        assert_eq!(job.refresh().unwrap(), PrinterJobState::UNKNOWN);
+        assert_eq!(job.status(), PrinterJobState::UNKNOWN);
+    }
+}